@@ -14,6 +14,14 @@ void write_ascii(context_t *context, char*);
 
 i64 rand_i64(context_t *context, i64 lower, i64 higher);
 
+i64 size(context_t *context);
+
+void write_answer_nl(context_t *context);
+
+void write_answer_i64(context_t *context, i64);
+
+void write_answer_ascii(context_t *context, char*);
+
 template<class It>
 void write_i64_seq(context_t *context, It first, It end) {
 	while (first != end) write_i64(context, *first), first++;
@@ -0,0 +1,166 @@
+use std::{num::NonZeroUsize, sync::Mutex, time::Duration};
+
+use crate::{
+	Failure, Session,
+	args::{Args, DiffMode, Language},
+	error::Error,
+	prepare, watch,
+};
+
+/// Held while a session runs, as the settings of the report are shared by the whole process.
+static RUNNING: Mutex<()> = Mutex::new(());
+
+/// A fuzzing session set up from code rather than the command line, such as
+/// `FuzzSession::builder(Language::Cpp, "e").specification("spec").compare("brute")`.
+/// Every option of the command line is available through [`FuzzSessionBuilder::configure`].
+/// Sessions of the same process run one at a time.
+pub struct FuzzSession;
+
+impl FuzzSession {
+	/// A session of the solution, written in the language all its programs are written in.
+	pub fn builder(language: Language, name: &str) -> FuzzSessionBuilder {
+		let args = Args::new(language, name).map(|mut args| {
+			args.quiet = true;
+			args
+		});
+		FuzzSessionBuilder { args }
+	}
+}
+
+pub struct FuzzSessionBuilder {
+	/// The error of the defaults, if they are invalid, which is returned when the session runs.
+	args: Result<Args, Error>,
+}
+
+impl FuzzSessionBuilder {
+	/// The specification the tests are generated from, or with [`Self::generate`] the C++
+	/// generator.
+	pub fn specification(self, specification: &str) -> Self {
+		self.set(|args| args.specification = Some(specification.to_string()))
+	}
+
+	/// Generates the tests with the C++ generator given as the specification.
+	pub fn generate(self) -> Self {
+		self.set(|args| args.generate = true)
+	}
+
+	/// Compares the output of the solution with this solution. May be given several times, in
+	/// which case all outputs must agree.
+	pub fn compare(self, comparator: &str) -> Self {
+		self.set(|args| args.compare.push(comparator.to_string()))
+	}
+
+	/// Checks the output of the solution with this verifier. Combined with [`Self::compare`] the
+	/// output must both satisfy the verifier and agree with the comparators.
+	pub fn verify(self, verifier: &str) -> Self {
+		self.set(|args| args.verify = Some(verifier.to_string()))
+	}
+
+	/// Accepts numbers of the outputs which differ by at most this much.
+	pub fn float_eps(self, eps: f64) -> Self {
+		self.set(|args| args.float_eps = Some(eps))
+	}
+
+	pub fn diff_mode(self, mode: DiffMode) -> Self {
+		self.set(|args| args.diff_mode = mode)
+	}
+
+	pub fn max_tests(self, tests: u64) -> Self {
+		self.set(|args| args.max_tests = Some(tests))
+	}
+
+	pub fn jobs(self, jobs: NonZeroUsize) -> Self {
+		self.set(|args| args.jobs = jobs)
+	}
+
+	pub fn seed(self, seed: u64) -> Self {
+		self.set(|args| args.seed = Some(seed))
+	}
+
+	pub fn time_limit(self, limit: Duration) -> Self {
+		self.set(|args| args.time_limit = Some(limit))
+	}
+
+	/// The memory limit of the solution and the comparators in bytes.
+	pub fn memory_limit(self, bytes: u64) -> Self {
+		self.set(|args| args.memory_limit = Some(bytes))
+	}
+
+	/// Keeps the counterexamples as they were found instead of minimizing them.
+	pub fn no_minimize(self) -> Self {
+		self.set(|args| args.no_minimize = true)
+	}
+
+	/// Reports the progress of the session on stderr like the command line does, which is
+	/// silent by default.
+	pub fn report_progress(self) -> Self {
+		self.set(|args| args.quiet = false)
+	}
+
+	/// Sets the options without a method on the arguments of the session, as the command line
+	/// would. Unlike the command line, combinations of options which conflict or require another
+	/// are not rejected, so they are the caller's to get right.
+	pub fn configure(self, configure: impl FnOnce(&mut Args)) -> Self {
+		self.set(configure)
+	}
+
+	fn set(mut self, set: impl FnOnce(&mut Args)) -> Self {
+		if let Ok(args) = &mut self.args {
+			set(args);
+		}
+		self
+	}
+
+	/// Runs the session, returning the counterexamples found. A program which fails to build is
+	/// an error rather than the exit of the process. The specification is checked as on the
+	/// command line: it is required unless the inputs of a directory are run, and it or the source
+	/// of its generator must exist.
+	pub fn run(self) -> Result<Vec<Counterexample>, Error> {
+		let mut args = self.args?;
+		if args.specification.is_none() && args.spec_inline.is_none() && args.inputs.is_none() {
+			return Err(Error::Arguments(
+				"the specification is required, unless the inputs of a directory are run"
+					.to_string(),
+			));
+		}
+		let _running = RUNNING
+			.lock()
+			.unwrap_or_else(|poisoned| poisoned.into_inner());
+		watch::report_build_failures();
+		prepare(&mut args)?;
+		let session = Session::new(args)?;
+		let failures = session.find_failures()?;
+		Ok(failures.into_iter().map(Counterexample::new).collect())
+	}
+}
+
+/// A test the solution failed.
+pub struct Counterexample {
+	/// Identifies the kind of failure, as in the JSON report.
+	pub kind: &'static str,
+	pub message: String,
+	/// The seed the test was generated from, absent for saved inputs.
+	pub seed: Option<u64>,
+	pub input: Vec<u8>,
+	/// A smaller input failing the same way, unless minimization was disabled.
+	pub minimized: Option<Vec<u8>>,
+	pub output: Option<Vec<u8>>,
+	pub expected: Option<Vec<u8>>,
+	pub stderr: Vec<u8>,
+}
+
+impl Counterexample {
+	fn new(failure: Failure) -> Counterexample {
+		let status = &failure.outcome.status;
+		Counterexample {
+			kind: status.name(),
+			message: status.message(),
+			seed: failure.seed,
+			input: failure.test.input,
+			minimized: failure.minimized.map(|minimized| minimized.input),
+			output: failure.outcome.output,
+			expected: failure.outcome.expected,
+			stderr: failure.outcome.stderr,
+		}
+	}
+}
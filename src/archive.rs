@@ -0,0 +1,55 @@
+use std::{
+	io,
+	path::{Path, PathBuf},
+	time::SystemTime,
+};
+
+use crate::{Failure, Session, json::Json, output, report, resume};
+
+/// Where every counterexample found is kept, as the files of the session are overwritten by the
+/// next one found.
+const DIR: &str = ".cpfuzz/failures";
+
+/// Copies the saved files of the failure into a directory of its own, named by when it was found
+/// and its verdict such as `20240131-235959-wrong_answer`, next to `metadata.json` describing
+/// it. Returns the directory.
+pub fn archive(
+	session: &Session,
+	failure: &Failure,
+	saved: &report::Artifacts,
+) -> io::Result<PathBuf> {
+	let found = output::timestamp(SystemTime::now());
+	let name = format!("{found}-{}", failure.outcome.status.name());
+	std::fs::create_dir_all(DIR)?;
+	// Failures found within the same second are told apart by a number.
+	let mut dir = Path::new(DIR).join(&name);
+	let mut i = 1;
+	loop {
+		match std::fs::create_dir(&dir) {
+			Err(e) if e.kind() == io::ErrorKind::AlreadyExists => {
+				i += 1;
+				dir = Path::new(DIR).join(format!("{name}-{i}"));
+			}
+			result => break result?,
+		}
+	}
+	let mut archived = Vec::new();
+	for (kind, path) in saved {
+		let path = Path::new(path);
+		let copy = dir.join(path.file_name().expect("saved files have names"));
+		std::fs::copy(path, &copy)?;
+		archived.push((*kind, copy.to_string_lossy().into_owned()));
+	}
+	let metadata = Json::Object(vec![
+		("problem", session.args.name.as_str().into()),
+		("found", found.into()),
+		(
+			"arguments",
+			Json::Array(resume::current().into_iter().map(Json::from).collect()),
+		),
+		("session_seed", session.seed.to_string().into()),
+		("failure", report::failure_json(failure, &archived)),
+	]);
+	std::fs::write(dir.join("metadata.json"), format!("{metadata}\n"))?;
+	Ok(dir)
+}
@@ -1,3 +1,7 @@
+use std::{num::NonZeroUsize, path::PathBuf, time::Duration};
+
+use crate::{budget::parse_duration, error::Error};
+
 #[derive(clap::Parser, clap::ValueEnum, Clone, Copy, Debug)]
 pub enum Language {
 	Rust,
@@ -6,22 +10,851 @@ pub enum Language {
 	CppSanitize,
 }
 
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum DiffMode {
+	/// The outputs must be byte for byte identical.
+	Exact,
+	/// The outputs must consist of the same whitespace separated tokens.
+	Tokens,
+	/// The outputs must have the same lines, each consisting of the same tokens.
+	Lines,
+	/// Like lines, but the lines may be in any order.
+	UnorderedLines,
+	/// The outputs must consist of the same tokens in any order.
+	SortedTokens,
+}
+
+/// How the verifier is given the input and the output of the solution. Unless the protocol says
+/// otherwise, the verifier accepts the output by exiting with code zero.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum VerifyProtocol {
+	/// The input, a newline and the output are concatenated on stdin.
+	Concatenate,
+	/// The verifier is invoked as `VERIFIER <input>` with the path of a file containing the input,
+	/// and the output on stdin.
+	Files,
+	/// The verifier is a checker written with testlib.h, invoked as
+	/// `VERIFIER <input> <output> <answer>`. The answer is the output of the first comparator or the
+	/// answer of the generator, and otherwise the output itself. The exit codes of testlib decide.
+	Testlib,
+	/// The verifier is an output validator as Kattis, DOMjudge and the ICPC run them, invoked as
+	/// `VERIFIER <input> <answer> <feedback dir>` with the output on stdin and the answer as for
+	/// testlib. It accepts the output by exiting with code 42 and rejects it with 43, writing why to
+	/// judgemessage.txt in the feedback directory.
+	#[value(aliases = ["icpc", "domjudge"])]
+	Kattis,
+}
+
+/// How the interactor is given the input and reports its verdict.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum InteractorProtocol {
+	/// The input is written to the stdin of the interactor before the interaction, and the
+	/// interactor rejects the solution by exiting with a non-zero exit code.
+	Stdin,
+	/// The interactor is invoked as `INTERACTOR <input> <verdict>` with the path of a file
+	/// containing the input, and may write a verdict line to the verdict file: `OK`, `WA <message>`
+	/// for a wrong answer or `PV <message>` for a protocol violation. Without a verdict the exit
+	/// code decides.
+	Files,
+	/// The interactor is the output validator of an interactive problem as Kattis, DOMjudge and
+	/// the ICPC run them, invoked as `INTERACTOR <input> <answer> <feedback dir>`. The answer is
+	/// that of the generator, otherwise empty. It accepts the interaction by exiting with code 42
+	/// and rejects it with 43, writing why to judgemessage.txt in the feedback directory.
+	#[value(aliases = ["icpc", "domjudge"])]
+	Kattis,
+}
+
+/// The format of the report of the session, in addition to the human-readable report on stderr.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ReportFormat {
+	/// Only the human-readable report.
+	Human,
+	/// A JSON object with the verdict, the seed, the number of tests run and every reported
+	/// failure with the paths of its saved files and the resource usage of the programs.
+	Json,
+	/// GitHub Actions annotations of the failures, with the report of every failure in a
+	/// collapsible group. Without a budget the session stops after 10 minutes.
+	Github,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ColorMode {
+	/// Colour the report when stderr is a terminal and NO_COLOR is not set.
+	Auto,
+	Always,
+	Never,
+}
+
+/// Documentation of the exit codes, which scripts may rely on.
+const EXIT_CODES: &str = "\
+Exit codes:
+  0   No counterexample was found
+  1   cpfuzz itself failed
+  10  Wrong answer: outputs differ or the checker or interactor rejected the answer
+  11  Runtime error: the solution exited with a non-zero exit code, crashed or left input unread
+  12  Time limit: the solution was too slow or the interaction stalled
+  13  The verifier rejected the output
+  14  Memory limit exceeded
+  15  The interaction protocol was violated, or a party stopped while the other expected data
+  16  A comparator, the interactor or a judge failed
+  130 Interrupted by Ctrl-C before a counterexample was found";
+
 #[derive(clap::Parser, Debug)]
+#[command(
+	args_conflicts_with_subcommands = true,
+	subcommand_negates_reqs = true,
+	after_help = EXIT_CODES
+)]
+pub struct Cli {
+	#[command(subcommand)]
+	pub command: Option<Command>,
+
+	/// Absent only when a subcommand is given.
+	#[command(flatten)]
+	pub args: Option<Args>,
+}
+
+#[derive(clap::Subcommand, Debug)]
+pub enum Command {
+	/// Fuzz the solution, as cpfuzz does when no subcommand is given.
+	Run(Box<Args>),
+	/// Run a saved test again with the arguments of its session: the fuzz.seed of a failure is
+	/// regenerated from its seed, and saved inputs are run as they are.
+	Replay(ReplayArgs),
+	/// Run the solution and the program it is compared with on an input and print their outputs
+	/// side by side, marking the tokens which differ as the outputs are compared. Exits with 10
+	/// if they differ.
+	Diff(DiffArgs),
+	/// Print the inputs the session generates to stdout without running the solution, with the
+	/// seed of each on stderr. They are the tests of the first iterations of a session of the same
+	/// --seed, as long as it does not mutate them or ramp the size up. Prints one input unless
+	/// --max-tests is given.
+	Sample(Box<Args>),
+	/// Scaffold a problem in the working directory: the specification NAME.spec, or with
+	/// --generate the generator NAME_gen.cpp with cpfuzz.h next to it. Existing files are kept.
+	Init(InitArgs),
+	/// Run only the solution on generated inputs and print the distribution of its running time,
+	/// saving the slowest input to bench.in. Runs 100 tests unless --max-tests is given.
+	Bench(Box<Args>),
+	/// Receive problems from the Competitive Companion browser extension, saving their samples to
+	/// NAME.samples and scaffolding the specification NAME.spec from the first sample.
+	Listen(ListenArgs),
+	/// Turn counterexamples into tests of the official test set, answered by the reference
+	/// solution.
+	Export(ExportArgs),
+	/// Print the completion script of a shell, which completes the names of programs to the C++
+	/// files of the directory and the binaries of its Cargo workspace. For bash add
+	/// `source <(cpfuzz completions bash)` to ~/.bashrc.
+	Completions(CompletionsArgs),
+	/// Bootstrap the specification of a Codeforces problem from its statement: the input is laid
+	/// out like the first sample, and its numbers are named and bounded as the input format says
+	/// where it can be recognized. Check the specification against the statement before fuzzing.
+	SpecFromUrl(SpecFromUrlArgs),
+	/// Fuzz every problem of a contest in turn for a bounded budget, printing the verdict of each.
+	/// Exits with the exit code of the first problem which failed.
+	Bundle(BundleArgs),
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ReplayArgs {
+	/// The saved test: the fuzz.seed of a failure, which is regenerated at the size it failed at, a
+	/// saved input such as fuzz.min.in, or a directory of inputs.
+	pub saved: PathBuf,
+
+	#[command(flatten)]
+	pub args: Box<Args>,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct DiffArgs {
+	/// Language of the solutions.
+	pub language: Language,
+
+	/// The solution, whose output is shown on the left.
+	pub name: String,
+
+	/// The solution it is compared with, whose output is shown on the right.
+	pub compare: String,
+
+	/// The input both are run on, such as fuzz.min.in.
+	pub input: PathBuf,
+
+	/// Accept numbers which differ by at most this much, absolutely or relatively.
+	#[arg(long, value_name = "EPS")]
+	pub float_eps: Option<f64>,
+
+	/// How strictly outputs are compared.
+	#[arg(long, value_enum, default_value_t = DiffMode::Tokens)]
+	pub diff_mode: DiffMode,
+
+	/// When to colour the tokens which differ.
+	#[arg(long, value_name = "WHEN", value_enum, default_value_t = ColorMode::Auto)]
+	pub color: ColorMode,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct InitArgs {
+	/// The problem, which its files are named after.
+	pub name: String,
+
+	/// Scaffold a generator written against cpfuzz.h rather than a specification.
+	#[arg(short, long)]
+	pub generate: bool,
+
+	/// Language of the solution in the printed command line.
+	#[arg(long, value_enum, default_value_t = Language::Cpp)]
+	pub language: Language,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct BundleArgs {
+	/// The problems, one per line as the arguments of cpfuzz such as `cpp a a.spec --compare
+	/// a_brute`. Empty lines and lines starting with # are skipped. The files of each session start
+	/// with the name of its solution, such as a.fuzz.in.
+	pub manifest: PathBuf,
+
+	/// How long every problem is fuzzed, unless its line gives --max-time, --max-tests or
+	/// --until-pass.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "30s")]
+	pub budget: Duration,
+
+	/// Stop at the first problem which fails.
+	#[arg(long)]
+	pub fail_fast: bool,
+
+	#[arg(long, value_enum, default_value_t = ColorMode::Auto)]
+	pub color: ColorMode,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct SpecFromUrlArgs {
+	/// The problem, such as https://codeforces.com/contest/4/problem/A, or its page saved from the
+	/// browser. The page is downloaded with curl.
+	pub url: String,
+
+	/// Where to write the specification, - for stdout. Defaults to NAME.spec as listen names it,
+	/// which must not exist.
+	#[arg(short, long, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+
+	/// Language of the solution in the printed command line.
+	#[arg(long, value_enum, default_value_t = Language::Cpp)]
+	pub language: Language,
+}
+
+/// How a crash is run again for a debugger.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum RecordCrash {
+	/// Record it with rr, to debug it back and forth in time.
+	Rr,
+	/// Dump the core of the crashed process.
+	Core,
+}
+
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum Shell {
+	Bash,
+	Zsh,
+	Fish,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct CompletionsArgs {
+	#[arg(value_enum, required_unless_present("programs"))]
+	pub shell: Option<Shell>,
+
+	/// Print the programs the names complete to, which the scripts run.
+	#[arg(long, hide = true)]
+	pub programs: bool,
+}
+
+/// The layout of the exported tests.
+#[derive(clap::ValueEnum, Clone, Copy, Debug)]
+pub enum ExportFormat {
+	/// Pairs of NN.in and NN.ans in a directory, numbered after the tests already in it.
+	Directory,
+	/// A zip of tests/NN and tests/NN.a, as Polygon imports them.
+	Polygon,
+	/// A zip of data/secret/NN.in and data/secret/NN.ans, as in DOMjudge and Kattis packages.
+	Domjudge,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ExportArgs {
+	/// Language of the reference solution.
+	pub language: Language,
+
+	/// The correct solution, whose outputs become the answers of the tests.
+	pub reference: String,
+
+	/// Counterexamples to export, such as fuzz.min.in, or directories such as the corpus whose .in
+	/// files are exported. Inputs appearing twice are exported once.
+	#[arg(required = true, value_name = "INPUT")]
+	pub inputs: Vec<PathBuf>,
+
+	/// Where the tests are written. Defaults to tests, or tests.zip for the zipped formats.
+	#[arg(short, long, value_name = "PATH")]
+	pub output: Option<PathBuf>,
+
+	#[arg(long, value_enum, default_value_t = ExportFormat::Directory)]
+	pub format: ExportFormat,
+}
+
+#[derive(clap::Args, Debug)]
+pub struct ListenArgs {
+	/// Port to listen on, which must be one of the custom ports of Competitive Companion.
+	#[arg(long, default_value_t = 10043)]
+	pub port: u16,
+
+	/// Language of the solutions in the printed command lines.
+	#[arg(long, value_enum, default_value_t = Language::Cpp)]
+	pub language: Language,
+
+	/// Stop after receiving one problem, or every problem of a contest sent at once.
+	#[arg(long)]
+	pub once: bool,
+}
+
+#[derive(clap::Args, Clone, Debug)]
 pub struct Args {
 	pub language: Language,
 	pub name: String,
-	pub specification: String,
+	/// Not needed when running the inputs of a directory with --inputs or with --spec-inline. A
+	/// specification given as `-` is read from stdin.
+	#[arg(required_unless_present_any(["inputs", "spec_inline"]))]
+	pub specification: Option<String>,
 
+	/// The specification itself rather than the path of a file containing it, where `\n` separates
+	/// the lines, such as "int n 1 100\narr a n 1 1000000000".
+	#[arg(
+		long,
+		value_name = "SPECIFICATION",
+		conflicts_with_all(["specification", "generate", "testlib_generator", "inputs"])
+	)]
+	pub spec_inline: Option<String>,
+
+	/// The specification is a generator written against cpfuzz.h, whose C++ source is given with
+	/// or without `.cpp`.
 	#[arg(short, long)]
 	pub generate: bool,
 
+	/// A problem package in the format of problemtools, whose time limit, memory limit, float
+	/// tolerance, samples, output validator and input validators are used where not given
+	/// otherwise. Its input validators check the generated inputs as --input-validator does. The
+	/// validators must be C++, which they are built as whatever the language of the solution.
+	#[arg(long, value_name = "DIR")]
+	pub package: Option<PathBuf>,
+
+	/// The specification is a generator written with testlib.h, built like the solution and run as
+	/// `GENERATOR <ARGS> <seed>` for every test with the input on stdout. The seed is chosen by
+	/// cpfuzz, so the test is regenerated from the seed of cpfuzz like any other.
+	#[arg(long, conflicts_with("generate"))]
+	pub testlib_generator: bool,
+
+	/// An argument given to the testlib generator before the seed. May be given several times.
+	#[arg(
+		long = "generator-arg",
+		value_name = "ARG",
+		allow_hyphen_values = true,
+		requires("testlib_generator")
+	)]
+	pub generator_arguments: Vec<String>,
+
+	/// Another specification to generate tests from, as PATH or PATH:WEIGHT. Every test is
+	/// generated by one of the specifications and generators of the session, chosen in proportion
+	/// to their weights, so one session can mix random, adversarial and tiny tests. May be given
+	/// several times.
+	#[arg(
+		long = "spec",
+		value_name = "PATH[:WEIGHT]",
+		value_parser = parse_source,
+		conflicts_with("inputs")
+	)]
+	pub specifications: Vec<Source>,
+
+	/// Another generator written against cpfuzz.h to generate tests from, as NAME or NAME:WEIGHT
+	/// like --spec, whose C++ source is given with or without `.cpp`. May be given several times.
+	#[arg(
+		long = "gen",
+		value_name = "NAME[:WEIGHT]",
+		value_parser = parse_source,
+		conflicts_with("inputs")
+	)]
+	pub generators: Vec<Source>,
+
+	/// The weight of the specification or generator given as an argument against those of --spec
+	/// and --gen.
+	#[arg(
+		long,
+		value_name = "WEIGHT",
+		default_value_t = 1,
+		value_parser = clap::value_parser!(u32).range(1..)
+	)]
+	pub weight: u32,
+
 	#[arg(short, long, value_name = "INTERACTOR")]
 	pub interactive: Option<String>,
 
-	#[arg(short, long, value_name = "COMPARATOR", conflicts_with("interactive"))]
-	pub compare: Option<String>,
+	/// How the interactor is given the input and reports its verdict.
+	#[arg(long, value_enum, default_value_t = InteractorProtocol::Stdin, requires("interactive"))]
+	pub interactor_protocol: InteractorProtocol,
 
-	#[arg(short, long, value_name = "VERIFIER", conflicts_with("interactive"), conflicts_with("compare"))]
+	/// Size of the buffer data is passed between the solution and the interactor through, e.g.
+	/// 64K. Larger buffers need fewer system calls for chatty protocols.
+	#[arg(
+		long,
+		value_name = "SIZE",
+		value_parser = parse_memory,
+		default_value = "64K",
+		requires("interactive")
+	)]
+	pub pipe_buffer: u64,
+
+	/// Compare the output of the solution with this solution. May be given several times, in which
+	/// case all outputs must agree. In interactive mode all solutions are run against the
+	/// interactor and the last lines they write are compared as their final answers.
+	#[arg(short, long, value_name = "COMPARATOR")]
+	pub compare: Vec<String>,
+
+	#[arg(short, long, value_name = "VERIFIER", conflicts_with("interactive"))]
 	pub verify: Option<String>,
+
+	/// Program every generated input is run through before it is used, which rejects an input
+	/// breaking the constraints by exiting with a non-zero exit code, as testlib.h validators do.
+	/// Rejected inputs are skipped and counted, and the first is saved to fuzz.invalid.in.
+	#[arg(long, value_name = "VALIDATOR")]
+	pub input_validator: Option<String>,
+
+	/// Fail the session on the first generated input the input validators reject.
+	#[arg(long)]
+	pub fail_on_invalid: bool,
+
+	/// How the verifier is given the input and output.
+	#[arg(long, value_enum, default_value_t = VerifyProtocol::Concatenate, requires("verify"))]
+	pub verify_protocol: VerifyProtocol,
+
+	/// Fail a test in which the solution exits without reading all of the input, which usually
+	/// means it reads another format than the generator writes. On by default when the output is
+	/// compared, checked or verified, where such a solution only passes by coincidence, and only
+	/// reported otherwise. Give --require-consume=false to only report it. Input left unread by
+	/// --persistent and interactive solutions is not noticed.
+	#[arg(
+		long,
+		value_name = "BOOL",
+		num_args = 0..=1,
+		require_equals = true,
+		default_missing_value = "true"
+	)]
+	pub require_consume: Option<bool>,
+
+	/// Judge the output of the primary with a checker, which is invoked as
+	/// `CHECKER <input> <output> <answer>` where the answer is the output of the comparator, of
+	/// which there must be exactly one. The checker accepts the output by exiting with code zero.
+	/// Checkers written with testlib.h work unmodified: their message is reported, and a checker
+	/// failing with _fail is reported as such rather than as a wrong answer.
+	#[arg(
+		long,
+		value_name = "CHECKER",
+		requires("compare"),
+		conflicts_with("verify"),
+		conflicts_with("interactive")
+	)]
+	pub checker: Option<String>,
+
+	/// Compare the output of the solution with the answer written by the generator, using the
+	/// write_answer functions of cpfuzz.h.
+	#[arg(
+		long,
+		requires("generate"),
+		conflicts_with_all(["interactive", "compare", "verify"])
+	)]
+	pub answer: bool,
+
+	/// Run the solution twice on every input and fail if the outputs differ, which catches wrong
+	/// answers caused by uninitialized memory or iteration over unordered containers.
+	#[arg(
+		long,
+		conflicts_with_all(["interactive", "compare", "verify", "checker", "answer"])
+	)]
+	pub self_check: bool,
+
+	/// Keep the solution running between tests instead of spawning it for every test, which is
+	/// much faster on small inputs. The solution must then be a harness answering one test after
+	/// the other: every input is written to its stdin as its length in bytes on a line followed by
+	/// the input, and the harness answers with the length of its output on a line followed by the
+	/// output. It should exit once stdin is closed. Its stderr is only reported if it dies.
+	#[arg(long, conflicts_with("interactive"))]
+	pub persistent: bool,
+
+	/// Build the C++ solution with coverage instrumentation and generate half of the tests by
+	/// mutating the inputs which covered new blocks of it, which reaches deep branches sooner.
+	/// Mutated inputs have no seed and are reproduced from their saved input instead.
+	#[arg(long, conflicts_with_all(["persistent", "inputs"]))]
+	pub coverage: bool,
+
+	/// Generate half of the tests by mutating the inputs of the corpus and the failures found:
+	/// choosing a number anew or nudging it, swapping or duplicating numbers and lines, or growing
+	/// and shrinking arrays. The mutated inputs still follow the specification. Corpus inputs are
+	/// only mutated with a specification, as a generator can not read them back. Mutated inputs
+	/// have no seed and are reproduced from their saved input instead.
+	#[arg(long, conflicts_with("inputs"))]
+	pub mutate: bool,
+
+	/// Before generating tests at random, run every input of the specification whose numbers are
+	/// all between -K and K, or the nearest value to that range their bounds allow: every array
+	/// of length at most K over the small values and every permutation of at most K elements.
+	/// This finds most logic bugs, and is certain to find those with a failing test that small.
+	/// These tests do not count towards --max-tests. Needs a specification.
+	#[arg(
+		long,
+		value_name = "K",
+		value_parser = clap::value_parser!(i64).range(1..),
+		conflicts_with_all([
+			"inputs",
+			"generate",
+			"testlib_generator",
+			"specifications",
+			"generators"
+		])
+	)]
+	pub exhaustive_upto: Option<i64>,
+
+	/// The value of the variable `size`, which specifications can use as a bound such as in
+	/// `int n 1 size` and generators read with `size(context)`.
+	#[arg(long, value_name = "SIZE", value_parser = clap::value_parser!(i64).range(1..))]
+	pub size: Option<i64>,
+
+	/// Ramp `size` up from 1 to the value of --size over the session instead, spending the most
+	/// tests on the sizes which run fast. Tiny inputs find logic bugs quickly, large ones time
+	/// limits. The size of a failing test is printed with its seed.
+	#[arg(long, requires("size"))]
+	pub adaptive_size: bool,
+
+	/// Accept numbers in the compared outputs which differ by at most this much, absolutely or
+	/// relatively.
+	#[arg(long, value_name = "EPS")]
+	pub float_eps: Option<f64>,
+
+	/// How strictly outputs are compared.
+	#[arg(long, value_enum, default_value_t = DiffMode::Tokens)]
+	pub diff_mode: DiffMode,
+
+	/// Time limit of the solution, e.g. 2s. A run taking longer is killed.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+	pub time_limit: Option<Duration>,
+
+	/// Time limit of the comparators, which are usually slow brute forces. Defaults to the time
+	/// limit of the solution.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration, requires("compare"))]
+	pub compare_time_limit: Option<Duration>,
+
+	/// Memory limit of the solution and the comparators, e.g. 256M or 1G.
+	#[arg(long, value_name = "LIMIT", value_parser = parse_memory)]
+	pub memory_limit: Option<u64>,
+
+	/// Kill an interaction which has not finished after this long, e.g. 10s.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration, requires("interactive"))]
+	pub interaction_timeout: Option<Duration>,
+
+	/// Kill an interaction in which neither party wrote anything for this long, which happens when
+	/// both wait for the other.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration, default_value = "10s")]
+	pub idle_timeout: Duration,
+
+	/// Number of tests to run concurrently. The workers share the iterations of the session rather
+	/// than drawing from a random stream each, and every test is generated from the seed of its
+	/// iteration, so the workers never repeat each other's tests and a session of the same --seed
+	/// generates the same tests with any number of jobs. That does not hold with --adaptive-size,
+	/// --mutate or --coverage, whose tests depend on how fast and in which order the earlier ones
+	/// finished.
+	#[arg(short, long, value_name = "N", default_value_t = NonZeroUsize::MIN)]
+	pub jobs: NonZeroUsize,
+
+	/// Apply the profile of this name from cpfuzz.toml in the working directory, a table such as
+	/// `[profile.quick]` setting max-tests, max-time, time-limit, compare-time-limit, memory-limit,
+	/// size or adaptive-size like their options, and with `sanitize = true` building as cpp-sanitize
+	/// or rust-debug. Options given on the command line take precedence.
+	#[arg(long, value_name = "NAME")]
+	pub profile: Option<String>,
+
+	/// Stop after running this many tests. Generated inputs skipped as duplicates do not count,
+	/// until a thousand in a row show that the specification has run out of inputs.
+	#[arg(long, value_name = "N")]
+	pub max_tests: Option<u64>,
+
+	/// Verify the solution before submitting by running this many tests, printing a summary if
+	/// all of them passed.
+	#[arg(long, value_name = "N", conflicts_with("max_tests"))]
+	pub until_pass: Option<u64>,
+
+	/// Stop after running for this long, e.g. 60s or 5m.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+	pub max_time: Option<Duration>,
+
+	/// Keep fuzzing until this many distinct failures are found, saving them to fuzz.1.in,
+	/// fuzz.2.in, ...
+	#[arg(short, long, value_name = "K")]
+	pub keep_going: Option<NonZeroUsize>,
+
+	/// Rerun every failing test this many times and only report it if it fails the same way every
+	/// time. Otherwise it is reported as flaky, saved to fuzz.flaky.in, and fuzzing continues.
+	#[arg(long, value_name = "K", default_value_t = 0)]
+	pub recheck: u32,
+
+	/// When a program crashes, run the input it crashed on again for a debugger: rr records the
+	/// run to fuzz.rr for rr replay, core dumps its core to fuzz.core for gdb. C++ is built again
+	/// with debug information for it.
+	#[arg(long, value_enum, value_name = "HOW", conflicts_with("interactive"))]
+	pub record_crash: Option<RecordCrash>,
+
+	/// Seed of the session, making the generated tests reproducible. A seed generates the same tests
+	/// on every platform, unless a testlib generator makes them. Chosen at random by default.
+	/// Every test has a seed of its own derived from it and the iteration, which a failure is
+	/// reported with and saves to fuzz.seed.
+	#[arg(long, value_name = "SEED")]
+	pub seed: Option<u64>,
+
+	/// Regenerate and run only the test with this seed, as printed when a failure is found.
+	#[arg(long, value_name = "SEED", conflicts_with("seed"))]
+	pub replay_seed: Option<u64>,
+
+	/// Run only the given saved input, such as a previous fuzz.in, instead of generating tests.
+	#[arg(long, value_name = "FILE", conflicts_with("replay_seed"))]
+	pub input: Option<PathBuf>,
+
+	/// Run every file of this directory, except the .ans files holding the answers, instead of
+	/// generating tests, reporting the verdict of each.
+	#[arg(
+		long,
+		value_name = "DIR",
+		conflicts_with_all(["input", "replay_seed", "corpus", "generate"])
+	)]
+	pub inputs: Option<PathBuf>,
+
+	/// Directory of official sample tests, pairs of NAME.in and NAME.ans, whose answers the output
+	/// of the solution is compared with as --diff-mode says before fuzzing. Fuzzing only starts once
+	/// every sample passes.
+	#[arg(
+		long,
+		value_name = "DIR",
+		conflicts_with_all(["input", "inputs", "replay_seed", "interactive"])
+	)]
+	pub samples: Option<PathBuf>,
+
+	/// Directory of regression inputs. Every .in file in it is run before fuzzing starts.
+	#[arg(long, value_name = "DIR")]
+	pub corpus: Option<PathBuf>,
+
+	/// Save new counterexamples to the corpus directory.
+	#[arg(long, requires("corpus"))]
+	pub save_corpus: bool,
+
+	/// Write every generated input to this directory, listing the seed and verdict of each in
+	/// verdicts.txt, to audit what the generator produced.
+	#[arg(long, value_name = "DIR", conflicts_with("inputs"))]
+	pub log_inputs: Option<PathBuf>,
+
+	/// Keep only this many of the most recent logged inputs.
+	#[arg(long, value_name = "N", default_value_t = 1000, requires("log_inputs"))]
+	pub log_max_files: usize,
+
+	/// Keep only as many of the most recent logged inputs as fit in this size, e.g. 100M.
+	#[arg(
+		long,
+		value_name = "SIZE",
+		value_parser = parse_memory,
+		default_value = "100M",
+		requires("log_inputs")
+	)]
+	pub log_max_size: u64,
+
+	/// Append a JSON line with the seed, verdict, running time of the solution and input size of
+	/// every test to this file.
+	#[arg(long, value_name = "FILE", conflicts_with("inputs"))]
+	pub event_log: Option<PathBuf>,
+
+	/// Treat runs of the solution taking longer than this as failures, e.g. 1.5s, to find slow
+	/// inputs.
+	#[arg(long, value_name = "DURATION", value_parser = parse_duration)]
+	pub fail_if_slower_than: Option<Duration>,
+
+	/// Treat runs of the solution taking more than this many times as long as the first comparator
+	/// as failures.
+	#[arg(long, value_name = "RATIO", requires("compare"))]
+	pub fail_if_ratio: Option<f64>,
+
+	/// Print the mean, 95th percentile and maximum running time of the solution at the end.
+	#[arg(long)]
+	pub timing: bool,
+
+	/// Show a dashboard of what every worker is doing, the throughput and the failures found
+	/// instead of the progress line, which is useful with --jobs.
+	#[arg(long, conflicts_with_all(["quiet", "verbose"]))]
+	pub tui: bool,
+
+	/// Only report the final verdict and the paths of the saved files.
+	#[arg(short, long, conflicts_with("verbose"))]
+	pub quiet: bool,
+
+	/// Report the verdict and timing of every test, and when given twice also every spawned
+	/// command line. There is no short flag as -v is --verify.
+	#[arg(long, action = clap::ArgAction::Count)]
+	pub verbose: u8,
+
+	/// Directory in which fuzz.in and the other files of the session are saved. `{problem}` is
+	/// replaced by the name of the solution and `{timestamp}` by the start of the session.
+	/// Defaults to the current directory. The next session overwrites them, so every generated
+	/// counterexample is also copied to .cpfuzz/failures/TIMESTAMP-VERDICT with metadata.json.
+	#[arg(long, value_name = "DIR")]
+	pub out_dir: Option<PathBuf>,
+
+	/// Name the files of the session start with instead of fuzz, with the same placeholders as
+	/// --out-dir.
+	#[arg(long, value_name = "PREFIX", default_value = "fuzz")]
+	pub out_prefix: String,
+
+	/// Format of the report written to stdout or --report-file at the end of the session.
+	#[arg(long, value_name = "FORMAT", value_enum, default_value_t = ReportFormat::Human)]
+	pub report: ReportFormat,
+
+	/// Write the report to this file instead of stdout.
+	#[arg(long, value_name = "FILE")]
+	pub report_file: Option<PathBuf>,
+
+	/// When to colour the report.
+	#[arg(long, value_name = "WHEN", value_enum, default_value_t = ColorMode::Auto)]
+	pub color: ColorMode,
+
+	/// Do not shrink failing inputs into fuzz.min.in.
+	#[arg(long)]
+	pub no_minimize: bool,
+
+	/// Start over whenever the source of a program or the specification changes, rebuilding the
+	/// programs which changed and running the counterexamples found before the corpus. Runs until
+	/// Ctrl-C.
+	#[arg(long)]
+	pub watch: bool,
+
+	/// Continue the last session of the directory where it stopped, from the state saved to
+	/// .cpfuzz/session while it ran: the seeds it covered are not tested again, the budget is what
+	/// it left, and its statistics and the inputs kept for mutation carry over. Given alone,
+	/// `cpfuzz --resume` runs the session with the arguments it was started with.
+	#[arg(long, conflicts_with_all(["watch", "input", "inputs", "replay_seed"]))]
+	pub resume: bool,
+}
+
+impl Args {
+	/// The arguments of a session of the solution with every option at its default, as if only
+	/// the language and the name were given on the command line.
+	pub fn new(language: Language, name: &str) -> Result<Args, Error> {
+		use clap::{Args as _, FromArgMatches as _, ValueEnum as _};
+
+		// The specification is not required here, which the language always being given says.
+		let command = Args::augment_args(clap::Command::new("cpfuzz").no_binary_name(true))
+			.mut_arg("specification", |arg| {
+				arg.required_unless_present("language")
+			});
+		let language = language
+			.to_possible_value()
+			.expect("no language is skipped");
+		let invalid = |e: clap::Error| Error::Arguments(e.to_string());
+		let matches = command
+			.try_get_matches_from([language.get_name(), name])
+			.map_err(invalid)?;
+		Args::from_arg_matches(&matches).map_err(invalid)
+	}
 }
 
+/// A specification or generator given with --spec or --gen, with the weight of choosing it. The
+/// weights are 32 bits, so the total weight of the session can not overflow.
+#[derive(Clone, Debug)]
+pub struct Source {
+	pub path: String,
+	pub weight: u32,
+}
+
+fn parse_source(src: &str) -> Result<Source, String> {
+	let (path, weight) = match src.rsplit_once(':') {
+		Some((path, "")) => return Err(format!("the weight of {path} is missing after the colon")),
+		Some((path, weight)) if weight.bytes().all(|b| b.is_ascii_digit()) => {
+			let weight = weight.parse().ok().filter(|&weight| weight > 0);
+			let Some(weight) = weight else {
+				return Err(format!(
+					"the weight of {path} must be a positive integer of at most {}",
+					u32::MAX
+				));
+			};
+			(path, weight)
+		}
+		_ => (src, 1),
+	};
+	Ok(Source {
+		path: path.to_string(),
+		weight,
+	})
+}
+
+pub fn parse_memory(src: &str) -> Result<u64, String> {
+	let (digits, unit) = match src.find(|c: char| !c.is_ascii_digit()) {
+		Some(i) => src.split_at(i),
+		None => (src, ""),
+	};
+	let amount: u64 = digits
+		.parse()
+		.map_err(|_| format!("'{src}' is not a memory amount"))?;
+	let shift = match unit {
+		"" | "B" => 0,
+		"K" | "KB" | "KiB" => 10,
+		"M" | "MB" | "MiB" => 20,
+		"G" | "GB" | "GiB" => 30,
+		_ => return Err(format!("unknown unit '{unit}', expected one of K, M or G")),
+	};
+	amount
+		.checked_mul(1 << shift)
+		.ok_or_else(|| format!("'{src}' is too large"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn source_weights() {
+		let source = parse_source("tiny.spec:3").unwrap();
+		assert_eq!((source.path.as_str(), source.weight), ("tiny.spec", 3));
+		assert_eq!(parse_source("tiny.spec").unwrap().weight, 1);
+		assert_eq!(parse_source("dir:name.spec").unwrap().path, "dir:name.spec");
+		assert!(parse_source("tiny.spec:0").is_err());
+		assert_eq!(
+			parse_source("tiny.spec:4294967295").unwrap().weight,
+			u32::MAX
+		);
+		assert!(parse_source("tiny.spec:4294967296").is_err());
+		assert!(parse_source("tiny.spec:18446744073709551615").is_err());
+		assert_eq!(
+			parse_source("tiny.spec:").err().unwrap(),
+			"the weight of tiny.spec is missing after the colon"
+		);
+	}
+
+	#[test]
+	fn memory_amounts() {
+		assert_eq!(parse_memory("1000"), Ok(1000));
+		assert_eq!(parse_memory("64K"), Ok(64 << 10));
+		assert_eq!(parse_memory("256MB"), Ok(256 << 20));
+		assert_eq!(parse_memory("2GiB"), Ok(2 << 30));
+		assert_eq!(
+			parse_memory("2T").err().unwrap(),
+			"unknown unit 'T', expected one of K, M or G"
+		);
+		assert_eq!(
+			parse_memory("M").err().unwrap(),
+			"'M' is not a memory amount"
+		);
+		assert_eq!(
+			parse_memory("-1").err().unwrap(),
+			"'-1' is not a memory amount"
+		);
+		assert!(parse_memory("18446744073709551615K").is_err());
+	}
+}
@@ -0,0 +1,88 @@
+use std::{io, mem, process::ExitCode, time::Duration};
+
+use crate::{
+	INTERRUPTED,
+	args::Args,
+	choices::{self, Choices},
+	error::{Component, Context as _, Error},
+	generator::{Generator, Test},
+	interrupt,
+	limits::Limits,
+	progress::Progress,
+	usage::{self, Statistics},
+	verbosity,
+};
+
+/// Number of tests run when --max-tests is not given.
+const DEFAULT_TESTS: u64 = 100;
+
+/// Runs only the solution on generated inputs, printing the distribution of its running time and
+/// saving the slowest input to `bench.in`. Ctrl-C stops it early, summarizing the tests run.
+pub fn bench(args: &Args) -> Result<ExitCode, Error> {
+	if args.inputs.is_some() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"bench generates its inputs and can not be used with --inputs",
+		)
+		.into());
+	}
+	args.language
+		.build(&args.name)
+		.building(Component::Solution, &args.name)?;
+	let generator = Generator::new(args)?;
+	let limits = Limits::new(args);
+	let seed = args.seed.unwrap_or_else(|| fastrand::u64(..));
+	let statistics = Statistics::new();
+	let progress = Progress::new(!verbosity::quiet());
+	let mut slowest: Option<(Duration, Test)> = None;
+	let mut failed = 0;
+	let mut peak_memory = 0;
+	let mut buffer = Vec::new();
+	for test in 0..args.max_tests.unwrap_or(DEFAULT_TESTS) {
+		let test_seed = choices::test_seed(seed, test);
+		let mut choices = Choices::random(test_seed).sized(args.size);
+		let test = generator.generate(&mut choices, mem::take(&mut buffer))?;
+		usage::take();
+		let run = args.language.run(&args.name, &limits, &test.input)?;
+		// The run was killed by the interrupt, so it is not measured.
+		if interrupt::interrupted() {
+			break;
+		}
+		if !run.exit.success() {
+			failed += 1;
+		}
+		let usage = usage::take()
+			.into_iter()
+			.find(|(program, _)| *program == args.name)
+			.map(|(_, usage)| usage);
+		let wall = usage.map_or(Duration::ZERO, |usage| usage.wall);
+		peak_memory = usage.map_or(peak_memory, |usage| peak_memory.max(usage.memory));
+		statistics.record(wall, Some(test_seed));
+		if slowest.as_ref().is_none_or(|(slowest, _)| wall > *slowest) {
+			if let Some((_, previous)) = slowest.replace((wall, test)) {
+				buffer = previous.input;
+			}
+		} else {
+			buffer = test.input;
+		}
+		progress.update(&statistics)?;
+	}
+	progress.finish(&statistics)?;
+	eprintln!();
+	if let Some(distribution) = statistics.distribution() {
+		eprintln!("{distribution}");
+	}
+	eprintln!("Peak memory usage: {}", usage::describe_memory(peak_memory));
+	if failed > 0 {
+		eprintln!("{failed} runs exited with a failure");
+	}
+	if let Some((wall, test)) = slowest {
+		test.save("bench")?;
+		eprintln!("Slowest input took {wall:.1?}, saved to bench.in");
+	}
+	eprintln!("Seed {seed}");
+	match interrupt::interrupted() {
+		true => Ok(ExitCode::from(INTERRUPTED)),
+		false => Ok(ExitCode::SUCCESS),
+	}
+}
@@ -0,0 +1,7 @@
+use std::process::ExitCode;
+
+use cpfuzz_core::error;
+
+fn main() -> ExitCode {
+	error::exit(cpfuzz_core::cargo_cli(std::env::args_os()))
+}
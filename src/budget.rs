@@ -0,0 +1,216 @@
+use std::{
+	collections::BTreeSet,
+	sync::{
+		Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+	time::{Duration, Instant},
+};
+
+use crate::args::{self, ReportFormat};
+
+/// How long a session reporting to GitHub Actions runs without a budget, as a CI job has to
+/// finish.
+const CI_MAX_TIME: Duration = Duration::from_secs(600);
+
+/// Most generated inputs in a row which are skipped as duplicates without counting against
+/// --max-tests. After as many the inputs are taken to have run out, and the duplicates count.
+pub const MAX_CONSECUTIVE_DUPLICATES: u64 = 1000;
+
+/// The tests which have finished, for resuming the session without running them again.
+struct Finished {
+	/// Every test before this one has finished.
+	first: u64,
+	/// The tests after the first unfinished one which have finished, as several run at once.
+	after: BTreeSet<u64>,
+}
+
+/// Bounds on how long a session may run, shared between all workers.
+pub struct Budget {
+	tests: AtomicU64,
+	max_tests: Option<u64>,
+	/// The tests which were skipped as duplicates without counting against --max-tests.
+	duplicates: AtomicU64,
+	/// The tests skipped as duplicates since the last one which was not.
+	consecutive: AtomicU64,
+	deadline: Option<Instant>,
+	finished: Mutex<Finished>,
+}
+
+impl Budget {
+	pub fn new(args: &args::Args) -> Budget {
+		let max_tests = args.max_tests.or(args.until_pass);
+		let max_time = match args.report {
+			ReportFormat::Github if max_tests.is_none() => args.max_time.or(Some(CI_MAX_TIME)),
+			_ => args.max_time,
+		};
+		Budget {
+			tests: AtomicU64::new(0),
+			max_tests,
+			duplicates: AtomicU64::new(0),
+			consecutive: AtomicU64::new(0),
+			deadline: max_time.map(|max_time| Instant::now() + max_time),
+			finished: Mutex::new(Finished {
+				first: 0,
+				after: BTreeSet::new(),
+			}),
+		}
+	}
+
+	/// Continues a session which stopped with these tests finished, of which `duplicates` did not
+	/// count, after running for the duration, which is taken from the time left.
+	pub fn resume(&mut self, first: u64, after: &[u64], duplicates: u64, elapsed: Duration) {
+		*self.tests.get_mut() = first;
+		*self.duplicates.get_mut() = duplicates;
+		self.deadline = self
+			.deadline
+			.map(|deadline| deadline.checked_sub(elapsed).unwrap_or_else(Instant::now));
+		let finished = self.finished.get_mut().expect("not poisoned");
+		finished.first = first;
+		finished.after = after.iter().copied().collect();
+	}
+
+	/// Whether the time of the session is up.
+	pub fn expired(&self) -> bool {
+		self.deadline
+			.is_some_and(|deadline| Instant::now() >= deadline)
+	}
+
+	/// Claims the index of the next test, returning None if the budget has been exhausted. Tests
+	/// which finished before the session was resumed are skipped.
+	pub fn next(&self) -> Option<u64> {
+		loop {
+			if self.expired() {
+				return None;
+			}
+			let test = self.tests.fetch_add(1, Ordering::Relaxed);
+			let duplicates = self.duplicates.load(Ordering::Relaxed);
+			if self
+				.max_tests
+				.is_some_and(|max_tests| test >= max_tests.saturating_add(duplicates))
+			{
+				return None;
+			}
+			if !self
+				.finished
+				.lock()
+				.expect("not poisoned")
+				.after
+				.contains(&test)
+			{
+				return Some(test);
+			}
+		}
+	}
+
+	/// Notes that the test claimed with [`Self::next`] finished, so it is not run again when the
+	/// session is resumed.
+	pub fn finish(&self, test: u64) {
+		let mut finished = self.finished.lock().expect("not poisoned");
+		let Finished { first, after } = &mut *finished;
+		after.insert(test);
+		while after.remove(first) {
+			*first += 1;
+		}
+	}
+
+	/// Notes that the test claimed with [`Self::next`] was skipped as a duplicate, which does not
+	/// count unless the inputs have run out.
+	pub fn duplicate(&self, test: u64) {
+		if self.consecutive.fetch_add(1, Ordering::Relaxed) < MAX_CONSECUTIVE_DUPLICATES {
+			self.duplicates.fetch_add(1, Ordering::Relaxed);
+		}
+		self.finish(test);
+	}
+
+	/// Notes that a test was generated which was not a duplicate.
+	pub fn distinct(&self) {
+		self.consecutive.store(0, Ordering::Relaxed);
+	}
+
+	/// Whether the last [`MAX_CONSECUTIVE_DUPLICATES`] generated inputs were all duplicates, so
+	/// the specification has likely run out of inputs.
+	pub fn exhausted(&self) -> bool {
+		self.consecutive.load(Ordering::Relaxed) >= MAX_CONSECUTIVE_DUPLICATES
+	}
+
+	/// The tests skipped as duplicates which did not count.
+	pub fn duplicates(&self) -> u64 {
+		self.duplicates.load(Ordering::Relaxed)
+	}
+
+	/// The first test which has not finished, and the tests after it which have.
+	pub fn finished(&self) -> (u64, Vec<u64>) {
+		let finished = self.finished.lock().expect("not poisoned");
+		(finished.first, finished.after.iter().copied().collect())
+	}
+}
+
+pub fn parse_duration(src: &str) -> Result<Duration, String> {
+	let (digits, unit) = match src.find(|c: char| !c.is_ascii_digit() && c != '.') {
+		Some(i) => src.split_at(i),
+		None => (src, "s"),
+	};
+	let amount: f64 = digits
+		.parse()
+		.map_err(|_| format!("'{src}' is not a duration"))?;
+	let seconds = match unit {
+		"ms" => amount / 1000.0,
+		"s" => amount,
+		"m" => amount * 60.0,
+		"h" => amount * 3600.0,
+		_ => {
+			return Err(format!(
+				"unknown unit '{unit}', expected one of ms, s, m or h"
+			));
+		}
+	};
+	Duration::try_from_secs_f64(seconds).map_err(|_| format!("'{src}' is not a valid duration"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn duplicates_do_not_count() {
+		let mut args = args::Args::new(args::Language::Cpp, "solution").unwrap();
+		args.max_tests = Some(2);
+		let budget = Budget::new(&args);
+		let test = budget.next().unwrap();
+		budget.duplicate(test);
+		assert_eq!(
+			[budget.next(), budget.next(), budget.next()],
+			[Some(1), Some(2), None]
+		);
+		// Once the inputs run out, the duplicates count.
+		let budget = Budget::new(&args);
+		for _ in 0..MAX_CONSECUTIVE_DUPLICATES + 2 {
+			let Some(test) = budget.next() else {
+				break;
+			};
+			budget.duplicate(test);
+		}
+		assert!(budget.exhausted());
+		assert_eq!(budget.next(), None);
+	}
+
+	#[test]
+	fn durations() {
+		assert_eq!(parse_duration("2"), Ok(Duration::from_secs(2)));
+		assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+		assert_eq!(parse_duration("250ms"), Ok(Duration::from_millis(250)));
+		assert_eq!(parse_duration("10m"), Ok(Duration::from_secs(600)));
+		assert_eq!(parse_duration("1h"), Ok(Duration::from_secs(3600)));
+		assert_eq!(
+			parse_duration("3d").err().unwrap(),
+			"unknown unit 'd', expected one of ms, s, m or h"
+		);
+		assert_eq!(parse_duration("s").err().unwrap(), "'s' is not a duration");
+		assert_eq!(
+			parse_duration("-1s").err().unwrap(),
+			"'-1s' is not a duration"
+		);
+		assert!(parse_duration("99999999999999999999h").is_err());
+	}
+}
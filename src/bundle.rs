@@ -0,0 +1,191 @@
+use std::{
+	ffi::OsString,
+	io,
+	process::ExitCode,
+	time::{Duration, Instant},
+};
+
+use clap::Parser as _;
+
+use crate::{
+	Session,
+	args::{BundleArgs, Cli, Command},
+	conclude, config,
+	error::Error,
+	interrupt, read_specification, style, usage, verbosity, watch,
+};
+
+/// The verdict of a problem by the exit code of its session, as the help documents them.
+fn verdict(exit_code: u8) -> &'static str {
+	match exit_code {
+		0 => "passed",
+		10 => "wrong answer",
+		11 => "runtime error",
+		12 => "time limit",
+		13 => "rejected by the verifier",
+		14 => "memory limit",
+		15 => "protocol violation",
+		16 => "a judge failed",
+		130 => "interrupted",
+		_ => "failed",
+	}
+}
+
+/// Splits a line of the manifest into arguments at whitespace, keeping what is quoted with ' or "
+/// together. None if a quote is not closed.
+fn words(line: &str) -> Option<Vec<OsString>> {
+	let mut words = Vec::new();
+	let mut word: Option<String> = None;
+	let mut quote = None;
+	for c in line.chars() {
+		match quote {
+			Some(q) if c == q => quote = None,
+			Some(_) => word.get_or_insert_default().push(c),
+			None if c == '\'' || c == '"' => {
+				quote = Some(c);
+				word.get_or_insert_default();
+			}
+			None if c.is_whitespace() => words.extend(word.take().map(OsString::from)),
+			None => word.get_or_insert_default().push(c),
+		}
+	}
+	words.extend(word.map(OsString::from));
+	quote.is_none().then_some(words)
+}
+
+/// The result of fuzzing one problem of the bundle.
+struct Problem {
+	name: String,
+	/// The exit code of its session, or why it could not run.
+	result: Result<u8, Error>,
+	tests: usize,
+	elapsed: Duration,
+}
+
+/// Fuzzes the problem a line of the manifest gives for the budget, unless the line has a budget of
+/// its own.
+fn fuzz(words: Vec<OsString>, bundle: &BundleArgs) -> Result<(u8, usize), Error> {
+	let cli = Cli::try_parse_from(std::iter::once("cpfuzz".into()).chain(words))
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?;
+	let args = match cli.command {
+		None => cli.args,
+		Some(Command::Run(args)) => Some(*args),
+		Some(_) => None,
+	};
+	let Some(mut args) = args else {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"a problem of a bundle can not be a subcommand other than run",
+		)
+		.into());
+	};
+	if args.resume {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"a problem of a bundle can not be resumed",
+		)
+		.into());
+	}
+	if args.max_time.is_none() && args.max_tests.is_none() && args.until_pass.is_none() {
+		args.max_time = Some(bundle.budget);
+	}
+	// The problems share the directory, so their files are told apart by the solution.
+	if args.out_prefix == "fuzz" {
+		args.out_prefix = "{problem}.fuzz".to_string();
+	}
+	config::apply(&mut args)?;
+	verbosity::init(&args);
+	read_specification(&mut args)?;
+	usage::clear_builds();
+	let session = Session::new(args)?;
+	let failures = session.find_failures()?;
+	let (exit_code, _) = conclude(&session, &failures)?;
+	let (tests, _) = session.statistics.maximum();
+	Ok((exit_code, tests))
+}
+
+/// Fuzzes every problem of the manifest in turn for a bounded budget, printing the verdict of each
+/// at the end. Exits with the exit code of the first problem which failed, or 1 if a problem could
+/// not be fuzzed at all.
+pub fn bundle(args: &BundleArgs) -> Result<ExitCode, Error> {
+	style::init(args.color);
+	let manifest = std::fs::read_to_string(&args.manifest)?;
+	let mut lines = Vec::new();
+	for (i, line) in manifest.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		let Some(words) = words(line) else {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!("{}:{}: unclosed quote", args.manifest.display(), i + 1),
+			)
+			.into());
+		};
+		lines.push((i + 1, words));
+	}
+	// A failed build fails its problem rather than the bundle.
+	watch::report_build_failures();
+	let total = lines.len();
+	let mut problems = Vec::new();
+	for (i, (line, words)) in lines.into_iter().enumerate() {
+		if interrupt::interrupted() {
+			break;
+		}
+		// The language is the first argument, and the solution the second.
+		let name = words.get(1).map_or_else(
+			|| format!("line {line}"),
+			|name| name.to_string_lossy().into_owned(),
+		);
+		style::section(&format!("PROBLEM {name} ({}/{total})", i + 1));
+		let started = Instant::now();
+		let (result, tests) = match fuzz(words, args) {
+			Ok((exit_code, tests)) => (Ok(exit_code), tests),
+			Err(e) => {
+				eprintln!("\n{} {e}", style::failed("Error:"));
+				(Err(e), 0)
+			}
+		};
+		let failed = !matches!(result, Ok(0));
+		problems.push(Problem {
+			name,
+			result,
+			tests,
+			elapsed: started.elapsed(),
+		});
+		if failed && args.fail_fast {
+			break;
+		}
+	}
+
+	style::section("BUNDLE");
+	let width = problems.iter().map(|problem| problem.name.len()).max();
+	for problem in &problems {
+		let verdict = match &problem.result {
+			Ok(0) => style::passed(verdict(0)),
+			Ok(exit_code) => style::failed(verdict(*exit_code)),
+			Err(_) => style::failed("error"),
+		};
+		eprintln!(
+			"  {:width$}  {verdict}, {} tests in {:.1?}",
+			problem.name,
+			style::separate_thousands(problem.tests),
+			problem.elapsed,
+			width = width.unwrap_or(0)
+		);
+	}
+	let skipped = total - problems.len();
+	if skipped > 0 {
+		eprintln!("  {skipped} problems not fuzzed");
+	}
+	let exit_code = problems
+		.iter()
+		.find_map(|problem| match problem.result {
+			Ok(0) => None,
+			Ok(exit_code) => Some(exit_code),
+			Err(_) => Some(1),
+		})
+		.unwrap_or(0);
+	Ok(ExitCode::from(exit_code))
+}
@@ -0,0 +1,254 @@
+use std::{
+	ffi::OsString,
+	io,
+	path::{Path, PathBuf},
+	process::{Command, ExitCode, Stdio},
+	sync::OnceLock,
+};
+
+use clap::{Parser as _, ValueEnum as _};
+
+use crate::{
+	args::{self, Cli},
+	error::{Context as _, Error},
+	json::Value,
+};
+
+/// The workspace of the Rust programs when cpfuzz runs as `cargo cpfuzz`. Otherwise it runs in
+/// the root of a package with the programs as `src/bin` targets.
+static WORKSPACE: OnceLock<Workspace> = OnceLock::new();
+
+struct Workspace {
+	/// As given with --manifest-path, otherwise cargo finds it from the working directory.
+	manifest_path: Option<PathBuf>,
+	target_directory: PathBuf,
+	/// The binary targets of the workspace with their sources.
+	bins: Vec<(String, PathBuf)>,
+}
+
+impl Workspace {
+	/// Asks cargo for the target directory and binaries of the workspace.
+	fn load(manifest_path: Option<PathBuf>) -> Result<Workspace, Error> {
+		let output = Self::metadata(&manifest_path)
+			.output()
+			.map_err(crate::missing("cargo", crate::NOT_INSTALLED))?;
+		if !output.status.success() {
+			let stderr = String::from_utf8_lossy(&output.stderr);
+			return Err(io::Error::other(stderr.trim().to_string()))
+				.context(|| "reading the workspace with cargo metadata".to_string());
+		}
+		Ok(Self::parse(manifest_path, &output.stdout)?)
+	}
+
+	fn metadata(manifest_path: &Option<PathBuf>) -> Command {
+		let mut command = Command::new("cargo");
+		command.args(["metadata", "--format-version", "1", "--no-deps"]);
+		if let Some(manifest_path) = manifest_path {
+			command.arg("--manifest-path").arg(manifest_path);
+		}
+		command
+	}
+
+	fn parse(manifest_path: Option<PathBuf>, metadata: &[u8]) -> io::Result<Workspace> {
+		let invalid = || {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				"invalid output of cargo metadata",
+			)
+		};
+		let metadata = Value::parse(&String::from_utf8_lossy(metadata)).ok_or_else(invalid)?;
+		let target_directory = metadata
+			.get("target_directory")
+			.and_then(Value::as_str)
+			.ok_or_else(invalid)?;
+		let mut bins = Vec::new();
+		for package in metadata
+			.get("packages")
+			.and_then(Value::as_array)
+			.unwrap_or(&[])
+		{
+			for target in package
+				.get("targets")
+				.and_then(Value::as_array)
+				.unwrap_or(&[])
+			{
+				let kinds = target.get("kind").and_then(Value::as_array).unwrap_or(&[]);
+				if !kinds.iter().any(|kind| kind.as_str() == Some("bin")) {
+					continue;
+				}
+				let name = target.get("name").and_then(Value::as_str);
+				let source = target.get("src_path").and_then(Value::as_str);
+				if let (Some(name), Some(source)) = (name, source) {
+					bins.push((name.to_string(), PathBuf::from(source)));
+				}
+			}
+		}
+		Ok(Workspace {
+			manifest_path,
+			target_directory: PathBuf::from(target_directory),
+			bins,
+		})
+	}
+
+	/// The binary a program given on the command line names: a binary of the workspace, or the
+	/// binary built from the source file of that path. Other names are kept, for cargo to report.
+	fn resolve(&self, program: &str) -> String {
+		if self.bins.iter().any(|(name, _)| name == program) {
+			return program.to_string();
+		}
+		let Ok(path) = Path::new(program).canonicalize() else {
+			return program.to_string();
+		};
+		match self.bins.iter().find(|(_, source)| *source == path) {
+			Some((name, _)) => name.clone(),
+			None => program.to_string(),
+		}
+	}
+}
+
+/// The binaries of the workspace in the working directory, or none if cargo can not tell, for
+/// completing the names of programs.
+pub fn bins() -> Vec<String> {
+	let output = Workspace::metadata(&None).stderr(Stdio::null()).output();
+	let workspace = match output {
+		Ok(output) if output.status.success() => Workspace::parse(None, &output.stdout).ok(),
+		_ => None,
+	};
+	workspace
+		.into_iter()
+		.flat_map(|workspace| workspace.bins)
+		.map(|(name, _)| name)
+		.collect()
+}
+
+/// Where cargo puts the binary of the program built with the profile, such as `release`.
+pub fn artifact(profile: &str, program: &str) -> PathBuf {
+	let target_directory = match WORKSPACE.get() {
+		Some(workspace) => &workspace.target_directory,
+		None => Path::new("target"),
+	};
+	target_directory.join(profile).join(program)
+}
+
+/// The source file of the binary of the program, relative to the working directory when it is
+/// inside it.
+pub fn source(program: &str) -> String {
+	let source = WORKSPACE.get().and_then(|workspace| {
+		let (_, source) = workspace.bins.iter().find(|(name, _)| name == program)?;
+		let relative = std::env::current_dir()
+			.ok()
+			.and_then(|dir| Some(source.strip_prefix(dir).ok()?.to_path_buf()));
+		Some(relative.unwrap_or_else(|| source.clone()))
+	});
+	match source {
+		Some(source) => source.to_string_lossy().into_owned(),
+		None => format!("src/bin/{program}.rs"),
+	}
+}
+
+/// The arguments cargo is given to build a program of the workspace.
+pub fn manifest_arguments() -> Vec<OsString> {
+	match WORKSPACE.get().and_then(|w| w.manifest_path.as_ref()) {
+		Some(manifest_path) => vec!["--manifest-path".into(), manifest_path.into()],
+		None => Vec::new(),
+	}
+}
+
+/// Removes `--manifest-path PATH` from the arguments, returning the path.
+fn take_manifest_path(arguments: &mut Vec<OsString>) -> Option<PathBuf> {
+	let i = arguments.iter().position(|argument| {
+		argument == "--manifest-path"
+			|| argument
+				.to_str()
+				.is_some_and(|argument| argument.starts_with("--manifest-path="))
+	})?;
+	let argument = arguments.remove(i);
+	match argument
+		.to_str()
+		.and_then(|a| a.strip_prefix("--manifest-path="))
+	{
+		Some(path) => Some(PathBuf::from(path)),
+		None => (i < arguments.len()).then(|| arguments.remove(i).into()),
+	}
+}
+
+/// Runs cpfuzz as `cargo cpfuzz`, in which the programs are binaries of the Cargo workspace, such
+/// as `cargo cpfuzz e spec_e --compare brute`. The language is Rust unless it is given, and the
+/// programs may also be given by the paths of their sources.
+pub fn cli(arguments: impl IntoIterator<Item = OsString>) -> Result<ExitCode, Error> {
+	let mut arguments = crate::resume::arguments(arguments)?;
+	// Cargo gives the name of the subcommand as the first argument.
+	if arguments
+		.get(1)
+		.is_some_and(|argument| argument == "cpfuzz")
+	{
+		arguments.remove(1);
+	}
+	let workspace = Workspace::load(take_manifest_path(&mut arguments))?;
+	let language = match arguments.get(1).and_then(|argument| argument.to_str()) {
+		Some("run" | "diff" | "sample" | "bench" | "export") => Some(2),
+		// After the saved test.
+		Some("replay") => Some(3),
+		Some("listen" | "init" | "completions" | "spec-from-url" | "bundle" | "help") => None,
+		_ => Some(1),
+	};
+	let is_language = |argument: &OsString| {
+		argument
+			.to_str()
+			.is_some_and(|argument| args::Language::from_str(argument, false).is_ok())
+	};
+	if let Some(i) = language
+		&& arguments
+			.get(i)
+			.is_none_or(|argument| !is_language(argument))
+	{
+		arguments.insert(i.min(arguments.len()), "rust".into());
+	}
+	// Printed and returned rather than exiting like Cli::parse_from, so cpfuzz exits in one place.
+	let mut cli = match Cli::try_parse_from(arguments) {
+		Ok(cli) => cli,
+		Err(e) => {
+			e.print()?;
+			return Ok(ExitCode::from(e.exit_code() as u8));
+		}
+	};
+	let resolve_args = |args: &mut args::Args| {
+		args.name = workspace.resolve(&args.name);
+		for program in args
+			.compare
+			.iter_mut()
+			.chain(&mut args.verify)
+			.chain(&mut args.checker)
+			.chain(&mut args.interactive)
+			.chain(&mut args.input_validator)
+		{
+			*program = workspace.resolve(program);
+		}
+		if args.testlib_generator
+			&& let Some(specification) = &mut args.specification
+		{
+			*specification = workspace.resolve(specification);
+		}
+	};
+	match &mut cli.command {
+		Some(
+			args::Command::Run(args) | args::Command::Sample(args) | args::Command::Bench(args),
+		) => resolve_args(args),
+		Some(args::Command::Replay(replay)) => resolve_args(&mut replay.args),
+		Some(args::Command::Diff(args)) => {
+			args.name = workspace.resolve(&args.name);
+			args.compare = workspace.resolve(&args.compare);
+		}
+		Some(args::Command::Export(args)) => args.reference = workspace.resolve(&args.reference),
+		Some(
+			args::Command::Listen(_)
+			| args::Command::Init(_)
+			| args::Command::Completions(_)
+			| args::Command::SpecFromUrl(_)
+			| args::Command::Bundle(_),
+		) => {}
+		None => cli.args.iter_mut().for_each(resolve_args),
+	}
+	let _ = WORKSPACE.set(workspace);
+	crate::cli(cli)
+}
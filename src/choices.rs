@@ -0,0 +1,149 @@
+use std::vec;
+
+use crate::random::Random;
+
+/// The random decisions made while generating a test.
+///
+/// Every decision is recorded as an offset from the lower end of its range, so replaying a
+/// modified recording regenerates a valid test where smaller offsets give smaller values.
+pub struct Choices {
+	source: Source,
+	recorded: Vec<u64>,
+	/// The size of the test, if the session has one.
+	size: Option<i64>,
+}
+
+enum Source {
+	Random(Random),
+	Replay(vec::IntoIter<u64>),
+	/// Replays the choices which are present and chooses the others at random, as well as any
+	/// after the end.
+	Mutate(vec::IntoIter<Option<u64>>, Random),
+	/// Replays the choices and picks the lowest value within `upto` of zero after them, recording
+	/// the highest offset every choice could have taken within it.
+	Exhaustive {
+		replay: vec::IntoIter<u64>,
+		upto: i64,
+		highest: Vec<u64>,
+	},
+}
+
+/// Derives the seed of a single test from the seed of the session, such that every test can be
+/// regenerated on its own.
+pub fn test_seed(seed: u64, test: u64) -> u64 {
+	Random::with_seed(seed ^ test.wrapping_mul(0x9e37_79b9_7f4a_7c15)).u64()
+}
+
+impl Choices {
+	pub fn random(seed: u64) -> Choices {
+		Choices {
+			source: Source::Random(Random::with_seed(seed)),
+			recorded: Vec::new(),
+			size: None,
+		}
+	}
+
+	/// Replays the given choices, picking the lowest possible value once they run out.
+	pub fn replay(choices: Vec<u64>) -> Choices {
+		Choices {
+			source: Source::Replay(choices.into_iter()),
+			recorded: Vec::new(),
+			size: None,
+		}
+	}
+
+	/// Mutates recorded choices by choosing the missing ones at random.
+	pub fn mutate(choices: Vec<Option<u64>>, seed: u64) -> Choices {
+		Choices {
+			source: Source::Mutate(choices.into_iter(), Random::with_seed(seed)),
+			recorded: Vec::new(),
+			size: None,
+		}
+	}
+
+	/// Enumerates the tests whose numbers are all within `upto` of zero, or as close to it as their
+	/// ranges allow, starting from the one with these choices. See [`Self::next_exhaustive`].
+	pub fn exhaustive(choices: Vec<u64>, upto: i64) -> Choices {
+		Choices {
+			source: Source::Exhaustive {
+				replay: choices.into_iter(),
+				upto,
+				highest: Vec::new(),
+			},
+			recorded: Vec::new(),
+			size: None,
+		}
+	}
+
+	/// The choices of the test after this one when enumerating them with [`Self::exhaustive`],
+	/// which increments the last choice which can be and drops those after it. None once every
+	/// test has been enumerated.
+	pub fn next_exhaustive(&self) -> Option<Vec<u64>> {
+		let Source::Exhaustive { highest, .. } = &self.source else {
+			return None;
+		};
+		let i = self
+			.recorded
+			.iter()
+			.zip(highest)
+			.rposition(|(offset, highest)| offset < highest)?;
+		let mut next = self.recorded[..i].to_vec();
+		next.push(self.recorded[i] + 1);
+		Some(next)
+	}
+
+	/// Generates the test at the size, which the specification or generator reads as `size`.
+	pub fn sized(self, size: Option<i64>) -> Choices {
+		Choices { size, ..self }
+	}
+
+	pub fn size(&self) -> Option<i64> {
+		self.size
+	}
+
+	pub fn i64(&mut self, lower: i64, higher: i64) -> i64 {
+		assert!(lower <= higher, "empty range {lower}..={higher}");
+		let range = higher.abs_diff(lower);
+		let offset = match &mut self.source {
+			Source::Random(random) => random.up_to(range),
+			Source::Replay(replay) => replay.next().unwrap_or(0).min(range),
+			Source::Mutate(base, random) => match base.next().flatten() {
+				Some(offset) => offset.min(range),
+				None => random.up_to(range),
+			},
+			Source::Exhaustive {
+				replay,
+				upto,
+				highest,
+			} => {
+				let (low, high) = (lower.max(-*upto), higher.min(*upto));
+				let (low, high) = match low <= high {
+					true => (low, high),
+					false if lower > *upto => (lower, lower),
+					false => (higher, higher),
+				};
+				let (first, last) = (low.abs_diff(lower), high.abs_diff(lower));
+				highest.push(last);
+				replay.next().unwrap_or(first).clamp(first, last)
+			}
+		};
+		self.recorded.push(offset);
+		lower.wrapping_add_unsigned(offset)
+	}
+
+	/// Shuffles the slice such that replaying all zero choices leaves it unchanged.
+	pub fn shuffle<T>(&mut self, slice: &mut [T]) {
+		for i in (1..slice.len()).rev() {
+			let j = i - self.i64(0, i as i64) as usize;
+			slice.swap(i, j);
+		}
+	}
+
+	pub fn recorded(&self) -> &[u64] {
+		&self.recorded
+	}
+
+	pub fn into_recorded(self) -> Vec<u64> {
+		self.recorded
+	}
+}
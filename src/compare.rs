@@ -0,0 +1,416 @@
+use std::io::{self, BufRead};
+
+use crate::args::{self, DiffMode};
+
+/// How the outputs of the primary and secondary are compared. They are compared as bytes, so an
+/// output which is not UTF-8 is never taken as equal to another one.
+pub struct Comparison {
+	mode: DiffMode,
+	float_eps: Option<f64>,
+}
+
+/// The first token at which two outputs differ.
+pub struct Divergence<'a> {
+	/// Line of the token, counting from one.
+	pub line: usize,
+	/// Index of the token in the whole output, counting from one.
+	pub token: usize,
+	/// The token of the output, or none if it ended.
+	pub output: Option<&'a [u8]>,
+	/// The token of the expected output, or none if it ended.
+	pub expected: Option<&'a [u8]>,
+}
+
+impl Divergence<'_> {
+	/// Where the outputs differ, such as `First difference at line 2, token 5: '3' where '4' was
+	/// expected`.
+	pub fn describe(&self) -> String {
+		// A token which is not UTF-8 is shown escaped, as it would not tell otherwise.
+		let describe = |token: Option<&[u8]>| match token.map(std::str::from_utf8) {
+			Some(Ok(token)) => format!("'{token}'"),
+			Some(Err(_)) => format!("'{}'", token.unwrap_or_default().escape_ascii()),
+			None => "end of output".to_string(),
+		};
+		format!(
+			"First difference at line {}, token {}: {} where {} was expected",
+			self.line,
+			self.token,
+			describe(self.output),
+			describe(self.expected),
+		)
+	}
+}
+
+/// A line of two outputs side by side, as their tokens with whether each differs from the one
+/// across from it.
+pub type Row<'a> = [Vec<(&'a [u8], bool)>; 2];
+
+/// The whitespace separated tokens of the output.
+fn tokens(output: &[u8]) -> impl Iterator<Item = &[u8]> {
+	output
+		.split(u8::is_ascii_whitespace)
+		.filter(|token| !token.is_empty())
+}
+
+/// The lines of the output without their line endings.
+pub fn lines(output: &[u8]) -> impl DoubleEndedIterator<Item = &[u8]> {
+	let output = output.strip_suffix(b"\n").unwrap_or(output);
+	output
+		.split(|&b| b == b'\n')
+		.map(|line| line.strip_suffix(b"\r").unwrap_or(line))
+}
+
+/// The tokens of the output together with their line numbers.
+fn numbered_tokens(output: &[u8]) -> impl Iterator<Item = (usize, &[u8])> {
+	lines(output)
+		.enumerate()
+		.flat_map(|(i, line)| tokens(line).map(move |token| (i + 1, token)))
+}
+
+/// Reads the next whitespace separated token into `token`, returning false once there are no
+/// more.
+fn read_token(reader: &mut impl BufRead, token: &mut Vec<u8>) -> io::Result<bool> {
+	token.clear();
+	loop {
+		let buf = reader.fill_buf()?;
+		if buf.is_empty() {
+			return Ok(!token.is_empty());
+		}
+		let mut used = 0;
+		let mut ended = false;
+		for &b in buf {
+			used += 1;
+			if !b.is_ascii_whitespace() {
+				token.push(b);
+			} else if !token.is_empty() {
+				ended = true;
+				break;
+			}
+		}
+		reader.consume(used);
+		if ended {
+			return Ok(true);
+		}
+	}
+}
+
+/// Whether the token is an integer, which is always compared exactly as large integers can not be
+/// represented as floats.
+fn is_integer(token: &[u8]) -> bool {
+	let digits = match token.first() {
+		Some(b'-' | b'+') => &token[1..],
+		_ => token,
+	};
+	!digits.is_empty() && digits.iter().all(u8::is_ascii_digit)
+}
+
+/// The token as a float, if it is one.
+fn float(token: &[u8]) -> Option<f64> {
+	std::str::from_utf8(token).ok()?.parse().ok()
+}
+
+impl Comparison {
+	pub fn new(args: &args::Args) -> Comparison {
+		Comparison::with(args.diff_mode, args.float_eps)
+	}
+
+	pub fn with(mode: DiffMode, float_eps: Option<f64>) -> Comparison {
+		Comparison { mode, float_eps }
+	}
+
+	fn tokens_equal(&self, a: &[u8], b: &[u8]) -> bool {
+		if a == b {
+			return true;
+		}
+		let Some(eps) = self.float_eps else {
+			return false;
+		};
+		if is_integer(a) && is_integer(b) {
+			return false;
+		}
+		match (float(a), float(b)) {
+			(Some(a), Some(b)) if a.is_finite() && b.is_finite() => {
+				let difference = (a - b).abs();
+				difference <= eps || difference <= eps * a.abs().max(b.abs())
+			}
+			_ => false,
+		}
+	}
+
+	fn token_sequences_equal<'a>(
+		&self,
+		mut a: impl Iterator<Item = &'a [u8]>,
+		mut b: impl Iterator<Item = &'a [u8]>,
+	) -> bool {
+		loop {
+			match (a.next(), b.next()) {
+				(None, None) => return true,
+				(Some(a), Some(b)) if self.tokens_equal(a, b) => {}
+				_ => return false,
+			}
+		}
+	}
+
+	/// The lines of the output with trailing empty lines removed.
+	fn lines(output: &[u8]) -> Vec<&[u8]> {
+		let mut lines: Vec<_> = lines(output).collect();
+		while lines
+			.last()
+			.is_some_and(|line| line.trim_ascii().is_empty())
+		{
+			lines.pop();
+		}
+		lines
+	}
+
+	pub fn equal(&self, a: &[u8], b: &[u8]) -> bool {
+		match self.mode {
+			DiffMode::Exact => a == b,
+			DiffMode::Tokens => self.token_sequences_equal(tokens(a), tokens(b)),
+			DiffMode::Lines => {
+				let a = Self::lines(a);
+				let b = Self::lines(b);
+				a.len() == b.len()
+					&& a.iter()
+						.zip(&b)
+						.all(|(a, b)| self.token_sequences_equal(tokens(a), tokens(b)))
+			}
+			DiffMode::UnorderedLines => {
+				let normalize = |output| -> Vec<Vec<&[u8]>> {
+					let mut lines: Vec<_> = Self::lines(output)
+						.into_iter()
+						.map(|line| tokens(line).collect())
+						.collect();
+					lines.sort();
+					lines
+				};
+				let a = normalize(a);
+				let b = normalize(b);
+				a.len() == b.len()
+					&& a.iter().zip(&b).all(|(a, b)| {
+						self.token_sequences_equal(a.iter().copied(), b.iter().copied())
+					})
+			}
+			DiffMode::SortedTokens => {
+				let mut a: Vec<_> = tokens(a).collect();
+				let mut b: Vec<_> = tokens(b).collect();
+				a.sort_unstable();
+				b.sort_unstable();
+				self.token_sequences_equal(a.into_iter(), b.into_iter())
+			}
+		}
+	}
+
+	/// Whether two outputs are equal, comparing them as they are read so neither is held in memory
+	/// as a whole. None in the modes which need the whole outputs.
+	pub fn equal_streams(
+		&self,
+		mut a: impl BufRead,
+		mut b: impl BufRead,
+	) -> io::Result<Option<bool>> {
+		match self.mode {
+			DiffMode::Exact => loop {
+				let (a_buf, b_buf) = (a.fill_buf()?, b.fill_buf()?);
+				let n = a_buf.len().min(b_buf.len());
+				if n == 0 {
+					return Ok(Some(a_buf.is_empty() && b_buf.is_empty()));
+				}
+				if a_buf[..n] != b_buf[..n] {
+					return Ok(Some(false));
+				}
+				a.consume(n);
+				b.consume(n);
+			},
+			DiffMode::Tokens => {
+				let (mut a_token, mut b_token) = (Vec::new(), Vec::new());
+				loop {
+					match (
+						read_token(&mut a, &mut a_token)?,
+						read_token(&mut b, &mut b_token)?,
+					) {
+						(false, false) => return Ok(Some(true)),
+						(true, true) if self.tokens_equal(&a_token, &b_token) => {}
+						_ => return Ok(Some(false)),
+					}
+				}
+			}
+			_ => Ok(None),
+		}
+	}
+
+	/// Groups the labelled outputs into groups of equal outputs, largest group first.
+	pub fn group<'a>(&self, outputs: &[(&'a str, &[u8])]) -> Vec<Vec<&'a str>> {
+		let mut groups: Vec<(&[u8], Vec<&str>)> = Vec::new();
+		for &(label, output) in outputs {
+			match groups
+				.iter_mut()
+				.find(|(representative, _)| self.equal(representative, output))
+			{
+				Some((_, labels)) => labels.push(label),
+				None => groups.push((output, vec![label])),
+			}
+		}
+		groups.sort_by_key(|(_, labels)| std::cmp::Reverse(labels.len()));
+		groups.into_iter().map(|(_, labels)| labels).collect()
+	}
+
+	/// The lines of the outputs side by side, with the tokens which differ marked. The lines are
+	/// sorted in the modes which do not care for their order, and with sorted tokens every token
+	/// is a line of its own.
+	pub fn side_by_side<'a>(&self, output: &'a [u8], expected: &'a [u8]) -> Vec<Row<'a>> {
+		let lines = |output: &'a [u8]| -> Vec<Vec<&'a [u8]>> {
+			match self.mode {
+				DiffMode::Exact | DiffMode::Tokens | DiffMode::Lines => Self::lines(output)
+					.into_iter()
+					.map(|line| tokens(line).collect())
+					.collect(),
+				DiffMode::UnorderedLines => {
+					let mut lines: Vec<Vec<_>> = Self::lines(output)
+						.into_iter()
+						.map(|line| tokens(line).collect())
+						.collect();
+					lines.sort();
+					lines
+				}
+				DiffMode::SortedTokens => {
+					let mut tokens: Vec<_> = tokens(output).collect();
+					tokens.sort_unstable();
+					tokens.into_iter().map(|token| vec![token]).collect()
+				}
+			}
+		};
+		let equal = |a, b| match self.mode {
+			DiffMode::Exact => a == b,
+			_ => self.tokens_equal(a, b),
+		};
+		let (output, expected) = (lines(output), lines(expected));
+		(0..output.len().max(expected.len()))
+			.map(|i| {
+				let (a, b) = (
+					output.get(i).map_or(&[][..], Vec::as_slice),
+					expected.get(i).map_or(&[][..], Vec::as_slice),
+				);
+				let mark = |line: &[&'a [u8]], other: &[&'a [u8]]| {
+					line.iter()
+						.enumerate()
+						.map(|(j, &token)| {
+							let differs = other.get(j).is_none_or(|&other| !equal(token, other));
+							(token, differs)
+						})
+						.collect()
+				};
+				[mark(a, b), mark(b, a)]
+			})
+			.collect()
+	}
+
+	/// The first differing token of two outputs. Only the modes which compare the tokens in order
+	/// have a meaningful first difference, and outputs differing only in whitespace have none.
+	pub fn divergence<'a>(&self, output: &'a [u8], expected: &'a [u8]) -> Option<Divergence<'a>> {
+		if matches!(self.mode, DiffMode::UnorderedLines | DiffMode::SortedTokens) {
+			return None;
+		}
+		let mut a = numbered_tokens(output);
+		let mut b = numbered_tokens(expected);
+		for token in 1.. {
+			match (a.next(), b.next()) {
+				(None, None) => return None,
+				(Some((_, a)), Some((_, b))) if self.tokens_equal(a, b) => {}
+				(a, b) => {
+					return Some(Divergence {
+						line: a.or(b).map_or(0, |(line, _)| line),
+						token,
+						output: a.map(|(_, token)| token),
+						expected: b.map(|(_, token)| token),
+					});
+				}
+			}
+		}
+		unreachable!("the outputs are finite")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn modes() {
+		let exact = Comparison::with(DiffMode::Exact, None);
+		assert!(exact.equal(b"1 2\n", b"1 2\n"));
+		assert!(!exact.equal(b"1 2\n", b"1 2"));
+		let tokens = Comparison::with(DiffMode::Tokens, None);
+		assert!(tokens.equal(b"1  2\n3\n", b"1 2 3"));
+		assert!(!tokens.equal(b"1 2", b"1 2 3"));
+		let lines = Comparison::with(DiffMode::Lines, None);
+		assert!(lines.equal(b"1  2\r\n3\n\n", b"1 2\n3"));
+		assert!(!lines.equal(b"1 2\n3", b"1\n2 3"));
+		let unordered = Comparison::with(DiffMode::UnorderedLines, None);
+		assert!(unordered.equal(b"3 4\n1 2\n", b"1 2\n3  4"));
+		assert!(!unordered.equal(b"1 2\n3 4", b"2 1\n3 4"));
+		let sorted = Comparison::with(DiffMode::SortedTokens, None);
+		assert!(sorted.equal(b"3 1\n2", b"1 2 3"));
+		assert!(!sorted.equal(b"1 1 2", b"1 2 2"));
+	}
+
+	#[test]
+	fn float_eps() {
+		let comparison = Comparison::with(DiffMode::Tokens, Some(1e-6));
+		assert!(comparison.equal(b"0.1000001", b"0.1"));
+		assert!(!comparison.equal(b"0.100002", b"0.1"));
+		// Relative to large values.
+		assert!(comparison.equal(b"1000000.5", b"1000000"));
+		// Integers are compared exactly, however large.
+		assert!(!comparison.equal(b"10000000000000000001", b"10000000000000000000"));
+		assert!(comparison.equal(b"1.0", b"1"));
+		assert!(!comparison.equal(b"nan", b"NaN"));
+		assert!(!comparison.equal(b"inf", b"1e308"));
+		assert!(!Comparison::with(DiffMode::Tokens, None).equal(b"1.0", b"1"));
+	}
+
+	#[test]
+	fn streams() {
+		let comparison = Comparison::with(DiffMode::Tokens, None);
+		let equal = |a: &[u8], b: &[u8]| comparison.equal_streams(a, b).unwrap();
+		assert_eq!(equal(b"1  2\n3", b"1 2 3\n"), Some(true));
+		assert_eq!(equal(b"1 2", b"1 2 3"), Some(false));
+		let lines = Comparison::with(DiffMode::Lines, None);
+		assert_eq!(lines.equal_streams(&b"1"[..], &b"1"[..]).unwrap(), None);
+	}
+
+	#[test]
+	fn divergence() {
+		let comparison = Comparison::with(DiffMode::Tokens, None);
+		let divergence = comparison.divergence(b"1 2\n3 5\n", b"1 2\n3 4").unwrap();
+		assert_eq!(
+			divergence.describe(),
+			"First difference at line 2, token 4: '5' where '4' was expected"
+		);
+		let divergence = comparison.divergence(b"1\n", b"1\n2").unwrap();
+		assert_eq!(
+			divergence.describe(),
+			"First difference at line 2, token 2: end of output where '2' was expected"
+		);
+		assert!(comparison.divergence(b"1  2", b"1 2\n").is_none());
+		let sorted = Comparison::with(DiffMode::SortedTokens, None);
+		assert!(sorted.divergence(b"1", b"2").is_none());
+	}
+
+	#[test]
+	fn side_by_side() {
+		let comparison = Comparison::with(DiffMode::Tokens, None);
+		let rows = comparison.side_by_side(b"1 2\n3", b"1 5\n");
+		let [output, expected] = &rows[0];
+		assert_eq!(output, &[(&b"1"[..], false), (&b"2"[..], true)]);
+		assert_eq!(expected, &[(&b"1"[..], false), (&b"5"[..], true)]);
+		assert_eq!(rows[1], [vec![(&b"3"[..], true)], vec![]]);
+		assert_eq!(rows.len(), 2);
+	}
+
+	#[test]
+	fn groups() {
+		let comparison = Comparison::with(DiffMode::Tokens, None);
+		let groups = comparison.group(&[("a", b"1"), ("b", b"2"), ("c", b"1 ")]);
+		assert_eq!(groups, [vec!["a", "c"], vec!["b"]]);
+	}
+}
@@ -0,0 +1,333 @@
+use std::{fmt::Write as _, path::Path, process::ExitCode};
+
+use clap::{ArgAction, CommandFactory as _};
+
+use crate::{
+	args::{Cli, CompletionsArgs, Shell},
+	cargo,
+	error::Error,
+};
+
+/// Arguments naming a program, which complete to the C++ files and Cargo binaries.
+const PROGRAMS: &[&str] = &[
+	"name",
+	"compare",
+	"verify",
+	"checker",
+	"interactive",
+	"reference",
+	"input_validator",
+];
+
+/// What the value of an argument completes to.
+enum Kind {
+	Values(Vec<String>),
+	Program,
+	File,
+	Directory,
+	/// Anything, such as a number, which is not completed.
+	Text,
+}
+
+impl Kind {
+	fn of(arg: &clap::Arg) -> Kind {
+		let values: Vec<_> = arg
+			.get_possible_values()
+			.iter()
+			.filter(|value| !value.is_hide_set())
+			.map(|value| value.get_name().to_string())
+			.collect();
+		let name = arg
+			.get_value_names()
+			.and_then(|names| names.first())
+			.map(|name| name.as_str());
+		if !values.is_empty() {
+			Kind::Values(values)
+		} else if PROGRAMS.contains(&arg.get_id().as_str()) {
+			Kind::Program
+		} else if arg.get_id() == "specification" {
+			Kind::File
+		} else {
+			match name {
+				Some("DIR") => Kind::Directory,
+				Some("FILE" | "PATH" | "INPUT") => Kind::File,
+				_ => Kind::Text,
+			}
+		}
+	}
+
+	fn bash(&self) -> String {
+		match self {
+			Kind::Values(values) => format!("COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))", values.join(" ")),
+			Kind::Program => "COMPREPLY=($(compgen -W \"$(cpfuzz completions --programs 2>/dev/null)\" -- \"$cur\"))".to_string(),
+			Kind::File => "COMPREPLY=($(compgen -f -- \"$cur\"))".to_string(),
+			Kind::Directory => "COMPREPLY=($(compgen -d -- \"$cur\"))".to_string(),
+			Kind::Text => "COMPREPLY=()".to_string(),
+		}
+	}
+
+	fn fish(&self) -> String {
+		match self {
+			Kind::Values(values) => format!("-x -a '{}'", values.join(" ")),
+			Kind::Program => "-x -a '(__cpfuzz_programs)'".to_string(),
+			Kind::File => "-r -F".to_string(),
+			Kind::Directory => "-x -a '(__fish_complete_directories)'".to_string(),
+			Kind::Text => "-x".to_string(),
+		}
+	}
+}
+
+/// A command of cpfuzz: its options and positional arguments.
+struct Command {
+	name: String,
+	/// The long and short flags of the option with its description, and what its value completes
+	/// to unless it is a flag.
+	options: Vec<(Vec<String>, String, Option<Kind>)>,
+	positionals: Vec<Kind>,
+	/// Whether the last positional argument takes any number of values.
+	variadic: bool,
+}
+
+impl Command {
+	fn new(name: &str, command: &clap::Command) -> Command {
+		let mut options = Vec::new();
+		let mut positionals = Vec::new();
+		let mut variadic = false;
+		for arg in command.get_arguments().filter(|arg| !arg.is_hide_set()) {
+			if arg.is_positional() {
+				positionals.push(Kind::of(arg));
+				variadic = matches!(arg.get_action(), ArgAction::Append);
+				continue;
+			}
+			let flags = arg
+				.get_long()
+				.map(|long| format!("--{long}"))
+				.into_iter()
+				.chain(arg.get_short().map(|short| format!("-{short}")))
+				.collect();
+			let help = arg
+				.get_help()
+				.map(|help| help.to_string())
+				.unwrap_or_default();
+			let help = help
+				.split(". ")
+				.next()
+				.unwrap_or("")
+				.trim_end_matches('.')
+				.to_string();
+			let kind = arg.get_action().takes_values().then(|| Kind::of(arg));
+			options.push((flags, help, kind));
+		}
+		Command {
+			name: name.to_string(),
+			options,
+			positionals,
+			variadic,
+		}
+	}
+}
+
+/// The commands of cpfuzz, the first being cpfuzz itself.
+fn commands() -> (Vec<Command>, Vec<(String, String)>) {
+	let mut cli = Cli::command();
+	cli.build();
+	let mut commands = vec![Command::new("cpfuzz", &cli)];
+	let mut subcommands = Vec::new();
+	for subcommand in cli.get_subcommands().filter(|s| s.get_name() != "help") {
+		let about = subcommand
+			.get_about()
+			.map(|about| about.to_string())
+			.unwrap_or_default();
+		let about = about
+			.split(". ")
+			.next()
+			.unwrap_or("")
+			.trim_end_matches('.')
+			.to_string();
+		subcommands.push((subcommand.get_name().to_string(), about));
+		commands.push(Command::new(subcommand.get_name(), subcommand));
+	}
+	(commands, subcommands)
+}
+
+fn bash() -> String {
+	let (commands, subcommands) = commands();
+	let names: Vec<_> = subcommands.iter().map(|(name, _)| name.as_str()).collect();
+	let mut script = String::new();
+	let mut line = |line: &str| {
+		script.push_str(line);
+		script.push('\n');
+	};
+	line("_cpfuzz() {");
+	line("\tlocal cur=${COMP_WORDS[COMP_CWORD]} prev=${COMP_WORDS[COMP_CWORD-1]}");
+	line("\tlocal command=cpfuzz positional=0 skip= i");
+	line("\tfor ((i = 1; i < COMP_CWORD; i++)); do");
+	line("\t\tif [[ -n $skip ]]; then skip=; continue; fi");
+	line("\t\tcase $command:${COMP_WORDS[i]} in");
+	for command in &commands {
+		let valued: Vec<_> = command
+			.options
+			.iter()
+			.filter(|(_, _, kind)| kind.is_some())
+			.flat_map(|(flags, _, _)| flags.iter().map(|flag| format!("{}:{flag}", command.name)))
+			.collect();
+		if !valued.is_empty() {
+			line(&format!("\t\t{}) skip=1 ;;", valued.join("|")));
+		}
+	}
+	line("\t\t*:-*) ;;");
+	let switch: Vec<_> = names.iter().map(|name| format!("cpfuzz:{name}")).collect();
+	line(&format!(
+		"\t\t{}) if ((positional == 0)); then command=${{COMP_WORDS[i]}}; else ((positional++)); fi ;;",
+		switch.join("|")
+	));
+	line("\t\t*) ((positional++)) ;;");
+	line("\t\tesac");
+	line("\tdone");
+	line("\tcase $command:$prev in");
+	for command in &commands {
+		for (flags, _, kind) in &command.options {
+			if let Some(kind) = kind {
+				let patterns: Vec<_> = flags
+					.iter()
+					.map(|flag| format!("{}:{flag}", command.name))
+					.collect();
+				line(&format!(
+					"\t{}) {}; return ;;",
+					patterns.join("|"),
+					kind.bash()
+				));
+			}
+		}
+	}
+	line("\tesac");
+	line("\tif [[ $cur == -* ]]; then");
+	line("\t\tcase $command in");
+	for command in &commands {
+		let flags: Vec<_> = command
+			.options
+			.iter()
+			.flat_map(|(flags, _, _)| flags.clone())
+			.collect();
+		line(&format!(
+			"\t\t{}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")) ;;",
+			command.name,
+			flags.join(" ")
+		));
+	}
+	line("\t\tesac");
+	line("\t\treturn");
+	line("\tfi");
+	line("\tcase $command:$positional in");
+	for command in &commands {
+		for (i, kind) in command.positionals.iter().enumerate() {
+			let mut action = kind.bash();
+			// Subcommands go where the first positional argument of cpfuzz does.
+			if command.name == "cpfuzz" && i == 0 {
+				action = format!(
+					"{action}; COMPREPLY+=($(compgen -W \"{}\" -- \"$cur\"))",
+					names.join(" ")
+				);
+			}
+			let pattern = match command.variadic && i + 1 == command.positionals.len() {
+				true => "*".to_string(),
+				false => i.to_string(),
+			};
+			line(&format!("\t{}:{pattern}) {action} ;;", command.name));
+		}
+	}
+	line("\tesac");
+	line("}");
+	line("complete -F _cpfuzz cpfuzz");
+	script
+}
+
+fn zsh() -> String {
+	format!(
+		"#compdef cpfuzz\nautoload -U +X bashcompinit && bashcompinit\n{}",
+		bash()
+	)
+}
+
+fn fish() -> String {
+	let (commands, subcommands) = commands();
+	let names: Vec<_> = subcommands.iter().map(|(name, _)| name.as_str()).collect();
+	let quote = |text: &str| format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"));
+	let mut script = String::new();
+	writeln!(
+		script,
+		"function __cpfuzz_programs; cpfuzz completions --programs 2>/dev/null; end"
+	)
+	.expect("write to memory");
+	writeln!(script, "complete -c cpfuzz -f").expect("write to memory");
+	for command in &commands {
+		let condition = match command.name.as_str() {
+			"cpfuzz" => format!("not __fish_seen_subcommand_from {}", names.join(" ")),
+			name => format!("__fish_seen_subcommand_from {name}"),
+		};
+		let complete = format!("complete -c cpfuzz -n {}", quote(&condition));
+		if command.name == "cpfuzz" {
+			for (name, about) in &subcommands {
+				writeln!(script, "{complete} -a {name} -d {}", quote(about))
+					.expect("write to memory");
+			}
+		}
+		// Fish does not tell the positional arguments apart, so all of them are offered.
+		for kind in &command.positionals {
+			writeln!(script, "{complete} {}", kind.fish()).expect("write to memory");
+		}
+		for (flags, help, kind) in &command.options {
+			let mut options = String::new();
+			for flag in flags {
+				match flag.strip_prefix("--") {
+					Some(long) => write!(options, " -l {long}"),
+					None => write!(options, " -s {}", &flag[1..]),
+				}
+				.expect("write to memory");
+			}
+			if let Some(kind) = kind {
+				write!(options, " {}", kind.fish()).expect("write to memory");
+			}
+			writeln!(script, "{complete}{options} -d {}", quote(help)).expect("write to memory");
+		}
+	}
+	script
+}
+
+/// The programs in the working directory: the C++ files and the binaries of the Cargo workspace.
+fn programs() -> Vec<String> {
+	let mut programs = Vec::new();
+	if let Ok(entries) = std::fs::read_dir(".") {
+		for entry in entries.flatten() {
+			let path = entry.path();
+			if path.extension().is_some_and(|extension| extension == "cpp")
+				&& let Some(stem) = path.file_stem()
+			{
+				programs.push(stem.to_string_lossy().into_owned());
+			}
+		}
+	}
+	if Path::new("Cargo.toml").exists() {
+		programs.extend(cargo::bins());
+	}
+	programs.sort();
+	programs.dedup();
+	programs
+}
+
+/// Prints the completion script of the shell, or the programs for the scripts to complete.
+pub fn completions(args: &CompletionsArgs) -> Result<ExitCode, Error> {
+	if args.programs {
+		for program in programs() {
+			println!("{program}");
+		}
+		return Ok(ExitCode::SUCCESS);
+	}
+	let script = match args.shell.expect("required without --programs") {
+		Shell::Bash => bash(),
+		Shell::Zsh => zsh(),
+		Shell::Fish => fish(),
+	};
+	print!("{script}");
+	Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,201 @@
+use std::{io, time::Duration};
+
+use crate::{
+	args::{Args, Language, parse_memory},
+	budget::parse_duration,
+};
+
+/// The configuration read from the working directory, whose `[profile.NAME]` tables are the
+/// profiles of --profile.
+const PATH: &str = "cpfuzz.toml";
+
+/// The options a profile can set, by their names on the command line.
+const KEYS: [&str; 8] = [
+	"max-tests",
+	"max-time",
+	"time-limit",
+	"compare-time-limit",
+	"memory-limit",
+	"size",
+	"adaptive-size",
+	"sanitize",
+];
+
+/// A value of the subset of TOML profiles are written in.
+enum Value {
+	String(String),
+	Integer(i64),
+	Boolean(bool),
+}
+
+impl Value {
+	fn parse(src: &str) -> Option<Value> {
+		let (value, rest) = if let Some(rest) = src.strip_prefix('"') {
+			let mut string = String::new();
+			let mut chars = rest.char_indices();
+			let end = loop {
+				match chars.next()? {
+					(i, '"') => break i,
+					(_, '\\') => string.push(chars.next()?.1),
+					(_, c) => string.push(c),
+				}
+			};
+			(Value::String(string), &rest[end + 1..])
+		} else {
+			let end = src.find([' ', '\t', '#']).unwrap_or(src.len());
+			let (token, rest) = src.split_at(end);
+			let value = match token {
+				"true" => Value::Boolean(true),
+				"false" => Value::Boolean(false),
+				_ => Value::Integer(token.replace('_', "").parse().ok()?),
+			};
+			(value, rest)
+		};
+		let rest = rest.trim_start();
+		(rest.is_empty() || rest.starts_with('#')).then_some(value)
+	}
+}
+
+/// The options of the profile as they are written in the configuration, with their lines.
+fn profile(src: &str, name: &str) -> io::Result<Option<Vec<(usize, String, Value)>>> {
+	let invalid = |line: usize, message: &str| {
+		io::Error::new(
+			io::ErrorKind::InvalidData,
+			format!("{PATH}:{line}: {message}"),
+		)
+	};
+	let mut options = None;
+	let mut table = None;
+	for (i, line) in src.lines().enumerate() {
+		let line = line.trim();
+		if line.is_empty() || line.starts_with('#') {
+			continue;
+		}
+		if let Some(header) = line.strip_prefix('[') {
+			let Some((header, _)) = header.split_once(']') else {
+				return Err(invalid(i + 1, "unclosed table header"));
+			};
+			table = header.trim().strip_prefix("profile.").map(str::to_string);
+			if table.as_deref() == Some(name) {
+				options = Some(Vec::new());
+			}
+			continue;
+		}
+		let Some((key, value)) = line.split_once('=') else {
+			return Err(invalid(i + 1, "expected `key = value`"));
+		};
+		// Only the profile asked for is checked, other tables may be for newer versions.
+		if table.as_deref() != Some(name) {
+			continue;
+		}
+		let key = key.trim();
+		if !KEYS.contains(&key) {
+			let message = format!(
+				"unknown option '{key}', expected one of {}",
+				KEYS.join(", ")
+			);
+			return Err(invalid(i + 1, &message));
+		}
+		let Some(value) = Value::parse(value.trim()) else {
+			return Err(invalid(
+				i + 1,
+				"expected a string, an integer, true or false",
+			));
+		};
+		if let Some(options) = &mut options {
+			options.push((i + 1, key.to_string(), value));
+		}
+	}
+	Ok(options)
+}
+
+/// Applies the profile given with --profile to the options not given on the command line.
+pub fn apply(args: &mut Args) -> io::Result<()> {
+	let Some(name) = args.profile.clone() else {
+		return Ok(());
+	};
+	let src = match std::fs::read_to_string(PATH) {
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!("--profile {name} is read from {PATH}, which does not exist"),
+			));
+		}
+		src => src?,
+	};
+	let Some(options) = profile(&src, &name)? else {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("{PATH} has no profile '{name}', which is the table [profile.{name}]"),
+		));
+	};
+	for (line, key, value) in options {
+		let invalid = |expected: &str| {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("{PATH}:{line}: {key} must be {expected}"),
+			)
+		};
+		let value = &value;
+		let duration = || match value {
+			Value::String(duration) => parse_duration(duration).map_err(|e| invalid(&e)),
+			_ => Err(invalid("a duration such as \"2s\"")),
+		};
+		let positive = || match value {
+			Value::Integer(n) if *n > 0 => Ok(*n),
+			_ => Err(invalid("a positive integer")),
+		};
+		let boolean = || match value {
+			Value::Boolean(b) => Ok(*b),
+			_ => Err(invalid("true or false")),
+		};
+		match key.as_str() {
+			"max-tests" => {
+				let max_tests = positive()? as u64;
+				if args.max_tests.is_none() && args.until_pass.is_none() {
+					args.max_tests = Some(max_tests);
+				}
+			}
+			"max-time" => {
+				args.max_time = Some(args.max_time.map_or_else(duration, Ok)?);
+			}
+			"time-limit" => {
+				args.time_limit = Some(args.time_limit.map_or_else(duration, Ok)?);
+			}
+			"compare-time-limit" => {
+				let limit: Duration = args.compare_time_limit.map_or_else(duration, Ok)?;
+				// Comparators are only limited apart from the solution when there are any.
+				if !args.compare.is_empty() {
+					args.compare_time_limit = Some(limit);
+				}
+			}
+			"memory-limit" => {
+				let limit = match value {
+					Value::String(limit) => parse_memory(limit).map_err(|e| invalid(&e))?,
+					_ => return Err(invalid("a memory amount such as \"256M\"")),
+				};
+				args.memory_limit = Some(args.memory_limit.unwrap_or(limit));
+			}
+			"size" => {
+				let size = positive()?;
+				args.size = Some(args.size.unwrap_or(size));
+			}
+			"adaptive-size" => args.adaptive_size |= boolean()?,
+			"sanitize" if boolean()? => {
+				args.language = match args.language {
+					Language::Cpp => Language::CppSanitize,
+					Language::Rust => Language::RustDebug,
+					language => language,
+				};
+			}
+			_ => {}
+		}
+	}
+	if args.adaptive_size && args.size.is_none() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!("the profile '{name}' ramps the size up, which needs a size to ramp up to"),
+		));
+	}
+	Ok(())
+}
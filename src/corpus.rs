@@ -0,0 +1,54 @@
+use std::{
+	hash::{DefaultHasher, Hash as _, Hasher as _},
+	io,
+	path::{Path, PathBuf},
+};
+
+use crate::generator::Test;
+
+/// The `.in` files of the corpus directory in sorted order. A missing directory is an empty
+/// corpus, as it is created once the first counterexample is saved.
+pub fn inputs(dir: &Path) -> io::Result<Vec<PathBuf>> {
+	let mut inputs = Vec::new();
+	let entries = match std::fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(inputs),
+		Err(e) => return Err(e),
+	};
+	for entry in entries {
+		let path = entry?.path();
+		if path.extension().is_some_and(|extension| extension == "in") {
+			inputs.push(path);
+		}
+	}
+	inputs.sort();
+	Ok(inputs)
+}
+
+/// Every file of the directory in sorted order, except the `.ans` files holding the answers of
+/// the others.
+pub fn files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+	let mut inputs = Vec::new();
+	for entry in std::fs::read_dir(dir)? {
+		let entry = entry?;
+		let path = entry.path();
+		if entry.file_type()?.is_file()
+			&& path.extension().is_none_or(|extension| extension != "ans")
+		{
+			inputs.push(path);
+		}
+	}
+	inputs.sort();
+	Ok(inputs)
+}
+
+/// Saves a test to the corpus directory, named by the hash of the input so saving it again is a
+/// no-op.
+pub fn save(dir: &Path, test: &Test) -> io::Result<PathBuf> {
+	let mut hasher = DefaultHasher::new();
+	test.input.hash(&mut hasher);
+	std::fs::create_dir_all(dir)?;
+	let stem = dir.join(format!("{:016x}", hasher.finish()));
+	test.save(&stem.to_string_lossy())?;
+	Ok(stem.with_extension("in"))
+}
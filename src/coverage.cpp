@@ -0,0 +1,47 @@
+// Coverage runtime linked into solutions built by cpfuzz --coverage. The solution is compiled with
+// -fsanitize-coverage=trace-pc, which calls __sanitizer_cov_trace_pc at every basic block. The
+// blocks reached are marked in a map shared with cpfuzz through a file, so it survives crashes.
+#include <fcntl.h>
+#include <stdint.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <sys/mman.h>
+#include <unistd.h>
+
+#define CPFUZZ_COVERAGE_MAP_SIZE 65536
+
+static unsigned char *cpfuzz_coverage_map;
+static bool cpfuzz_coverage_initialized;
+
+static void cpfuzz_coverage_initialize() {
+	cpfuzz_coverage_initialized = true;
+	const char *prefix = getenv("CPFUZZ_COVERAGE");
+	if (!prefix) {
+		return;
+	}
+	char path[4096];
+	snprintf(path, sizeof path, "%s.%d", prefix, (int) getpid());
+	int fd = open(path, O_RDWR | O_CREAT | O_TRUNC, 0600);
+	if (fd < 0) {
+		return;
+	}
+	if (ftruncate(fd, CPFUZZ_COVERAGE_MAP_SIZE) == 0) {
+		void *map = mmap(NULL, CPFUZZ_COVERAGE_MAP_SIZE, PROT_READ | PROT_WRITE, MAP_SHARED, fd, 0);
+		if (map != MAP_FAILED) {
+			cpfuzz_coverage_map = (unsigned char *) map;
+		}
+	}
+	close(fd);
+}
+
+extern "C" void __sanitizer_cov_trace_pc(void) {
+	if (!cpfuzz_coverage_initialized) {
+		cpfuzz_coverage_initialize();
+	}
+	if (cpfuzz_coverage_map) {
+		// Relative to the runtime, as the address the program is loaded at differs between runs.
+		uint64_t pc = (uint64_t) __builtin_return_address(0)
+			- (uint64_t) &cpfuzz_coverage_initialize;
+		cpfuzz_coverage_map[(pc * 0x9e3779b97f4a7c15ull) >> 48] = 1;
+	}
+}
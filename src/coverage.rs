@@ -0,0 +1,192 @@
+use std::{
+	cell::RefCell,
+	io::{self, Write as _},
+	path::PathBuf,
+	process::{Command, Stdio},
+	sync::{Mutex, OnceLock},
+	time::Instant,
+};
+
+use crate::{
+	args::{self, Language},
+	error::{Component, Context as _, Error},
+	interrupt,
+	mutate::Pool,
+	temp::Artifact,
+	usage, verbosity, watch,
+};
+
+/// Size of the map of covered blocks, which must match the runtime in coverage.cpp.
+const MAP_SIZE: usize = 1 << 16;
+
+/// The instrumented program and the prefix of the files its runs write their coverage to.
+static INSTRUMENTED: OnceLock<(String, PathBuf)> = OnceLock::new();
+
+thread_local! {
+	/// The blocks covered by the runs of this thread since they were last taken.
+	static COLLECTED: RefCell<Vec<u8>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Builds the solution instrumented with the coverage runtime, replacing the normal build.
+fn build(language: Language, problem: &str) -> Result<(), Error> {
+	let flags: &[&str] = match language {
+		Language::Cpp => &["-O2"],
+		Language::CppSanitize => &["-g", "-fsanitize=address,undefined"],
+		Language::Rust | Language::RustDebug => {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				"--coverage requires a C++ solution",
+			)
+			.into());
+		}
+	};
+	interrupt::check()?;
+	let started = Instant::now();
+	let runtime = Artifact::new("__cpfuzz_coverage.o");
+	let mut gcc = Command::new("g++");
+	gcc.args(["-O2", "-c", "-x", "c++", "-", "-o"])
+		.arg(runtime.path())
+		.stdin(Stdio::piped());
+	verbosity::command(&gcc);
+	let mut child = gcc
+		.spawn()
+		.map_err(crate::missing("g++", crate::NOT_INSTALLED))?;
+	child
+		.stdin
+		.take()
+		.expect("is piped")
+		.write_all(include_str!("coverage.cpp").as_bytes())?;
+	let mut exit_code = child.wait()?;
+	if exit_code.success() {
+		let mut gcc = Command::new("g++");
+		gcc.args(flags)
+			.arg("-fsanitize-coverage=trace-pc")
+			.arg(format!("{problem}.cpp"))
+			.arg(runtime.path())
+			.args(["-o", problem]);
+		verbosity::command(&gcc);
+		exit_code = gcc
+			.spawn()
+			.map_err(crate::missing("g++", crate::NOT_INSTALLED))?
+			.wait()?;
+	}
+	drop(runtime);
+	usage::record_build(problem, started.elapsed());
+	if !exit_code.success() {
+		return Err(watch::build_failed(exit_code).into());
+	}
+	let prefix = std::env::temp_dir().join(format!("cpfuzz-{}-coverage", std::process::id()));
+	let _ = INSTRUMENTED.set((problem.to_string(), prefix));
+	Ok(())
+}
+
+/// Tells a run of the instrumented program where to write its coverage.
+pub fn apply(program: &str, command: &mut Command) {
+	if let Some((instrumented, prefix)) = INSTRUMENTED.get()
+		&& instrumented == program
+	{
+		command.env("CPFUZZ_COVERAGE", prefix);
+	}
+}
+
+/// Collects the coverage written by the exited process of the program, if it is instrumented.
+pub fn collect(program: &str, id: u32) {
+	let Some((instrumented, prefix)) = INSTRUMENTED.get() else {
+		return;
+	};
+	if instrumented != program {
+		return;
+	}
+	let mut path = prefix.clone().into_os_string();
+	path.push(format!(".{id}"));
+	// A run which crashed before reaching a single block wrote nothing.
+	if let Ok(map) = std::fs::read(&path) {
+		let _ = std::fs::remove_file(&path);
+		merge(&map);
+	}
+}
+
+/// Adds covered blocks to those collected by this thread.
+pub fn merge(map: &[u8]) {
+	COLLECTED.with_borrow_mut(|collected| {
+		collected.resize(MAP_SIZE, 0);
+		for (collected, &covered) in collected.iter_mut().zip(map) {
+			*collected |= covered;
+		}
+	});
+}
+
+/// Takes the blocks collected by this thread, which is empty if nothing was collected.
+pub fn take() -> Vec<u8> {
+	COLLECTED.take()
+}
+
+struct Covered {
+	blocks: Vec<bool>,
+	count: usize,
+}
+
+/// The blocks of the solution covered so far with --coverage, and the inputs which covered new
+/// ones to mutate, which reaches the branches behind them far sooner than generating every input
+/// from scratch.
+pub struct Coverage {
+	covered: Mutex<Covered>,
+	pub kept: Pool,
+}
+
+impl Coverage {
+	pub fn new(args: &args::Args) -> Result<Option<Coverage>, Error> {
+		if !args.coverage {
+			return Ok(None);
+		}
+		build(args.language, &args.name).building(Component::Solution, &args.name)?;
+		Ok(Some(Coverage {
+			covered: Mutex::new(Covered {
+				blocks: vec![false; MAP_SIZE],
+				count: 0,
+			}),
+			kept: Pool::new(Vec::new()),
+		}))
+	}
+
+	/// Records the blocks covered by a test, keeping its choices if it covered new ones.
+	pub fn record(&self, map: &[u8], choices: &[u64]) {
+		let mut covered = self.covered.lock().expect("not poisoned");
+		let before = covered.count;
+		let Covered { blocks, count } = &mut *covered;
+		for (block, &new) in blocks.iter_mut().zip(map) {
+			if new != 0 && !*block {
+				*block = true;
+				*count += 1;
+			}
+		}
+		if covered.count > before {
+			self.kept.add(choices.to_vec());
+		}
+	}
+
+	/// The number of blocks covered.
+	pub fn blocks(&self) -> usize {
+		self.covered.lock().expect("not poisoned").count
+	}
+
+	/// The indices of the blocks covered.
+	pub fn covered(&self) -> Vec<usize> {
+		let covered = self.covered.lock().expect("not poisoned");
+		(0..MAP_SIZE).filter(|&i| covered.blocks[i]).collect()
+	}
+
+	/// Continues from the coverage of a session before it was resumed.
+	pub fn resume(&self, covered: &[usize], kept: Vec<Vec<u64>>) {
+		let mut blocks = self.covered.lock().expect("not poisoned");
+		for &block in covered.iter().filter(|&&block| block < MAP_SIZE) {
+			if !blocks.blocks[block] {
+				blocks.blocks[block] = true;
+				blocks.count += 1;
+			}
+		}
+		for choices in kept {
+			self.kept.add(choices);
+		}
+	}
+}
@@ -10,7 +10,9 @@ typedef struct context_t {
 	void (*write_nl)(context_state_t*);
 	void (*write_i64)(context_state_t*, i64);
 	void (*write_ascii)(context_state_t*, char*);
-	i64 (*rand_i64)(i64, i64);
+	i64 (*rand_i64)(context_state_t*, i64, i64);
+	void (*write_answer)(context_state_t*, bool);
+	i64 (*size)(context_state_t*);
 	context_state_t * context_state;
 } context_t;
 
@@ -33,7 +35,12 @@ void write_ascii(context_t *context, char *val) {
 }
 
 i64 rand_i64(context_t *context, i64 lower, i64 higher) {
-	return context->rand_i64(lower, higher);
+	return context->rand_i64(context->context_state, lower, higher);
+}
+
+// The size given with --size, which --adaptive-size ramps up over the session. Zero without --size.
+i64 size(context_t *context) {
+	return context->size(context->context_state);
 }
 
 std::vector<i64> rand_i64_array(context_t *context, i64 length, i64 lower, i64 higher) {
@@ -41,3 +48,21 @@ std::vector<i64> rand_i64_array(context_t *context, i64 length, i64 lower, i64 h
 	for (i64 i = 0; i < length; i++) res[i] = rand_i64(context, lower, higher);
 	return res;
 }
+
+void write_answer_nl(context_t *context) {
+	context->write_answer(context->context_state, true);
+	write_nl(context);
+	context->write_answer(context->context_state, false);
+}
+
+void write_answer_i64(context_t *context, i64 val) {
+	context->write_answer(context->context_state, true);
+	write_i64(context, val);
+	context->write_answer(context->context_state, false);
+}
+
+void write_answer_ascii(context_t *context, char *val) {
+	context->write_answer(context->context_state, true);
+	write_ascii(context, val);
+	context->write_answer(context->context_state, false);
+}
@@ -0,0 +1,113 @@
+use std::{
+	io::{self, Write as _},
+	process::ExitCode,
+};
+
+use crate::{
+	INTERRUPTED, IoResultExt as _, Status,
+	args::DiffArgs,
+	compare::{Comparison, Row},
+	error::{Component, Context as _, Error},
+	interrupt,
+	limits::Limits,
+	style,
+};
+
+/// The tokens of one side of a row separated by spaces, with those which differ coloured, and
+/// the width of the text without the colours.
+fn side(tokens: &[(&[u8], bool)], paint: fn(&str) -> String) -> (String, usize) {
+	let mut text = String::new();
+	let mut width = 0;
+	for (i, &(token, differs)) in tokens.iter().enumerate() {
+		if i > 0 {
+			text.push(' ');
+			width += 1;
+		}
+		let token = String::from_utf8_lossy(token);
+		width += token.chars().count();
+		match differs {
+			true => text.push_str(&paint(&token)),
+			false => text.push_str(&token),
+		}
+	}
+	(text, width)
+}
+
+/// Prints the rows with the line numbers, marking them as sdiff does: `|` where the lines differ,
+/// `<` and `>` where only one output has the line.
+fn print(rows: &[Row], names: [&str; 2]) -> io::Result<()> {
+	let sides: Vec<_> = rows
+		.iter()
+		.map(|[a, b]| (side(a, style::failed), side(b, style::passed)))
+		.collect();
+	let width = sides
+		.iter()
+		.map(|((_, width), _)| *width)
+		.chain([names[0].chars().count()])
+		.max()
+		.unwrap_or(0);
+	let numbers = rows.len().to_string().len();
+	let mut stdout = io::stdout().lock();
+	writeln!(
+		stdout,
+		"{:numbers$}  {:width$}    {}",
+		"", names[0], names[1]
+	)?;
+	for (i, (row, ((a, a_width), (b, _)))) in rows.iter().zip(sides).enumerate() {
+		let marker = match row {
+			[a, _] if a.is_empty() => '>',
+			[_, b] if b.is_empty() => '<',
+			[a, b] if a.iter().chain(b).any(|&(_, differs)| differs) => '|',
+			_ => ' ',
+		};
+		let padding = " ".repeat(width - a_width);
+		writeln!(stdout, "{:numbers$}  {a}{padding}  {marker} {b}", i + 1)?;
+	}
+	stdout.flush()
+}
+
+/// Runs both solutions on the input and prints their outputs side by side.
+pub fn diff(args: &DiffArgs) -> Result<ExitCode, Error> {
+	style::init(args.color);
+	let programs = [
+		(Component::Solution, &args.name),
+		(Component::Comparator, &args.compare),
+	];
+	for (component, program) in programs {
+		args.language.build(program).building(component, program)?;
+	}
+	let input = std::fs::read(&args.input)?;
+	let limits = Limits::none();
+	let mut outputs = Vec::new();
+	for (component, program) in programs {
+		let run = args
+			.language
+			.run(program, &limits, &input)
+			.running(component, program)?;
+		if interrupt::interrupted() {
+			return Ok(ExitCode::from(INTERRUPTED));
+		}
+		if let Some(status) = run.exit.failure(program, Status::Failed) {
+			let message = match status {
+				Status::Failed => format!("{program} exited with non-zero exit code"),
+				_ => status.message(),
+			};
+			eprintln!("{}", style::failed(&message));
+			io::stderr().write_all(&run.stderr)?;
+			return Ok(ExitCode::from(status.exit_code()));
+		}
+		outputs.push(run.stdout);
+	}
+	let comparison = Comparison::with(args.diff_mode, args.float_eps);
+	let rows = comparison.side_by_side(&outputs[0], &outputs[1]);
+	print(&rows, [&args.name, &args.compare]).ignore_broken_pipe()?;
+	if comparison.equal(&outputs[0], &outputs[1]) {
+		eprintln!("\n{}", style::passed("The outputs are equal"));
+		return Ok(ExitCode::SUCCESS);
+	}
+	eprintln!("\n{}", style::failed(&Status::DifferentOutputs.message()));
+	if let Some(divergence) = comparison.divergence(&outputs[0], &outputs[1]) {
+		eprintln!("{}", divergence.describe());
+	}
+	Ok(ExitCode::from(Status::DifferentOutputs.exit_code()))
+}
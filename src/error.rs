@@ -1,15 +1,360 @@
-use std::io;
+use std::{fmt, io, process::ExitCode};
+
+/// Where a token of a specification is, counting lines and columns from 1.
+#[derive(Debug, Clone, Copy)]
+pub struct Position {
+	pub line: usize,
+	pub column: usize,
+}
 
 #[derive(Debug)]
 pub enum SpecificationError {
-	Any,
+	/// A token where an atom starts is not one of `int`, `arr` and `perm`.
+	UnknownAtom {
+		position: Position,
+		token: String,
+		suggestion: Option<String>,
+	},
+	/// The line ended before the atom was complete.
+	MissingToken {
+		position: Position,
+		atom: &'static str,
+		expected: &'static str,
+	},
+	/// The name of an atom is one of the atoms, such as `int int 1 10`, or `size`.
+	ReservedName { position: Position, name: String },
+	/// The name of an atom is the name of an atom before it.
+	DuplicateName {
+		position: Position,
+		name: String,
+		first: Position,
+	},
+	/// A bound or length is neither an integer nor a name, such as `1e9`.
+	InvalidNumber {
+		position: Position,
+		token: String,
+		expected: &'static str,
+	},
+	/// A bound or length is an integer outside the range of 64-bit integers, which the values of
+	/// a specification are.
+	Overflow {
+		position: Position,
+		expression: String,
+	},
+	/// A bound or length names an integer which is not generated before it.
+	UnknownVariable {
+		position: Position,
+		name: String,
+		suggestion: Option<String>,
+	},
+	/// The lower bound of the atom was generated above its upper bound.
+	EmptyRange {
+		position: Position,
+		name: String,
+		lower: i64,
+		higher: i64,
+	},
+	/// The length of the array or permutation was generated negative.
+	NegativeLength {
+		position: Position,
+		name: String,
+		length: i64,
+	},
+	/// The array or permutation was generated too long to fit in memory.
+	TooLong {
+		position: Position,
+		name: String,
+		length: i64,
+	},
+}
+
+impl fmt::Display for Position {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		write!(f, "line {}, column {}", self.line, self.column)
+	}
+}
+
+/// Appends the suggestion to the message, if there is one.
+fn did_you_mean(f: &mut fmt::Formatter, suggestion: &Option<String>) -> fmt::Result {
+	match suggestion {
+		Some(suggestion) => write!(f, ", did you mean '{suggestion}'?"),
+		None => Ok(()),
+	}
+}
+
+impl fmt::Display for SpecificationError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			SpecificationError::UnknownAtom {
+				position,
+				token,
+				suggestion,
+			} => {
+				write!(f, "{position}: unknown atom '{token}'")?;
+				match suggestion {
+					Some(_) => did_you_mean(f, suggestion),
+					None => write!(f, ", expected int, arr or perm"),
+				}
+			}
+			SpecificationError::MissingToken {
+				position,
+				atom,
+				expected,
+			} => write!(f, "{position}: expected {expected} of the {atom}"),
+			SpecificationError::ReservedName { position, name } if name == "size" => write!(
+				f,
+				"{position}: 'size' is the size of the test and can not be the name of an atom"
+			),
+			SpecificationError::ReservedName { position, name } => write!(
+				f,
+				"{position}: '{name}' is an atom and can not be the name of one"
+			),
+			SpecificationError::DuplicateName {
+				position,
+				name,
+				first,
+			} => write!(
+				f,
+				"{position}: '{name}' is already the name of the atom at {first}"
+			),
+			SpecificationError::InvalidNumber {
+				position,
+				token,
+				expected,
+			} => write!(
+				f,
+				"{position}: expected {expected} to be an integer or a name, found '{token}'"
+			),
+			SpecificationError::Overflow {
+				position,
+				expression,
+			} => write!(
+				f,
+				"{position}: '{expression}' does not fit in a 64-bit integer, which holds \
+				 {}..={}",
+				i64::MIN,
+				i64::MAX
+			),
+			SpecificationError::UnknownVariable { position, name, .. } if name == "size" => write!(
+				f,
+				"{position}: the size is only known when it is given with --size"
+			),
+			SpecificationError::UnknownVariable {
+				position,
+				name,
+				suggestion,
+			} => {
+				write!(f, "{position}: '{name}' is not an integer generated before")?;
+				did_you_mean(f, suggestion)
+			}
+			SpecificationError::EmptyRange {
+				position,
+				name,
+				lower,
+				higher,
+			} => write!(
+				f,
+				"{position}: the range {lower}..={higher} of '{name}' is empty, as its lower bound is \
+				 above its upper bound"
+			),
+			SpecificationError::NegativeLength {
+				position,
+				name,
+				length,
+			} => write!(f, "{position}: the length {length} of '{name}' is negative"),
+			SpecificationError::TooLong {
+				position,
+				name,
+				length,
+			} => write!(
+				f,
+				"{position}: the length {length} of '{name}' is too long to fit in memory"
+			),
+		}
+	}
+}
+
+impl std::error::Error for SpecificationError {}
+
+/// The programs of a session, which errors are attributed to.
+#[derive(Debug, Clone, Copy)]
+pub enum Component {
+	Solution,
+	Comparator,
+	Reference,
+	Checker,
+	Verifier,
+	OutputValidator,
+	InputValidator,
+	Interactor,
+	Generator,
+}
+
+impl fmt::Display for Component {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		f.write_str(match self {
+			Component::Solution => "solution",
+			Component::Comparator => "comparator",
+			Component::Reference => "reference solution",
+			Component::Checker => "checker",
+			Component::Verifier => "verifier",
+			Component::OutputValidator => "output validator",
+			Component::InputValidator => "input validator",
+			Component::Interactor => "interactor",
+			Component::Generator => "generator",
+		})
+	}
+}
+
+/// What a program was doing when it failed.
+#[derive(Debug, Clone, Copy)]
+pub enum Stage {
+	Build,
+	Start,
+	Run,
+}
+
+/// The error of spawning a program, which tells a program which could not be started apart from
+/// one which failed while it ran.
+#[derive(Debug)]
+pub struct NotStarted(pub io::Error);
+
+impl fmt::Display for NotStarted {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+impl std::error::Error for NotStarted {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		Some(&self.0)
+	}
+}
+
+/// Wraps the error of spawning a program, keeping its kind.
+pub fn not_started(e: io::Error) -> io::Error {
+	io::Error::new(e.kind(), NotStarted(e))
 }
 
 #[derive(Debug)]
 pub enum Error {
-	#[allow(dead_code)] // This is a false positive as it is read in the glue code of main.
 	Io(io::Error),
 	Specification(SpecificationError),
+	/// The options of a session set up from code are invalid.
+	Arguments(String),
+	/// An error with what cpfuzz was doing when it happened, such as reading the specification.
+	Context {
+		context: String,
+		source: Box<Error>,
+	},
+	/// An error of one of the programs of the session, with which one and what it was doing.
+	Program {
+		component: Component,
+		program: String,
+		stage: Stage,
+		source: Box<Error>,
+	},
+}
+
+impl fmt::Display for Error {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Error::Io(e) => write!(f, "{e}"),
+			Error::Specification(e) => write!(f, "invalid specification: {e}"),
+			Error::Arguments(message) => f.write_str(message.trim_end()),
+			Error::Context { context, source } => write!(f, "while {context}: {source}"),
+			Error::Program {
+				component,
+				program,
+				stage,
+				source,
+			} => {
+				let failed = match stage {
+					Stage::Build => "failed to build",
+					Stage::Start => "failed to start",
+					Stage::Run => "failed while running",
+				};
+				// The solution is the one program not named on its own.
+				match component {
+					Component::Solution => write!(f, "the solution '{program}' {failed}: {source}"),
+					_ => write!(f, "{component} '{program}' {failed}: {source}"),
+				}
+			}
+		}
+	}
+}
+
+impl std::error::Error for Error {
+	fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+		match self {
+			Error::Io(e) => Some(e),
+			Error::Specification(e) => Some(e),
+			Error::Arguments(_) => None,
+			Error::Context { source, .. } | Error::Program { source, .. } => Some(source.as_ref()),
+		}
+	}
+}
+
+/// Explains a failure by what cpfuzz was doing, such as "reading --spec-inline", or by the
+/// program which failed.
+pub trait Context<T> {
+	fn context(self, context: impl FnOnce() -> String) -> Result<T, Error>;
+
+	/// Attributes a failure to building the program.
+	fn building(self, component: Component, program: &str) -> Result<T, Error>;
+
+	/// Attributes a failure to running the program, or to starting it if it could not be.
+	fn running(self, component: Component, program: &str) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+	fn context(self, context: impl FnOnce() -> String) -> Result<T, Error> {
+		self.map_err(|e| Error::Context {
+			context: context(),
+			source: Box::new(e.into()),
+		})
+	}
+
+	fn building(self, component: Component, program: &str) -> Result<T, Error> {
+		self.map_err(|e| Error::Program {
+			component,
+			program: program.to_string(),
+			stage: Stage::Build,
+			source: Box::new(e.into()),
+		})
+	}
+
+	fn running(self, component: Component, program: &str) -> Result<T, Error> {
+		self.map_err(|e| {
+			let e = e.into();
+			if let Error::Program { .. } = e {
+				// A program the failing one ran with, such as the solution of an interactor.
+				return e;
+			}
+			let started = match &e {
+				Error::Io(e) => e.get_ref().is_none_or(|e| !e.is::<NotStarted>()),
+				_ => true,
+			};
+			Error::Program {
+				component,
+				program: program.to_string(),
+				stage: if started { Stage::Run } else { Stage::Start },
+				source: Box::new(e),
+			}
+		})
+	}
+}
+
+/// The exit code of cpfuzz as run from the command line, printing its error for a human if it
+/// failed.
+pub fn exit(result: Result<ExitCode, Error>) -> ExitCode {
+	match result {
+		Ok(exit_code) => exit_code,
+		Err(e) => {
+			eprintln!("Error: {e}");
+			ExitCode::FAILURE
+		}
+	}
 }
 
 impl From<io::Error> for Error {
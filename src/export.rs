@@ -0,0 +1,115 @@
+use std::{
+	collections::HashSet,
+	io,
+	path::{Path, PathBuf},
+	process::ExitCode,
+};
+
+use crate::{
+	INTERRUPTED,
+	args::{ExportArgs, ExportFormat},
+	corpus,
+	error::{Component, Context as _, Error},
+	interrupt,
+	limits::Limits,
+	zip,
+};
+
+/// The inputs given to export, where directories contribute their `.in` files.
+fn inputs(paths: &[PathBuf]) -> io::Result<Vec<PathBuf>> {
+	let mut inputs = Vec::new();
+	for path in paths {
+		if path.is_dir() {
+			inputs.extend(corpus::inputs(path)?);
+		} else {
+			inputs.push(path.clone());
+		}
+	}
+	Ok(inputs)
+}
+
+/// The number of the last test already in the directory, so exported tests are added after them,
+/// and the inputs of the tests.
+fn existing(dir: &Path) -> io::Result<(usize, HashSet<Vec<u8>>)> {
+	let mut last = 0;
+	let mut inputs = HashSet::new();
+	for path in corpus::inputs(dir)? {
+		let number = path
+			.file_stem()
+			.and_then(|stem| stem.to_str()?.parse().ok());
+		last = last.max(number.unwrap_or(0));
+		inputs.insert(std::fs::read(path)?);
+	}
+	Ok((last, inputs))
+}
+
+/// Converts counterexamples into tests of the official test set, with the output of the reference
+/// solution as their answers.
+pub fn export(args: &ExportArgs) -> Result<ExitCode, Error> {
+	args.language
+		.build(&args.reference)
+		.building(Component::Reference, &args.reference)?;
+	let limits = Limits::none();
+	let output = args.output.clone().unwrap_or_else(|| match args.format {
+		ExportFormat::Directory => PathBuf::from("tests"),
+		ExportFormat::Polygon | ExportFormat::Domjudge => PathBuf::from("tests.zip"),
+	});
+	// Zips are written anew, while inputs already in the directory are not exported again.
+	let (last, mut seen) = match args.format {
+		ExportFormat::Directory => existing(&output)?,
+		ExportFormat::Polygon | ExportFormat::Domjudge => (0, HashSet::new()),
+	};
+	let mut tests = Vec::new();
+	let mut duplicates = 0;
+	for path in inputs(&args.inputs)? {
+		let input = std::fs::read(&path)?;
+		if !seen.insert(input.clone()) {
+			duplicates += 1;
+			continue;
+		}
+		let run = args.language.run(&args.reference, &limits, &input)?;
+		// Nothing is exported, rather than the tests answered before the interrupt.
+		if interrupt::interrupted() {
+			return Ok(ExitCode::from(INTERRUPTED));
+		}
+		if !run.exit.success() {
+			return Err(io::Error::other(format!(
+				"the reference {} failed on {}, so it has no answer",
+				args.reference,
+				path.display()
+			))
+			.into());
+		}
+		tests.push((input, run.stdout));
+	}
+	let exported = tests.len();
+	let name = |i: usize| format!("{:02}", last + 1 + i);
+	match args.format {
+		ExportFormat::Directory => {
+			std::fs::create_dir_all(&output)?;
+			for (i, (input, answer)) in tests.iter().enumerate() {
+				let stem = output.join(name(i));
+				std::fs::write(stem.with_extension("in"), input)?;
+				std::fs::write(stem.with_extension("ans"), answer)?;
+			}
+		}
+		ExportFormat::Polygon | ExportFormat::Domjudge => {
+			let (dir, input_extension, answer_extension) = match args.format {
+				ExportFormat::Polygon => ("tests", "", ".a"),
+				_ => ("data/secret", ".in", ".ans"),
+			};
+			let mut files = Vec::new();
+			for (i, (input, answer)) in tests.into_iter().enumerate() {
+				files.push((format!("{dir}/{}{input_extension}", name(i)), input));
+				files.push((format!("{dir}/{}{answer_extension}", name(i)), answer));
+			}
+			zip::write(&output, &files)?;
+		}
+	}
+	eprint!("Exported {} tests to {}", exported, output.display());
+	match duplicates {
+		0 => eprintln!(),
+		_ => eprintln!(", skipping {duplicates} duplicates"),
+	}
+	Ok(ExitCode::SUCCESS)
+}
@@ -1,6 +1,21 @@
-use std::{collections::HashMap, io::Write as _, process::{Command, Stdio}};
+use std::{
+	collections::HashMap,
+	ffi::OsStr,
+	io::{self, Write as _},
+	path::Path,
+	process::{Command, Stdio},
+};
 
-use crate::{args, error::{Error, SpecificationError}, generator_bindings::{Context, ContextState}};
+use crate::{
+	args::{self, Language},
+	choices::Choices,
+	error::{Component, Context as _, Error, Position, SpecificationError},
+	generator_bindings::{Context, ContextState},
+	interrupt,
+	limits::Limits,
+	temp::Artifact,
+	usage, verbosity, watch,
+};
 
 enum Numeric {
 	Integer(i64),
@@ -8,13 +23,24 @@ enum Numeric {
 }
 
 impl Numeric {
-	fn evaluate(&self, store: &HashMap<&str, i64>) -> Result<i64, SpecificationError> {
+	/// The value of the bound or length of the atom at the position.
+	fn evaluate(
+		&self,
+		store: &HashMap<&str, i64>,
+		position: Position,
+	) -> Result<i64, SpecificationError> {
 		match self {
 			Numeric::Integer(x) => Ok(*x),
-			Numeric::Variable(x) => store
-				.get(x.as_str())
-				.copied()
-				.ok_or(SpecificationError::Any),
+			Numeric::Variable(x) => {
+				store
+					.get(x.as_str())
+					.copied()
+					.ok_or_else(|| SpecificationError::UnknownVariable {
+						position,
+						name: x.clone(),
+						suggestion: None,
+					})
+			}
 		}
 	}
 }
@@ -29,135 +55,558 @@ enum SpecificationAtom {
 		length: Numeric,
 		lower: Numeric,
 		higher: Numeric,
-		_name: String,
+		name: String,
 	},
 	Permuation {
 		length: Numeric,
-		_name: String,
+		name: String,
 	},
 	NewLine,
 }
 
 pub struct Specification {
 	atoms: Vec<SpecificationAtom>,
+	/// Where each atom starts, for reporting the atoms which can not be generated.
+	positions: Vec<Position>,
+	/// The atoms which look like they can never be generated, to be reported when it is read.
+	warnings: Vec<String>,
+}
+
+/// The atoms a specification is made of.
+const ATOMS: [&str; 3] = ["int", "arr", "perm"];
+
+/// The number of single character edits turning one string into the other.
+fn distance(a: &str, b: &str) -> usize {
+	let b: Vec<char> = b.chars().collect();
+	let mut previous: Vec<usize> = (0..=b.len()).collect();
+	for (i, a) in a.chars().enumerate() {
+		let mut current = vec![i + 1];
+		for (j, &b) in b.iter().enumerate() {
+			let substituted = previous[j] + usize::from(a != b);
+			current.push(substituted.min(previous[j + 1] + 1).min(current[j] + 1));
+		}
+		previous = current;
+	}
+	previous[b.len()]
+}
+
+/// The candidate the token most likely misspells, if any is close enough, such as `arr` for
+/// `array`.
+fn suggestion<'a>(token: &str, candidates: impl IntoIterator<Item = &'a str>) -> Option<String> {
+	candidates
+		.into_iter()
+		.filter(|candidate| *candidate != token)
+		.map(|candidate| {
+			let prefix = token.starts_with(candidate) || candidate.starts_with(token);
+			(
+				if prefix {
+					0
+				} else {
+					distance(token, candidate)
+				},
+				candidate,
+			)
+		})
+		// Half of a short name is still a different name rather than a misspelling.
+		.filter(|&(distance, _)| distance <= token.chars().count() / 2)
+		.min()
+		.map(|(_, candidate)| candidate.to_string())
+}
+
+/// The tokens of a line of a specification with where they are, for reporting mistakes.
+struct Tokens<'a> {
+	src: &'a str,
+	line: usize,
+	tokens: std::str::SplitAsciiWhitespace<'a>,
+}
+
+impl<'a> Tokens<'a> {
+	fn new(src: &'a str, line: usize) -> Tokens<'a> {
+		Tokens {
+			src,
+			line,
+			tokens: src.split_ascii_whitespace(),
+		}
+	}
+
+	/// Where the token of the line starts.
+	fn position(&self, token: &str) -> Position {
+		let offset = token.as_ptr() as usize - self.src.as_ptr() as usize;
+		Position {
+			line: self.line,
+			column: self.src[..offset].chars().count() + 1,
+		}
+	}
+
+	/// The next token with its position, if the line has another.
+	fn next(&mut self) -> Option<(&'a str, Position)> {
+		let token = self.tokens.next()?;
+		Some((token, self.position(token)))
+	}
+
+	/// The next token of the atom, which expects it to be there.
+	fn expect(
+		&mut self,
+		atom: &'static str,
+		expected: &'static str,
+	) -> Result<(&'a str, Position), SpecificationError> {
+		self.next().ok_or_else(|| SpecificationError::MissingToken {
+			position: Position {
+				line: self.line,
+				column: self.src.trim_end().chars().count() + 1,
+			},
+			atom,
+			expected,
+		})
+	}
 }
 
+/// The name of the atom, which must not be an atom or the name of an atom before it, as the later
+/// one would replace the value of the first.
+fn read_name(
+	tokens: &mut Tokens,
+	atom: &'static str,
+	names: &mut Vec<(String, Position)>,
+) -> Result<String, SpecificationError> {
+	let (name, position) = tokens.expect(atom, "a name")?;
+	// An atom named size would shadow the size of the test.
+	if ATOMS.contains(&name) || name == "size" {
+		return Err(SpecificationError::ReservedName {
+			position,
+			name: name.to_string(),
+		});
+	}
+	if let Some(&(_, first)) = names.iter().find(|(defined, _)| defined == name) {
+		return Err(SpecificationError::DuplicateName {
+			position,
+			name: name.to_string(),
+			first,
+		});
+	}
+	names.push((name.to_string(), position));
+	Ok(name.to_string())
+}
 
-fn read_name<'a>(iter: &mut impl Iterator<Item = &'a str>) -> Result<String, SpecificationError> {
-	iter.next()
-		.ok_or(SpecificationError::Any)
-		.map(str::to_string)
+/// The next token of an input as a number.
+fn read_value<'a>(tokens: &mut impl Iterator<Item = &'a str>) -> Option<i64> {
+	tokens.next()?.parse().ok()
+}
+
+/// Reserves room for `length` values taking `size` elements each, which are generated at the
+/// position, so a length too long is an error rather than a crash.
+fn reserve<T>(
+	vec: &mut Vec<T>,
+	length: i64,
+	size: usize,
+	position: Position,
+	name: &str,
+) -> Result<(), SpecificationError> {
+	usize::try_from(length)
+		.ok()
+		.and_then(|length| length.checked_mul(size))
+		.filter(|&additional| vec.try_reserve(additional).is_ok())
+		.map(|_| ())
+		.ok_or_else(|| SpecificationError::TooLong {
+			position,
+			name: name.to_string(),
+			length,
+		})
+}
+
+/// The offset of the value from the lower end of the range, as a choice records it.
+fn offset(value: i64, lower: i64, higher: i64) -> Option<u64> {
+	(lower..=higher)
+		.contains(&value)
+		.then(|| value.abs_diff(lower))
+}
+
+/// The choices with which [`Choices::shuffle`] turns `1..=n` into the permutation, or None if it
+/// is not a permutation.
+fn unshuffle(permutation: &[i64]) -> Option<Vec<u64>> {
+	let n = permutation.len();
+	let mut seen = vec![false; n];
+	for &value in permutation {
+		let i = usize::try_from(value).ok()?.checked_sub(1)?;
+		if i >= n || std::mem::replace(&mut seen[i], true) {
+			return None;
+		}
+	}
+	let mut slice: Vec<i64> = (1..=n as i64).collect();
+	let mut position: Vec<usize> = (0..n).collect();
+	let mut choices = Vec::new();
+	for i in (1..n).rev() {
+		let j = position[permutation[i] as usize - 1];
+		choices.push((i - j) as u64);
+		slice.swap(i, j);
+		position[slice[i] as usize - 1] = i;
+		position[slice[j] as usize - 1] = j;
+	}
+	Some(choices)
 }
 
-fn read_numeric<'a>(
-	iter: &mut impl Iterator<Item = &'a str>,
+/// Reads a bound or length of the atom, an integer or the name of an integer generated before it,
+/// which are those known.
+fn read_numeric(
+	tokens: &mut Tokens,
+	atom: &'static str,
+	expected: &'static str,
+	known: &[String],
 ) -> Result<Numeric, SpecificationError> {
-	iter.next().ok_or(SpecificationError::Any).map(|s| {
-		s.parse()
-			.map(Numeric::Integer)
-			.unwrap_or(Numeric::Variable(s.to_string()))
-	})
+	let (token, position) = tokens.expect(atom, expected)?;
+	if let Ok(value) = token.parse() {
+		return Ok(Numeric::Integer(value));
+	}
+	// Digits alone which do not parse are an integer too large rather than a malformed one.
+	let digits = token.strip_prefix(['-', '+']).unwrap_or(token);
+	if !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) {
+		return Err(SpecificationError::Overflow {
+			position,
+			expression: token.to_string(),
+		});
+	}
+	if token.starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+') {
+		return Err(SpecificationError::InvalidNumber {
+			position,
+			token: token.to_string(),
+			expected,
+		});
+	}
+	// The size is only known while generating, as it is given with --size.
+	if token != "size" && !known.iter().any(|name| name == token) {
+		return Err(SpecificationError::UnknownVariable {
+			position,
+			name: token.to_string(),
+			suggestion: suggestion(token, known.iter().map(String::as_str).chain(["size"])),
+		});
+	}
+	Ok(Numeric::Variable(token.to_string()))
+}
+
+/// The values the integers of a specification can take as far as parsing tells, for finding the
+/// ranges and lengths which can not be generated before generating them.
+#[derive(Default)]
+struct Ranges {
+	/// The least and the greatest value of each integer whose bounds are known when parsing.
+	values: HashMap<String, (i64, i64)>,
+	warnings: Vec<String>,
+}
+
+impl Ranges {
+	/// The least and the greatest value of the bound or length, unless it depends on the size.
+	fn values(&self, numeric: &Numeric) -> Option<(i64, i64)> {
+		match numeric {
+			Numeric::Integer(x) => Some((*x, *x)),
+			Numeric::Variable(name) => self.values.get(name).copied(),
+		}
+	}
+
+	/// Notes the values the integer takes between the bounds.
+	fn define(&mut self, name: &str, lower: &Numeric, higher: &Numeric) {
+		match (self.values(lower), self.values(higher)) {
+			(Some((least, _)), Some((_, greatest))) => {
+				self.values.insert(name.to_string(), (least, greatest));
+			}
+			_ => {
+				self.values.remove(name);
+			}
+		}
+	}
+
+	/// Checks that the range of the atom can be generated. An empty range is an error if both
+	/// bounds are integers, and is warned about if the ranges of the integers they name make it
+	/// empty whatever they are generated as.
+	fn range(
+		&mut self,
+		position: Position,
+		name: &str,
+		lower: &Numeric,
+		higher: &Numeric,
+	) -> Result<(), SpecificationError> {
+		if let (Numeric::Integer(lower), Numeric::Integer(higher)) = (lower, higher)
+			&& lower > higher
+		{
+			return Err(SpecificationError::EmptyRange {
+				position,
+				name: name.to_string(),
+				lower: *lower,
+				higher: *higher,
+			});
+		}
+		if let (Some((least, _)), Some((_, greatest))) = (self.values(lower), self.values(higher))
+			&& least > greatest
+		{
+			self.warnings.push(format!(
+				"{position}: the range of '{name}' is always empty, as its lower bound is at \
+				 least {least} and its upper bound at most {greatest}"
+			));
+		}
+		Ok(())
+	}
+
+	/// Checks that the length of the atom can be generated, like [`Self::range`] checks ranges.
+	fn length(
+		&mut self,
+		position: Position,
+		name: &str,
+		length: &Numeric,
+	) -> Result<(), SpecificationError> {
+		match (length, self.values(length)) {
+			(Numeric::Integer(length), _) if *length < 0 => {
+				Err(SpecificationError::NegativeLength {
+					position,
+					name: name.to_string(),
+					length: *length,
+				})
+			}
+			(_, Some((_, greatest))) if greatest < 0 => {
+				self.warnings.push(format!(
+					"{position}: the length of '{name}' is always negative, as it is at most \
+					 {greatest}"
+				));
+				Ok(())
+			}
+			_ => Ok(()),
+		}
+	}
 }
 
 impl Specification {
 	fn parse(src: &str) -> Result<Specification, SpecificationError> {
-		Ok(Specification {
-			atoms: src.lines().try_fold(Vec::new(), |mut acc, line| {
-				let mut tokens = line.split_ascii_whitespace();
-				if !acc.is_empty() {
-					acc.push(SpecificationAtom::NewLine);
-				}
-				while let Some(ty) = tokens.next() {
-					match ty {
-						"int" => {
-							let name = read_name(&mut tokens)?;
-							let lower = read_numeric(&mut tokens)?;
-							let higher = read_numeric(&mut tokens)?;
-							acc.push(SpecificationAtom::Integer {
-								lower,
-								higher,
-								name,
-							});
+		let mut atoms = Vec::new();
+		let mut positions = Vec::new();
+		// The integers generated so far, which later bounds and lengths may refer to.
+		let mut known = Vec::new();
+		// The names of all atoms so far with where they are.
+		let mut names = Vec::new();
+		let mut ranges = Ranges::default();
+		for (i, line) in src.lines().enumerate() {
+			let mut tokens = Tokens::new(line, i + 1);
+			if !atoms.is_empty() {
+				atoms.push(SpecificationAtom::NewLine);
+				positions.push(Position {
+					line: i + 1,
+					column: 1,
+				});
+			}
+			while let Some((ty, position)) = tokens.next() {
+				let atom = match ty {
+					"int" => {
+						let name = read_name(&mut tokens, "int", &mut names)?;
+						let lower = read_numeric(&mut tokens, "int", "the lower bound", &known)?;
+						let higher = read_numeric(&mut tokens, "int", "the upper bound", &known)?;
+						ranges.range(position, &name, &lower, &higher)?;
+						ranges.define(&name, &lower, &higher);
+						known.push(name.clone());
+						SpecificationAtom::Integer {
+							lower,
+							higher,
+							name,
 						}
-						"arr" => {
-							let name = read_name(&mut tokens)?;
-							let length = read_numeric(&mut tokens)?;
-							let lower = read_numeric(&mut tokens)?;
-							let higher = read_numeric(&mut tokens)?;
-							acc.push(SpecificationAtom::Array {
-								length,
-								lower,
-								higher,
-								_name: name,
-							});
+					}
+					"arr" => {
+						let name = read_name(&mut tokens, "arr", &mut names)?;
+						let length = read_numeric(&mut tokens, "arr", "the length", &known)?;
+						let lower = read_numeric(&mut tokens, "arr", "the lower bound", &known)?;
+						let higher = read_numeric(&mut tokens, "arr", "the upper bound", &known)?;
+						ranges.length(position, &name, &length)?;
+						// The range of an empty array is never used.
+						if ranges
+							.values(&length)
+							.is_none_or(|(_, greatest)| greatest > 0)
+						{
+							ranges.range(position, &name, &lower, &higher)?;
 						}
-						"perm" => {
-							let name = read_name(&mut tokens)?;
-							let length = read_numeric(&mut tokens)?;
-							acc.push(SpecificationAtom::Permuation {
-								length,
-								_name: name,
-							});
+						SpecificationAtom::Array {
+							length,
+							lower,
+							higher,
+							name,
 						}
-						_ => return Err(SpecificationError::Any),
 					}
-				}
-				Ok(acc)
-			})?,
+					"perm" => {
+						let name = read_name(&mut tokens, "perm", &mut names)?;
+						let length = read_numeric(&mut tokens, "perm", "the length", &known)?;
+						ranges.length(position, &name, &length)?;
+						SpecificationAtom::Permuation { length, name }
+					}
+					_ => {
+						return Err(SpecificationError::UnknownAtom {
+							position,
+							token: ty.to_string(),
+							suggestion: suggestion(ty, ATOMS),
+						});
+					}
+				};
+				atoms.push(atom);
+				positions.push(position);
+			}
+		}
+		Ok(Specification {
+			atoms,
+			positions,
+			warnings: ranges.warnings,
 		})
 	}
 
-	fn generate(&self) -> Result<Vec<u8>, SpecificationError> {
+	fn generate(
+		&self,
+		choices: &mut Choices,
+		stdin: &mut Vec<u8>,
+	) -> Result<(), SpecificationError> {
 		let mut store = HashMap::new();
-		let mut stdin = Vec::new();
-		for atom in &self.atoms {
+		if let Some(size) = choices.size() {
+			store.insert("size", size);
+		}
+		for (atom, &position) in self.atoms.iter().zip(&self.positions) {
 			match atom {
 				SpecificationAtom::Integer {
 					lower,
 					higher,
 					name,
 				} => {
-					let lower = lower.evaluate(&store)?;
-					let higher = higher.evaluate(&store)?;
+					let lower = lower.evaluate(&store, position)?;
+					let higher = higher.evaluate(&store, position)?;
 					if higher < lower {
-						return Err(SpecificationError::Any);
+						return Err(SpecificationError::EmptyRange {
+							position,
+							name: name.clone(),
+							lower,
+							higher,
+						});
 					}
-					let val = fastrand::i64(lower..=higher);
+					let val = choices.i64(lower, higher);
 					store.insert(name, val);
-					write!(&mut stdin, "{val} ").expect("write to memory");
+					write!(stdin, "{val} ").expect("write to memory");
 				}
 				SpecificationAtom::Array {
 					length,
 					lower,
 					higher,
-					..
+					name,
 				} => {
-					let length = length.evaluate(&store)?;
+					let length = length.evaluate(&store, position)?;
 					if length < 0 {
-						return Err(SpecificationError::Any);
+						return Err(SpecificationError::NegativeLength {
+							position,
+							name: name.clone(),
+							length,
+						});
 					}
-					let lower = lower.evaluate(&store)?;
-					let higher = higher.evaluate(&store)?;
+					let lower = lower.evaluate(&store, position)?;
+					let higher = higher.evaluate(&store, position)?;
+					if length > 0 && higher < lower {
+						return Err(SpecificationError::EmptyRange {
+							position,
+							name: name.clone(),
+							lower,
+							higher,
+						});
+					}
+					// Every value is written with at least a digit and a space.
+					reserve(stdin, length, 2, position, name)?;
 					for _ in 0..length {
-						let val = fastrand::i64(lower..=higher);
-						write!(&mut stdin, "{val} ").expect("write to memory");
+						let val = choices.i64(lower, higher);
+						write!(stdin, "{val} ").expect("write to memory");
 					}
 				}
-				SpecificationAtom::Permuation { length, .. } => {
-					let length = length.evaluate(&store)?;
+				SpecificationAtom::Permuation { length, name } => {
+					let length = length.evaluate(&store, position)?;
 					if length < 0 {
-						return Err(SpecificationError::Any);
+						return Err(SpecificationError::NegativeLength {
+							position,
+							name: name.clone(),
+							length,
+						});
 					}
-					let mut perm: Vec<i64> = (1..=length).collect();
-					fastrand::shuffle(&mut perm);
+					let mut perm: Vec<i64> = Vec::new();
+					reserve(&mut perm, length, 1, position, name)?;
+					perm.extend(1..=length);
+					choices.shuffle(&mut perm);
 					for val in perm {
-						write!(&mut stdin, "{val} ").expect("write to memory");
+						write!(stdin, "{val} ").expect("write to memory");
 					}
 				}
 				SpecificationAtom::NewLine => stdin.push(b'\n'),
 			}
 		}
-		Ok(stdin)
+		Ok(())
+	}
+
+	/// Recovers the choices generating the input, or None if the specification can not generate
+	/// it.
+	fn choices(&self, input: &[u8], size: Option<i64>) -> Option<Vec<u64>> {
+		let mut tokens = std::str::from_utf8(input).ok()?.split_ascii_whitespace();
+		let mut store = HashMap::new();
+		if let Some(size) = size {
+			store.insert("size", size);
+		}
+		let mut choices = Vec::new();
+		for (atom, &position) in self.atoms.iter().zip(&self.positions) {
+			match atom {
+				SpecificationAtom::Integer {
+					lower,
+					higher,
+					name,
+				} => {
+					let lower = lower.evaluate(&store, position).ok()?;
+					let higher = higher.evaluate(&store, position).ok()?;
+					let val = read_value(&mut tokens)?;
+					choices.push(offset(val, lower, higher)?);
+					store.insert(name, val);
+				}
+				SpecificationAtom::Array {
+					length,
+					lower,
+					higher,
+					..
+				} => {
+					let length = length.evaluate(&store, position).ok()?;
+					let lower = lower.evaluate(&store, position).ok()?;
+					let higher = higher.evaluate(&store, position).ok()?;
+					for _ in 0..length {
+						choices.push(offset(read_value(&mut tokens)?, lower, higher)?);
+					}
+				}
+				SpecificationAtom::Permuation { length, .. } => {
+					let length = length.evaluate(&store, position).ok()?;
+					let perm = (0..length)
+						.map(|_| read_value(&mut tokens))
+						.collect::<Option<Vec<_>>>()?;
+					choices.extend(unshuffle(&perm)?);
+				}
+				SpecificationAtom::NewLine => {}
+			}
+		}
+		tokens.next().is_none().then_some(choices)
+	}
+}
+
+/// A generated input together with the expected answer, if the generator knows it.
+#[derive(Clone)]
+pub struct Test {
+	pub input: Vec<u8>,
+	pub answer: Option<Vec<u8>>,
+}
+
+impl Test {
+	/// Reads the input from `path` and the answer from the `.ans` file next to it, if it exists.
+	pub fn read(path: &Path) -> io::Result<Test> {
+		let input = std::fs::read(path)?;
+		let answer = match std::fs::read(path.with_extension("ans")) {
+			Ok(answer) => Some(answer),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+			Err(e) => return Err(e),
+		};
+		Ok(Test { input, answer })
+	}
+
+	/// Saves the test to `{stem}.in` and `{stem}.ans`.
+	pub fn save(&self, stem: &str) -> io::Result<()> {
+		std::fs::write(format!("{stem}.in"), &self.input)?;
+		if let Some(answer) = &self.answer {
+			std::fs::write(format!("{stem}.ans"), answer)?;
+		}
+		Ok(())
 	}
 }
 
@@ -167,69 +616,264 @@ pub enum Generator {
 		#[allow(dead_code)] // must be kept alive for function pointer to be safe.
 		library: libloading::Library,
 		generator: unsafe fn(&mut Context),
+		/// The file of the library, which is removed after it is unloaded.
+		#[allow(dead_code)]
+		artifact: Artifact,
+	},
+	/// A program written with testlib.h, which is given the seed as its last argument.
+	Testlib {
+		language: Language,
+		program: String,
+		arguments: Vec<String>,
+		limits: Limits,
 	},
+	/// Generates every test with one of the generators, chosen in proportion to their weights by
+	/// the first choice. The weights are those of the arguments, whose total is far below
+	/// [`i64::MAX`] as they are 32 bits.
+	Hybrid(Vec<(Generator, u64)>),
 }
 
 impl Generator {
+	/// The generator of the session, which combines the one given as an argument with those of
+	/// --spec and --gen.
 	pub fn new(args: &args::Args) -> Result<Generator, Error> {
+		let generator = Self::given(args)?;
+		if args.specifications.is_empty() && args.generators.is_empty() {
+			return Ok(generator);
+		}
+		let mut generators = vec![(generator, args.weight.into())];
+		for source in &args.specifications {
+			generators.push((Self::read(&source.path)?, source.weight.into()));
+		}
+		for (i, source) in args.generators.iter().enumerate() {
+			let artifact = Artifact::new(format!("./__cpfuzz_gen_{}.so", i + 1));
+			generators.push((Self::library(&source.path, artifact)?, source.weight.into()));
+		}
+		Ok(Generator::Hybrid(generators))
+	}
+
+	/// The generator given as an argument.
+	fn given(args: &args::Args) -> Result<Generator, Error> {
+		if let Some(inline) = &args.spec_inline {
+			let src = inline.replace("\\n", "\n");
+			let specification =
+				Specification::parse(&src).context(|| "reading --spec-inline".to_string())?;
+			return Ok(Generator::specification(specification));
+		}
+		let specification = args
+			.specification
+			.as_ref()
+			.expect("required unless running a directory of inputs or given inline");
 		if args.generate {
-			unsafe {
-				let mut gcc = Command::new("g++")
-					.args([
-						&format!("{}.cpp", args.specification),
-						"-x",
-						"c++",
-						"-shared",
-						"-o",
-						"__cpfuzz_gen.so",
-						"-",
-					])
-					.stdin(Stdio::piped())
-					.spawn()?;
-				write!(
-					&mut gcc.stdin.as_mut().unwrap(),
-					"{}",
-					include_str!("cpfuzz.cpp")
-				)?;
-				let exit_code = gcc.wait()?;
-				if !exit_code.success() {
-					std::process::exit(exit_code.code().unwrap_or(1));
-				}
-				let library = libloading::Library::new("./__cpfuzz_gen.so").unwrap();
-				let generator: unsafe fn(&mut Context) = std::mem::transmute(
-					library
-						.get::<unsafe fn(&Context)>(b"__generate\0")
-						.unwrap()
-						.into_raw()
-						.into_raw(),
-				);
-				Ok(Generator::Library { library, generator })
-			}
+			Self::library(specification, Artifact::new("./__cpfuzz_gen.so"))
+		} else if args.testlib_generator {
+			args.language
+				.build(specification)
+				.building(Component::Generator, specification)?;
+			Ok(Generator::Testlib {
+				language: args.language,
+				program: specification.clone(),
+				arguments: args.generator_arguments.clone(),
+				limits: Limits::new(args),
+			})
 		} else {
-			let src = std::fs::read_to_string(&args.specification)?;
-			Ok(Generator::Specification(Specification::parse(&src)?))
+			Self::read(specification)
+		}
+	}
+
+	/// Builds the generator written against cpfuzz.h from its source `{specification}.cpp` into
+	/// the library and loads it.
+	fn library(specification: &str, artifact: Artifact) -> Result<Generator, Error> {
+		interrupt::check().building(Component::Generator, specification)?;
+		unsafe {
+			let mut gcc = Command::new("g++");
+			gcc.arg(format!("{specification}.cpp"))
+				.args(["-x", "c++", "-shared", "-o"])
+				.arg(artifact.path())
+				.arg("-")
+				.stdin(Stdio::piped());
+			verbosity::command(&gcc);
+			let mut gcc = gcc
+				.spawn()
+				.map_err(crate::missing("g++", crate::NOT_INSTALLED))?;
+			write!(
+				&mut gcc.stdin.as_mut().unwrap(),
+				"{}",
+				include_str!("cpfuzz.cpp")
+			)?;
+			let exit_code = gcc.wait()?;
+			if !exit_code.success() {
+				return Err(watch::build_failed(exit_code))
+					.building(Component::Generator, specification);
+			}
+			let library = libloading::Library::new(artifact.path()).unwrap();
+			let generator: unsafe fn(&mut Context) = std::mem::transmute(
+				library
+					.get::<unsafe fn(&Context)>(b"__generate\0")
+					.unwrap()
+					.into_raw()
+					.into_raw(),
+			);
+			Ok(Generator::Library {
+				library,
+				generator,
+				artifact,
+			})
 		}
 	}
 
-	pub fn generate(&self) -> Result<Vec<u8>, Error> {
+	/// Reads the specification from its file.
+	fn read(specification: &str) -> Result<Generator, Error> {
+		let reading = || format!("reading the specification '{specification}'");
+		let src = std::fs::read_to_string(specification).context(reading)?;
+		Ok(Generator::specification(
+			Specification::parse(&src).context(reading)?,
+		))
+	}
+
+	/// Generates from the specification, warning about the atoms which look like they can never
+	/// be generated.
+	fn specification(specification: Specification) -> Generator {
+		if !verbosity::quiet() {
+			for warning in &specification.warnings {
+				eprintln!("Warning: {warning}");
+			}
+		}
+		Generator::Specification(specification)
+	}
+
+	/// Generates the test, writing the input into the buffer to reuse its allocation.
+	pub fn generate(&self, choices: &mut Choices, mut buffer: Vec<u8>) -> Result<Test, Error> {
+		buffer.clear();
 		match self {
-			Generator::Specification(specification) => specification.generate().map_err(Into::into),
+			Generator::Specification(specification) => {
+				specification.generate(choices, &mut buffer)?;
+				Ok(Test {
+					input: buffer,
+					answer: None,
+				})
+			}
 			Generator::Library { generator, .. } => {
-				let mut state = ContextState::new();
+				let mut state = ContextState::new(choices, buffer);
 				let mut context = Context::new(&mut state);
 				unsafe {
 					generator(&mut context);
 				}
-				Ok(state.into_stdin())
+				Ok(state.into_test())
+			}
+			Generator::Testlib {
+				language,
+				program,
+				arguments,
+				limits,
+			} => {
+				let seed = choices.i64(0, i64::MAX).to_string();
+				let arguments: Vec<&OsStr> =
+					arguments.iter().chain([&seed]).map(OsStr::new).collect();
+				let run = language
+					.run_with_arguments(program, &arguments, limits, &[])
+					.running(Component::Generator, program)?;
+				// Generating is not part of running the test.
+				usage::take();
+				if !run.exit.success() {
+					let stderr = String::from_utf8_lossy(&run.stderr);
+					return Err(io::Error::other(format!(
+						"generator {program} failed with seed {seed}: {}",
+						stderr.trim()
+					))
+					.into());
+				}
+				buffer.extend_from_slice(&run.stdout);
+				Ok(Test {
+					input: buffer,
+					answer: None,
+				})
+			}
+			Generator::Hybrid(generators) => {
+				let total: u64 = generators.iter().map(|(_, weight)| weight).sum();
+				let mut pick = choices.i64(0, total as i64 - 1) as u64;
+				for (generator, weight) in generators {
+					if pick < *weight {
+						return generator.generate(choices, buffer);
+					}
+					pick -= weight;
+				}
+				unreachable!("the pick is below the total weight")
+			}
+		}
+	}
+
+	/// Recovers the choices generating a saved input at the size, which only a specification
+	/// can. None if the input can not be generated.
+	pub fn choices(&self, input: &[u8], size: Option<i64>) -> Option<Vec<u64>> {
+		match self {
+			Generator::Specification(specification) => specification.choices(input, size),
+			Generator::Library { .. } | Generator::Testlib { .. } => None,
+			// Prefixed by the choice picking the first generator which can generate it.
+			Generator::Hybrid(generators) => {
+				let mut first = 0;
+				for (generator, weight) in generators {
+					if let Some(mut choices) = generator.choices(input, size) {
+						choices.insert(0, first);
+						return Some(choices);
+					}
+					first += weight;
+				}
+				None
 			}
 		}
 	}
 }
 
-impl Drop for Generator {
-	fn drop(&mut self) {
-		if matches!(self, Generator::Library { .. }) {
-			let _ = std::fs::remove_file("./__cpfuzz_gen.so");
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::choices;
+
+	/// A seed of a session must generate the same tests with every build of cpfuzz.
+	#[test]
+	fn pinned_generation() {
+		let specification = Specification::parse("int n 1 5\narr a n -100 100\nperm p n").unwrap();
+		let mut inputs = Vec::new();
+		for test in 0..2 {
+			let mut choices = Choices::random(choices::test_seed(1, test));
+			let mut input = Vec::new();
+			specification.generate(&mut choices, &mut input).unwrap();
+			inputs.push(String::from_utf8(input).unwrap());
+		}
+		assert_eq!(inputs, ["3 \n17 -76 65 \n2 1 3 ", "3 \n45 -13 74 \n2 1 3 "]);
+	}
+
+	#[test]
+	fn reserved_names() {
+		for src in [
+			"int int 1 10",
+			"int n 1 10\narr perm n 1 10",
+			"int size 1 10",
+		] {
+			assert!(matches!(
+				Specification::parse(src),
+				Err(SpecificationError::ReservedName { .. })
+			));
+		}
+		assert!(Specification::parse("int n 1 10\narr a size 1 n").is_ok());
+	}
+
+	#[test]
+	fn unshuffle_round_trip() {
+		for n in 0..7 {
+			for seed in 0..20 {
+				let mut permutation: Vec<i64> = (1..=n).collect();
+				Choices::random(seed).shuffle(&mut permutation);
+				let choices = unshuffle(&permutation).unwrap();
+				let mut replayed: Vec<i64> = (1..=n).collect();
+				Choices::replay(choices).shuffle(&mut replayed);
+				assert_eq!(replayed, permutation);
+			}
 		}
+		assert_eq!(unshuffle(&[1, 2, 3]), Some(vec![0, 0]));
+		assert_eq!(unshuffle(&[1, 1]), None);
+		assert_eq!(unshuffle(&[0, 1]), None);
+		assert_eq!(unshuffle(&[1, 3]), None);
 	}
 }
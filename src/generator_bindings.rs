@@ -1,54 +1,81 @@
 use std::io::Write as _;
 
-pub struct ContextState {
+use crate::{choices::Choices, generator::Test};
+
+pub struct ContextState<'c> {
 	stdin: Vec<u8>,
+	answer: Option<Vec<u8>>,
+	writing_answer: bool,
+	choices: &'c mut Choices,
 }
 
-impl ContextState {
-	pub fn new() -> ContextState {
-		ContextState { stdin: Vec::new() }
+impl<'c> ContextState<'c> {
+	/// Writes the input into the buffer, which must be empty.
+	pub fn new(choices: &'c mut Choices, buffer: Vec<u8>) -> ContextState<'c> {
+		ContextState {
+			stdin: buffer,
+			answer: None,
+			writing_answer: false,
+			choices,
+		}
+	}
+
+	fn output(&mut self) -> &mut Vec<u8> {
+		if self.writing_answer {
+			self.answer.get_or_insert_default()
+		} else {
+			&mut self.stdin
+		}
 	}
 
 	fn new_line(&mut self) {
-		let _ = writeln!(&mut self.stdin);
+		let _ = writeln!(self.output());
 	}
 
 	fn i64(&mut self, val: i64) {
-		let _ = write!(&mut self.stdin, "{val} ");
+		let _ = write!(self.output(), "{val} ");
 	}
 
 	fn ascii(&mut self, ascii: *const u8) {
+		let output = self.output();
 		for i in 0.. {
 			let res = unsafe { ascii.add(i).read() };
 			if res == 0 {
 				break;
 			}
-			self.stdin.push(res);
+			output.push(res);
 		}
-		self.stdin.push(b' ');
+		output.push(b' ');
 	}
 
-	pub fn into_stdin(self) -> Vec<u8> {
-		self.stdin
+	pub fn into_test(self) -> Test {
+		Test {
+			input: self.stdin,
+			answer: self.answer,
+		}
 	}
 }
 
 #[repr(C)]
-pub struct Context<'ctx> {
+pub struct Context<'ctx, 'c> {
 	write_nl: extern "C" fn(&mut ContextState),
 	write_i64: extern "C" fn(&mut ContextState, i64),
 	write_ascii: extern "C" fn(&mut ContextState, *const u8),
-	rand_i64: extern "C" fn(i64, i64) -> i64,
-	context_state: &'ctx mut ContextState,
+	rand_i64: extern "C" fn(&mut ContextState, i64, i64) -> i64,
+	write_answer: extern "C" fn(&mut ContextState, bool),
+	size: extern "C" fn(&mut ContextState) -> i64,
+	context_state: &'ctx mut ContextState<'c>,
 }
 
-impl<'ctx> Context<'ctx> {
-	pub fn new(context_state: &'ctx mut ContextState) -> Context<'ctx> {
+impl<'ctx, 'c> Context<'ctx, 'c> {
+	pub fn new(context_state: &'ctx mut ContextState<'c>) -> Context<'ctx, 'c> {
 		Context {
 			write_nl,
 			write_i64,
 			write_ascii,
 			rand_i64,
+			write_answer,
+			size,
 			context_state,
 		}
 	}
@@ -66,6 +93,15 @@ extern "C" fn write_ascii(context_state: &mut ContextState, ascii: *const u8) {
 	context_state.ascii(ascii);
 }
 
-extern "C" fn rand_i64(lower: i64, higher: i64) -> i64 {
-	fastrand::i64(lower..=higher)
+extern "C" fn rand_i64(context_state: &mut ContextState, lower: i64, higher: i64) -> i64 {
+	context_state.choices.i64(lower, higher)
+}
+
+extern "C" fn write_answer(context_state: &mut ContextState, answer: bool) {
+	context_state.writing_answer = answer;
+}
+
+/// The size of the test, or zero if the session has none.
+extern "C" fn size(context_state: &mut ContextState) -> i64 {
+	context_state.choices.size().unwrap_or(0)
 }
@@ -0,0 +1,52 @@
+use std::{io, path::Path, process::ExitCode};
+
+use crate::{args::InitArgs, error::Error};
+
+/// A specification to start from, in the shape of many problems.
+const SPECIFICATION: &str = "int n 1 100\narr a n 1 1000000000\n";
+
+/// A generator to start from, generating the same input as [`SPECIFICATION`].
+const GENERATOR: &str = r#"#include "cpfuzz.h"
+
+void generate(context_t *context) {
+	i64 n = rand_i64(context, 1, 100);
+	write_i64(context, n);
+	write_nl(context);
+	std::vector<i64> a = rand_i64_array(context, n, 1, 1000000000);
+	write_i64_seq(context, a.begin(), a.end());
+	write_nl(context);
+}
+"#;
+
+/// Writes the file unless it exists, reporting which it did.
+fn scaffold(path: &str, contents: &str) -> io::Result<()> {
+	if Path::new(path).exists() {
+		eprintln!("Kept the existing {path}");
+		return Ok(());
+	}
+	std::fs::write(path, contents)?;
+	eprintln!("Wrote {path}");
+	Ok(())
+}
+
+/// Scaffolds the specification or generator of a problem, printing how to fuzz it.
+pub fn init(args: &InitArgs) -> Result<ExitCode, Error> {
+	let name = &args.name;
+	let specification = if args.generate {
+		let generator = format!("{name}_gen");
+		scaffold(&format!("{generator}.cpp"), GENERATOR)?;
+		scaffold("cpfuzz.h", include_str!("../cpfuzz.h"))?;
+		format!("{generator} --generate")
+	} else {
+		let specification = format!("{name}.spec");
+		scaffold(&specification, SPECIFICATION)?;
+		specification
+	};
+	let language = clap::ValueEnum::to_possible_value(&args.language)
+		.expect("no variant is skipped")
+		.get_name()
+		.to_string();
+	eprintln!("Fill in the constraints of the problem, then fuzz with:");
+	eprintln!("cpfuzz {language} {name} {specification} --compare BRUTE");
+	Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,78 @@
+use std::{
+	collections::HashSet,
+	ffi::c_int,
+	io,
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	thread,
+	time::Duration,
+};
+
+use crate::limits;
+
+const SIGINT: c_int = 2;
+
+/// How often the programs are killed after an interrupt, catching those spawned meanwhile.
+const KILL_INTERVAL: Duration = Duration::from_millis(20);
+
+unsafe extern "C" {
+	fn signal(signal: c_int, handler: extern "C" fn(c_int)) -> usize;
+}
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// The programs currently running. They are spawned in their own process groups, so they do not
+/// receive the interrupt from the terminal themselves.
+static RUNNING: Mutex<Option<HashSet<u32>>> = Mutex::new(None);
+
+extern "C" fn interrupt(_: c_int) {
+	INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+/// Handles Ctrl-C by killing the running programs and letting the session stop gracefully.
+pub fn install() {
+	unsafe {
+		signal(SIGINT, interrupt);
+	}
+	thread::spawn(|| {
+		loop {
+			thread::sleep(KILL_INTERVAL);
+			if interrupted() {
+				let running = RUNNING.lock().expect("not poisoned");
+				running
+					.iter()
+					.flatten()
+					.copied()
+					.for_each(limits::kill_group);
+			}
+		}
+	});
+}
+
+pub fn interrupted() -> bool {
+	INTERRUPTED.load(Ordering::Relaxed)
+}
+
+/// Fails if cpfuzz was interrupted, so the step about to start, such as a build, is skipped.
+pub fn check() -> io::Result<()> {
+	match interrupted() {
+		true => Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted")),
+		false => Ok(()),
+	}
+}
+
+pub fn register(id: u32) {
+	RUNNING
+		.lock()
+		.expect("not poisoned")
+		.get_or_insert_default()
+		.insert(id);
+}
+
+pub fn unregister(id: u32) {
+	if let Some(running) = RUNNING.lock().expect("not poisoned").as_mut() {
+		running.remove(&id);
+	}
+}
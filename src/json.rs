@@ -0,0 +1,337 @@
+use std::fmt;
+
+/// A JSON value, written by the machine-readable reports.
+pub enum Json {
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<Json>),
+	Object(Vec<(&'static str, Json)>),
+}
+
+impl From<&str> for Json {
+	fn from(s: &str) -> Json {
+		Json::String(s.to_string())
+	}
+}
+
+impl From<String> for Json {
+	fn from(s: String) -> Json {
+		Json::String(s)
+	}
+}
+
+impl From<bool> for Json {
+	fn from(b: bool) -> Json {
+		Json::Bool(b)
+	}
+}
+
+impl From<u64> for Json {
+	fn from(n: u64) -> Json {
+		Json::Number(n as f64)
+	}
+}
+
+impl From<usize> for Json {
+	fn from(n: usize) -> Json {
+		Json::Number(n as f64)
+	}
+}
+
+impl From<f64> for Json {
+	fn from(n: f64) -> Json {
+		Json::Number(n)
+	}
+}
+
+impl<T: Into<Json>> From<Option<T>> for Json {
+	fn from(value: Option<T>) -> Json {
+		value.map_or(Json::Null, Into::into)
+	}
+}
+
+fn write_string(f: &mut fmt::Formatter, s: &str) -> fmt::Result {
+	f.write_str("\"")?;
+	for c in s.chars() {
+		match c {
+			'"' => f.write_str("\\\"")?,
+			'\\' => f.write_str("\\\\")?,
+			'\n' => f.write_str("\\n")?,
+			'\r' => f.write_str("\\r")?,
+			'\t' => f.write_str("\\t")?,
+			c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+			c => write!(f, "{c}")?,
+		}
+	}
+	f.write_str("\"")
+}
+
+/// Compact JSON on a single line.
+impl fmt::Display for Json {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			Json::Null => f.write_str("null"),
+			Json::Bool(b) => write!(f, "{b}"),
+			Json::Number(n) if n.is_finite() => write!(f, "{n}"),
+			Json::Number(_) => f.write_str("null"),
+			Json::String(s) => write_string(f, s),
+			Json::Array(values) => {
+				f.write_str("[")?;
+				for (i, value) in values.iter().enumerate() {
+					if i > 0 {
+						f.write_str(",")?;
+					}
+					write!(f, "{value}")?;
+				}
+				f.write_str("]")
+			}
+			Json::Object(fields) => {
+				f.write_str("{")?;
+				for (i, (key, value)) in fields.iter().enumerate() {
+					if i > 0 {
+						f.write_str(",")?;
+					}
+					write_string(f, key)?;
+					write!(f, ":{value}")?;
+				}
+				f.write_str("}")
+			}
+		}
+	}
+}
+
+/// A JSON value read from another program, such as the problems sent by Competitive Companion.
+pub enum Value {
+	Null,
+	Bool(bool),
+	Number(f64),
+	String(String),
+	Array(Vec<Value>),
+	Object(Vec<(String, Value)>),
+}
+
+impl Value {
+	/// Parses a complete JSON document, or None if it is malformed.
+	pub fn parse(src: &str) -> Option<Value> {
+		let mut parser = Parser {
+			src: src.as_bytes(),
+			at: 0,
+		};
+		let value = parser.value()?;
+		parser.whitespace();
+		(parser.at == parser.src.len()).then_some(value)
+	}
+
+	/// The field of an object with the key.
+	pub fn get(&self, key: &str) -> Option<&Value> {
+		match self {
+			Value::Object(fields) => fields.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+			_ => None,
+		}
+	}
+
+	pub fn as_str(&self) -> Option<&str> {
+		match self {
+			Value::String(s) => Some(s),
+			_ => None,
+		}
+	}
+
+	pub fn as_f64(&self) -> Option<f64> {
+		match self {
+			Value::Number(n) => Some(*n),
+			_ => None,
+		}
+	}
+
+	pub fn as_bool(&self) -> Option<bool> {
+		match self {
+			Value::Bool(b) => Some(*b),
+			_ => None,
+		}
+	}
+
+	pub fn as_array(&self) -> Option<&[Value]> {
+		match self {
+			Value::Array(values) => Some(values),
+			_ => None,
+		}
+	}
+}
+
+struct Parser<'a> {
+	src: &'a [u8],
+	at: usize,
+}
+
+impl Parser<'_> {
+	fn whitespace(&mut self) {
+		while self.src.get(self.at).is_some_and(u8::is_ascii_whitespace) {
+			self.at += 1;
+		}
+	}
+
+	/// Skips whitespace and consumes the byte if it comes next.
+	fn eat(&mut self, byte: u8) -> bool {
+		self.whitespace();
+		let next = self.src.get(self.at) == Some(&byte);
+		if next {
+			self.at += 1;
+		}
+		next
+	}
+
+	fn keyword(&mut self, keyword: &str, value: Value) -> Option<Value> {
+		let rest = self.src.get(self.at..)?;
+		rest.starts_with(keyword.as_bytes()).then(|| {
+			self.at += keyword.len();
+			value
+		})
+	}
+
+	fn value(&mut self) -> Option<Value> {
+		self.whitespace();
+		match self.src.get(self.at)? {
+			b'n' => self.keyword("null", Value::Null),
+			b't' => self.keyword("true", Value::Bool(true)),
+			b'f' => self.keyword("false", Value::Bool(false)),
+			b'"' => self.string().map(Value::String),
+			b'[' => {
+				self.at += 1;
+				let mut values = Vec::new();
+				if !self.eat(b']') {
+					loop {
+						values.push(self.value()?);
+						if self.eat(b']') {
+							break;
+						}
+						self.eat(b',').then_some(())?;
+					}
+				}
+				Some(Value::Array(values))
+			}
+			b'{' => {
+				self.at += 1;
+				let mut fields = Vec::new();
+				if !self.eat(b'}') {
+					loop {
+						self.whitespace();
+						let key = self.string()?;
+						self.eat(b':').then_some(())?;
+						fields.push((key, self.value()?));
+						if self.eat(b'}') {
+							break;
+						}
+						self.eat(b',').then_some(())?;
+					}
+				}
+				Some(Value::Object(fields))
+			}
+			_ => {
+				let start = self.at;
+				while self
+					.src
+					.get(self.at)
+					.is_some_and(|c| matches!(c, b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9'))
+				{
+					self.at += 1;
+				}
+				let number = std::str::from_utf8(&self.src[start..self.at]).ok()?;
+				number.parse().ok().map(Value::Number)
+			}
+		}
+	}
+
+	fn string(&mut self) -> Option<String> {
+		(self.src.get(self.at) == Some(&b'"')).then_some(())?;
+		self.at += 1;
+		let mut bytes = Vec::new();
+		loop {
+			let byte = *self.src.get(self.at)?;
+			self.at += 1;
+			match byte {
+				b'"' => return String::from_utf8(bytes).ok(),
+				b'\\' => {
+					let escaped = *self.src.get(self.at)?;
+					self.at += 1;
+					let c = match escaped {
+						b'"' => '"',
+						b'\\' => '\\',
+						b'/' => '/',
+						b'b' => '\u{8}',
+						b'f' => '\u{c}',
+						b'n' => '\n',
+						b'r' => '\r',
+						b't' => '\t',
+						b'u' => {
+							let mut code = self.hex()?;
+							// Characters outside the basic plane are written as surrogate pairs.
+							if (0xd800..0xdc00).contains(&code) {
+								self.src.get(self.at..)?.starts_with(b"\\u").then_some(())?;
+								self.at += 2;
+								let low = self.hex()?;
+								code = 0x10000
+									+ ((code - 0xd800) << 10) + (low.checked_sub(0xdc00)?);
+							}
+							char::from_u32(code)?
+						}
+						_ => return None,
+					};
+					bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+				}
+				byte => bytes.push(byte),
+			}
+		}
+	}
+
+	fn hex(&mut self) -> Option<u32> {
+		let digits = std::str::from_utf8(self.src.get(self.at..self.at + 4)?).ok()?;
+		self.at += 4;
+		u32::from_str_radix(digits, 16).ok()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn escaping() {
+		let json = Json::Object(vec![
+			("text", "a\"b\\c\nd\te\r\u{1}é".into()),
+			(
+				"values",
+				Json::Array(vec![1u64.into(), f64::NAN.into(), None::<bool>.into()]),
+			),
+		]);
+		assert_eq!(
+			json.to_string(),
+			r#"{"text":"a\"b\\c\nd\te\r\u0001é","values":[1,null,null]}"#
+		);
+	}
+
+	#[test]
+	fn round_trip() {
+		let src = "a\"b\\c\nd\te\r\u{1}é😀";
+		let value = Value::parse(&Json::from(src).to_string()).unwrap();
+		assert_eq!(value.as_str(), Some(src));
+	}
+
+	#[test]
+	fn parsing() {
+		let value = Value::parse(
+			r#" {"name": "A\u00e9\ud83d\ude00\/", "tests": [{"x": 1.5}, true, null]} "#,
+		)
+		.unwrap();
+		assert_eq!(value.get("name").and_then(Value::as_str), Some("Aé😀/"));
+		let tests = value.get("tests").and_then(Value::as_array).unwrap();
+		assert_eq!(tests[0].get("x").and_then(Value::as_f64), Some(1.5));
+		assert_eq!(tests[1].as_bool(), Some(true));
+		assert!(matches!(tests[2], Value::Null));
+		for src in ["", "{", "[1,]", "\"\\x\"", "1 2", "\"\\ud83d\""] {
+			assert!(Value::parse(src).is_none(), "{src}");
+		}
+	}
+}
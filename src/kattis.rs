@@ -0,0 +1,383 @@
+use std::{
+	io,
+	path::{Path, PathBuf},
+	time::Duration,
+};
+
+use crate::{
+	Exit, Interactor, Run, Status, Verdict, Verifier,
+	args::{self, InteractorProtocol, Language, VerifyProtocol},
+	error::{Component, Context as _, Error},
+	testlib, verbosity,
+};
+
+/// What validators of problem packages exit with when they accept the input or output.
+pub const ACCEPTED: i32 = 42;
+
+/// What validators of problem packages exit with when they reject the input or output.
+pub const REJECTED: i32 = 43;
+
+/// The values of a problem.yaml, with the keys of nested values joined by dots such as
+/// `limits.time_limit`. Only the plain `key: value` lines problem packages use are understood.
+fn read_yaml(src: &str) -> Vec<(String, String)> {
+	let mut entries = Vec::new();
+	// The keys of the mappings the line is nested in, with their indentation.
+	let mut parents: Vec<(usize, String)> = Vec::new();
+	for line in src.lines() {
+		let content = line.trim_start();
+		if content.is_empty() || content.starts_with('#') {
+			continue;
+		}
+		let indentation = line.len() - content.len();
+		let Some((key, value)) = content.split_once(':') else {
+			continue;
+		};
+		while parents
+			.last()
+			.is_some_and(|&(parent, _)| parent >= indentation)
+		{
+			parents.pop();
+		}
+		let key = key.trim().to_string();
+		let value = value.split(" #").next().unwrap_or("").trim();
+		if value.is_empty() {
+			parents.push((indentation, key));
+			continue;
+		}
+		let path: Vec<_> = parents
+			.iter()
+			.map(|(_, parent)| parent.as_str())
+			.chain([key.as_str()])
+			.collect();
+		let value = value.trim_matches(|c| c == '"' || c == '\'');
+		entries.push((path.join("."), value.to_string()));
+	}
+	entries
+}
+
+fn invalid(message: String) -> Error {
+	io::Error::new(io::ErrorKind::InvalidInput, message).into()
+}
+
+/// The validators in a directory of the package such as `output_validators`, each of which is a
+/// C++ file or a directory with one. Validators are named by their source without `.cpp`, which is
+/// how cpfuzz builds C++ programs.
+fn validators(dir: &Path) -> Result<Vec<String>, Error> {
+	let is_header = |path: &Path| {
+		path.extension()
+			.is_some_and(|extension| extension == "h" || extension == "hpp")
+	};
+	let is_source = |path: &Path| path.extension().is_some_and(|extension| extension == "cpp");
+	let mut validators = Vec::new();
+	let mut paths = match std::fs::read_dir(dir) {
+		Ok(entries) => entries
+			.map(|entry| Ok(entry?.path()))
+			.collect::<io::Result<Vec<_>>>()?,
+		Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(validators),
+		Err(e) => return Err(e.into()),
+	};
+	paths.sort();
+	for path in paths {
+		let source = if path.is_dir() {
+			let mut sources = Vec::new();
+			for entry in std::fs::read_dir(&path)? {
+				let source = entry?.path();
+				if is_source(&source) {
+					sources.push(source);
+				}
+			}
+			match <[PathBuf; 1]>::try_from(sources) {
+				Ok([source]) => source,
+				Err(_) => {
+					return Err(invalid(format!(
+						"{} must consist of a single C++ file, the only validators cpfuzz can build",
+						path.display()
+					)));
+				}
+			}
+		} else if is_source(&path) {
+			path
+		} else if is_header(&path) || path.with_extension("cpp").exists() {
+			// Headers of the validators, and the validators cpfuzz built before.
+			continue;
+		} else {
+			return Err(invalid(format!(
+				"{} is not a C++ file, the only validators cpfuzz can build",
+				path.display()
+			)));
+		};
+		validators.push(source.with_extension("").to_string_lossy().into_owned());
+	}
+	Ok(validators)
+}
+
+/// The first line of the feedback an output validator wrote on why it rejected the output.
+fn feedback(dir: &Path) -> Option<String> {
+	let message = std::fs::read_to_string(dir.join("judgemessage.txt")).ok()?;
+	let line = message
+		.lines()
+		.map(str::trim)
+		.find(|line| !line.is_empty())?;
+	Some(line.to_string())
+}
+
+/// Judges the output by how an output validator of a problem package exited, and its feedback in
+/// `feedback_dir`. Exiting otherwise than accepting or rejecting it is a failure of the validator.
+pub fn status(program: &str, run: &Run, feedback_dir: &Path) -> Status {
+	match run.exit {
+		Exit::Failure(Some(ACCEPTED)) => Status::Ok,
+		Exit::Failure(Some(REJECTED)) => Status::Rejected {
+			program: program.to_string(),
+			message: feedback(feedback_dir).unwrap_or_else(|| "wrong answer".to_string()),
+		},
+		Exit::Signal(signal) => Status::JudgeError {
+			program: program.to_string(),
+			signal,
+		},
+		Exit::MemoryLimit => testlib::out_of_memory(program),
+		_ => Status::JudgeFailed {
+			program: program.to_string(),
+			message: failure(run),
+		},
+	}
+}
+
+/// The verdict of an output validator of an interactive problem by how it exited, or None if it
+/// neither accepted nor rejected the interaction.
+pub fn verdict(exit: &Exit, feedback_dir: &Path) -> Option<Verdict> {
+	match exit {
+		Exit::Failure(Some(ACCEPTED)) => Some(Verdict::Accepted),
+		Exit::Failure(Some(REJECTED)) => Some(Verdict::WrongAnswer(
+			feedback(feedback_dir).unwrap_or_else(|| "wrong answer".to_string()),
+		)),
+		_ => None,
+	}
+}
+
+/// Why a validator neither accepted nor rejected.
+pub fn failure(run: &Run) -> String {
+	let code = match run.exit {
+		Exit::Success => Some(0),
+		Exit::Failure(code) => code,
+		_ => None,
+	};
+	let verdict = match code {
+		Some(code) => format!("exited with code {code} instead of {ACCEPTED} or {REJECTED}"),
+		None => "failed".to_string(),
+	};
+	testlib::message(run, &verdict)
+}
+
+/// A problem package in the format of problemtools, used by Kattis and many contest systems.
+pub struct Package {
+	/// The output validator, unless the output is compared with the answer.
+	pub verifier: Option<Verifier>,
+	/// The output validator of an interactive problem, which interacts with the solution.
+	pub interactor: Option<Interactor>,
+	/// Validators of the generated inputs, built as C++ like all validators.
+	pub input_validators: Vec<String>,
+}
+
+impl Package {
+	/// Loads the package given with --package, filling in the options which were not given from
+	/// it: the time and memory limits, the float tolerance and the samples. Its validators are
+	/// built.
+	pub fn load(args: &mut args::Args) -> Result<Option<Package>, Error> {
+		let Some(dir) = args.package.clone() else {
+			return Ok(None);
+		};
+		let yaml = match std::fs::read_to_string(dir.join("problem.yaml")) {
+			Ok(yaml) => read_yaml(&yaml),
+			Err(e) if e.kind() == io::ErrorKind::NotFound => Vec::new(),
+			Err(e) => return Err(e.into()),
+		};
+		let value = |key: &str| {
+			yaml.iter()
+				.find(|(k, _)| k == key)
+				.map(|(_, value)| value.as_str())
+		};
+		let validation = value("validation").unwrap_or("default");
+		let interactive = validation
+			.split_ascii_whitespace()
+			.any(|word| word == "interactive");
+		let flags: Vec<String> = value("validator_flags")
+			.unwrap_or("")
+			.split_ascii_whitespace()
+			.map(str::to_string)
+			.collect();
+		let mut found = Vec::new();
+
+		if args.time_limit.is_none() {
+			// Written by problemtools once it has timed the accepted submissions.
+			let time_limit = match std::fs::read_to_string(dir.join(".timelimit")) {
+				Ok(time_limit) => Some(time_limit),
+				Err(e) if e.kind() == io::ErrorKind::NotFound => {
+					value("limits.time_limit").map(str::to_string)
+				}
+				Err(e) => return Err(e.into()),
+			};
+			if let Some(time_limit) = time_limit {
+				let seconds = time_limit.trim().parse().ok();
+				let Some(time_limit) = seconds.and_then(|s| Duration::try_from_secs_f64(s).ok())
+				else {
+					return Err(invalid(format!(
+						"invalid time limit '{}'",
+						time_limit.trim()
+					)));
+				};
+				found.push(format!("time limit {time_limit:?}"));
+				args.time_limit = Some(time_limit);
+			}
+		}
+		if args.memory_limit.is_none()
+			&& let Some(memory) = value("limits.memory")
+		{
+			let Ok(megabytes) = memory.parse::<u64>() else {
+				return Err(invalid(format!("invalid memory limit '{memory}'")));
+			};
+			found.push(format!("memory limit {megabytes}M"));
+			args.memory_limit = Some(megabytes << 20);
+		}
+		if args.float_eps.is_none() {
+			// The default output validator takes either tolerance, cpfuzz takes the one given.
+			let tolerance = flags.windows(2).find_map(|flag| match flag[0].as_str() {
+				"float_tolerance" | "float_absolute_tolerance" | "float_relative_tolerance" => {
+					flag[1].parse().ok()
+				}
+				_ => None,
+			});
+			if let Some(tolerance) = tolerance {
+				found.push(format!("float tolerance {tolerance}"));
+				args.float_eps = Some(tolerance);
+			}
+		}
+		let samples = dir.join("data").join("sample");
+		// The samples of an interactive problem are interactions, which can not be compared.
+		if args.samples.is_none()
+			&& !interactive
+			&& args.interactive.is_none()
+			&& args.input.is_none()
+			&& args.inputs.is_none()
+			&& args.replay_seed.is_none()
+			&& samples.is_dir()
+		{
+			found.push(format!("samples {}", samples.display()));
+			args.samples = Some(samples);
+		}
+
+		let mut verifier = None;
+		let mut interactor = None;
+		let judged = match interactive {
+			true => args.interactive.is_some(),
+			false => args.verify.is_some() || args.checker.is_some(),
+		};
+		if validation.starts_with("custom") && !judged {
+			let output_validators = validators(&dir.join("output_validators"))?;
+			let Some(program) = output_validators.first() else {
+				return Err(invalid(format!(
+					"{} has custom validation but no output validator",
+					dir.display()
+				)));
+			};
+			Language::Cpp
+				.build(program)
+				.building(Component::OutputValidator, program)?;
+			if interactive {
+				found.push(format!("interactive output validator {program}"));
+				interactor = Some(Interactor {
+					program: program.clone(),
+					protocol: InteractorProtocol::Kattis,
+					language: Language::Cpp,
+					flags,
+					buffer: Interactor::buffer(args),
+				});
+			} else {
+				found.push(format!("output validator {program}"));
+				verifier = Some(Verifier {
+					program: program.clone(),
+					protocol: VerifyProtocol::Kattis,
+					language: Language::Cpp,
+					flags,
+				});
+			}
+		}
+		let mut input_validators = validators(&dir.join("input_validators"))?;
+		// The name of the directory in older packages.
+		input_validators.extend(validators(&dir.join("input_format_validators"))?);
+		for validator in &input_validators {
+			Language::Cpp
+				.build(validator)
+				.building(Component::InputValidator, validator)?;
+		}
+		if !input_validators.is_empty() {
+			found.push(format!("{} input validators", input_validators.len()));
+		}
+
+		if !verbosity::quiet() {
+			let found = match found.is_empty() {
+				true => "nothing to use".to_string(),
+				false => found.join(", "),
+			};
+			eprintln!("Problem package {}: {found}", dir.display());
+		}
+		Ok(Some(Package {
+			verifier,
+			interactor,
+			input_validators,
+		}))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::temp::TempDir;
+
+	fn run(exit: Exit) -> Run {
+		Run {
+			exit,
+			stdout: Vec::new(),
+			spilled: None,
+			stderr: Vec::new(),
+			unconsumed: false,
+		}
+	}
+
+	#[test]
+	fn exit_codes() {
+		let feedback_dir = TempDir::new("feedback").unwrap();
+		let status = |exit| super::status("validator", &run(exit), feedback_dir.path());
+		assert!(matches!(status(Exit::Failure(Some(ACCEPTED))), Status::Ok));
+		assert!(matches!(
+			status(Exit::Failure(Some(REJECTED))),
+			Status::Rejected { message, .. } if message == "wrong answer"
+		));
+		assert!(matches!(
+			status(Exit::Success),
+			Status::JudgeFailed { message, .. }
+				if message == "exited with code 0 instead of 42 or 43"
+		));
+		assert!(matches!(
+			status(Exit::Signal(6)),
+			Status::JudgeError { signal: 6, .. }
+		));
+		std::fs::write(
+			feedback_dir.path().join("judgemessage.txt"),
+			"\n  too short \n",
+		)
+		.unwrap();
+		assert!(matches!(
+			status(Exit::Failure(Some(REJECTED))),
+			Status::Rejected { message, .. } if message == "too short"
+		));
+		assert!(matches!(
+			verdict(&Exit::Failure(Some(REJECTED)), feedback_dir.path()),
+			Some(Verdict::WrongAnswer(message)) if message == "too short"
+		));
+		assert!(matches!(
+			verdict(&Exit::Failure(Some(ACCEPTED)), feedback_dir.path()),
+			Some(Verdict::Accepted)
+		));
+		assert!(verdict(&Exit::Failure(Some(1)), feedback_dir.path()).is_none());
+	}
+}
@@ -0,0 +1,2969 @@
+//! Stress testing of competitive programming solutions against brute forces, verifiers,
+//! checkers and interactors on generated tests. The command line is a thin wrapper around
+//! [`cli`], and [`FuzzSession`] runs sessions from code.
+
+use std::{
+	collections::HashSet,
+	ffi::OsStr,
+	fmt,
+	fs::{File, OpenOptions},
+	hash::{DefaultHasher, Hash as _, Hasher as _},
+	io::{self, BufRead, BufReader, BufWriter, Read, Write},
+	iter,
+	mem::{self, Discriminant},
+	num::NonZeroUsize,
+	os::unix::process::{CommandExt as _, ExitStatusExt as _},
+	path::{Path, PathBuf},
+	process::{Child, ChildStdin, ChildStdout, Command, ExitCode, ExitStatus, Stdio},
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, AtomicU64, Ordering},
+		mpsc,
+	},
+	thread::{self, JoinHandle},
+	time::{Duration, Instant},
+};
+
+use ansi_term::{Color, Style};
+
+mod api;
+mod archive;
+pub mod args;
+mod bench;
+mod budget;
+mod bundle;
+mod cargo;
+mod choices;
+mod compare;
+mod completions;
+mod config;
+mod corpus;
+mod coverage;
+mod diff;
+pub mod error;
+mod export;
+mod generator;
+mod generator_bindings;
+mod init;
+mod interrupt;
+mod json;
+mod kattis;
+mod limits;
+mod listen;
+mod log;
+mod mutate;
+mod output;
+mod persistent;
+mod progress;
+mod random;
+mod record;
+mod relay;
+mod report;
+mod resume;
+mod sample;
+mod schedule;
+mod shrink;
+mod signal;
+mod statement;
+mod style;
+mod temp;
+mod testlib;
+mod ui;
+mod usage;
+mod validation;
+mod verbosity;
+mod watch;
+mod zip;
+
+pub use api::{Counterexample, FuzzSession, FuzzSessionBuilder};
+use args::{InteractorProtocol, Language, VerifyProtocol};
+use budget::Budget;
+pub use cargo::cli as cargo_cli;
+use choices::Choices;
+use compare::Comparison;
+use coverage::Coverage;
+use error::{Component, Context as _, Error};
+use generator::Test;
+use json::Json;
+use kattis::Package;
+use limits::{Limits, Timer};
+use log::{EventLog, InputLog};
+use mutate::Pool;
+use persistent::Persistent;
+use progress::{Heartbeat, Progress};
+use report::Artifacts;
+pub use resume::arguments as resume_arguments;
+use resume::{Checkpoint, Saved};
+use schedule::Schedule;
+use temp::{Staged, TempDir, TempFile};
+use ui::Dashboard;
+use usage::{Statistics, Usage};
+use validation::Validators;
+
+trait IoResultExt {
+	fn ignore_broken_pipe(self) -> Self;
+}
+
+impl IoResultExt for io::Result<()> {
+	fn ignore_broken_pipe(self) -> Self {
+		match self {
+			Ok(()) => Ok(()),
+			Err(e) => match e.kind() {
+				io::ErrorKind::BrokenPipe => Ok(()),
+				_ => Err(e),
+			},
+		}
+	}
+}
+
+/// The hint of [`missing`] for the tools cpfuzz runs, such as g++.
+const NOT_INSTALLED: &str = "is it installed and on the PATH?";
+
+/// Explains the error of spawning a program which does not exist, which says nothing of which one
+/// it was otherwise.
+fn missing(program: impl fmt::Display, hint: &str) -> impl FnOnce(io::Error) -> io::Error {
+	move |e| match e.kind() {
+		io::ErrorKind::NotFound => {
+			io::Error::new(e.kind(), format!("could not run {program}, {hint}"))
+		}
+		_ => e,
+	}
+}
+
+enum Exit {
+	Success,
+	/// A non-zero exit code, absent if the process was judged to have failed otherwise.
+	Failure(Option<i32>),
+	Signal(i32),
+	MemoryLimit,
+	TimeLimit(Duration),
+}
+
+impl Exit {
+	fn success(&self) -> bool {
+		matches!(self, Exit::Success)
+	}
+
+	/// The status of `program` if it did not succeed, `failed` being the status of an ordinary
+	/// failure.
+	fn failure(&self, program: &str, failed: Status) -> Option<Status> {
+		match self {
+			Exit::Success => None,
+			Exit::Failure(_) => Some(failed),
+			&Exit::Signal(signal) => Some(Status::Crashed {
+				program: program.to_string(),
+				signal,
+			}),
+			Exit::MemoryLimit => Some(Status::MemoryLimit {
+				program: program.to_string(),
+			}),
+			&Exit::TimeLimit(limit) => Some(Status::TimeLimit {
+				program: program.to_string(),
+				limit,
+			}),
+		}
+	}
+}
+
+/// How much of the stderr of a program is kept, the rest is discarded.
+const STDERR_LIMIT: u64 = 1 << 16;
+
+/// Outputs larger than this are written to a temporary file rather than kept in memory, when
+/// they can be compared from there.
+const SPILL_THRESHOLD: u64 = 4 << 20;
+
+/// What a program reads from stdin.
+#[derive(Clone, Copy)]
+enum Stdin<'a> {
+	Bytes(&'a [u8]),
+	Staged(&'a Staged),
+}
+
+/// The result of running a program to completion.
+struct Run {
+	exit: Exit,
+	/// Empty if the output was spilled to a file.
+	stdout: Vec<u8>,
+	spilled: Option<TempFile>,
+	stderr: Vec<u8>,
+	/// Whether the program exited without reading all of its input, other than whitespace.
+	unconsumed: bool,
+}
+
+struct Process {
+	program: String,
+	started: Instant,
+	child: Child,
+	stderr: JoinHandle<io::Result<Vec<u8>>>,
+	timer: Option<Timer>,
+}
+
+impl Process {
+	/// Waits for the process to exit, recording how long it ran.
+	fn wait(self, limits: &Limits) -> io::Result<(Exit, Vec<u8>)> {
+		let Process {
+			program,
+			started,
+			mut child,
+			stderr,
+			timer,
+		} = self;
+		let (exit_code, cpu, memory) = usage::wait(&mut child)?;
+		let wall = started.elapsed();
+		let timed_out = timer.is_some_and(Timer::stop);
+		// Processes spawned by the program must not outlive it, and would keep stderr open.
+		limits::kill_group(child.id());
+		interrupt::unregister(child.id());
+		coverage::collect(&program, child.id());
+		usage::record(&program, Usage { wall, cpu, memory });
+		let stderr = stderr.join().expect("does not panic")?;
+		let exit = match limits.time_limit(&program) {
+			Some(limit) if timed_out => Exit::TimeLimit(limit),
+			_ => Self::classify(&program, exit_code, memory, &stderr, limits),
+		};
+		Ok((exit, stderr))
+	}
+
+	fn classify(
+		program: &str,
+		exit_code: ExitStatus,
+		memory: u64,
+		stderr: &[u8],
+		limits: &Limits,
+	) -> Exit {
+		if exit_code.success() {
+			Exit::Success
+		} else if limits.exceeded_memory(program, memory, stderr) {
+			Exit::MemoryLimit
+		} else if let Some(signal) = exit_code.signal() {
+			Exit::Signal(signal)
+		} else {
+			Exit::Failure(exit_code.code())
+		}
+	}
+}
+
+impl Language {
+	/// The source file of the program, which failures are attributed to.
+	fn source(self, problem: &str) -> String {
+		match self {
+			Language::Rust | Language::RustDebug => cargo::source(problem),
+			Language::Cpp | Language::CppSanitize => format!("{problem}.cpp"),
+		}
+	}
+
+	fn build(self, problem: &str) -> io::Result<()> {
+		if watch::unchanged(problem) {
+			return Ok(());
+		}
+		interrupt::check()?;
+		let (cmd, args): (&str, &[&str]) = match self {
+			Language::Rust => ("cargo", &["build", "--bin", problem, "--release"]),
+			Language::RustDebug => ("cargo", &["build", "--bin", problem]),
+			Language::Cpp => ("g++", &["-O2", &format!("{problem}.cpp"), "-o", problem]),
+			Language::CppSanitize => (
+				"g++",
+				&[
+					"-g",
+					"-fsanitize=address,undefined",
+					&format!("{problem}.cpp"),
+					"-o",
+					problem,
+				],
+			),
+		};
+		let started = Instant::now();
+		let mut command = Command::new(cmd);
+		command.args(args);
+		if let Language::Rust | Language::RustDebug = self {
+			command.args(cargo::manifest_arguments());
+		}
+		verbosity::command(&command);
+		let mut child = command.spawn().map_err(missing(cmd, NOT_INSTALLED))?;
+		let exit_code = child.wait()?;
+		usage::record_build(problem, started.elapsed());
+		if !exit_code.success() {
+			return Err(watch::build_failed(exit_code));
+		}
+		let binary = self.binary(problem);
+		if !binary.is_file() {
+			return Err(io::Error::new(
+				io::ErrorKind::NotFound,
+				format!(
+					"{cmd} succeeded without producing {}, did the build output go elsewhere?",
+					binary.display()
+				),
+			));
+		}
+		Ok(())
+	}
+
+	/// The path of the built program.
+	fn binary(self, problem: &str) -> PathBuf {
+		match self {
+			Language::Rust => cargo::artifact("release", problem),
+			Language::RustDebug => cargo::artifact("debug", problem),
+			// Joined, so an absolute path is kept as it is.
+			Language::Cpp | Language::CppSanitize => Path::new(".").join(problem),
+		}
+	}
+
+	fn spawn(self, problem: &str, arguments: &[&OsStr], limits: &Limits) -> io::Result<Process> {
+		self.spawn_reading(problem, arguments, limits, Stdio::piped())
+	}
+
+	/// Spawns the program like [`Self::spawn`], reading stdin from the given source.
+	fn spawn_reading(
+		self,
+		problem: &str,
+		arguments: &[&OsStr],
+		limits: &Limits,
+		stdin: Stdio,
+	) -> io::Result<Process> {
+		let binary = self.binary(problem);
+		let mut command = Command::new(&binary);
+		command
+			.args(arguments)
+			.process_group(0)
+			.stdin(stdin)
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped());
+		limits.apply(problem, self, &mut command);
+		coverage::apply(problem, &mut command);
+		verbosity::command(&command);
+		let started = Instant::now();
+		let mut child = command
+			.spawn()
+			.map_err(missing(
+				binary.display(),
+				"did the build output go elsewhere?",
+			))
+			.map_err(error::not_started)?;
+		interrupt::register(child.id());
+		let timer = limits.start_timer(problem, child.id());
+		let mut stderr = child.stderr.take().expect("is piped");
+		let stderr = thread::spawn(move || {
+			let mut buf = Vec::new();
+			stderr.by_ref().take(STDERR_LIMIT).read_to_end(&mut buf)?;
+			// Keep draining so the program does not block on a full pipe.
+			io::copy(&mut stderr, &mut io::sink())?;
+			Ok(buf)
+		});
+		Ok(Process {
+			program: problem.to_string(),
+			started,
+			child,
+			stderr,
+			timer,
+		})
+	}
+
+	fn run(self, problem: &str, limits: &Limits, input: &[u8]) -> io::Result<Run> {
+		self.run_with_arguments(problem, &[], limits, input)
+	}
+
+	/// Runs the program like [`Self::run`], spilling an output larger than [`SPILL_THRESHOLD`] to
+	/// a temporary file.
+	fn run_spilling(self, problem: &str, limits: &Limits, input: Stdin) -> io::Result<Run> {
+		self.run_process(problem, &[], limits, input, true)
+	}
+
+	fn run_with_arguments(
+		self,
+		problem: &str,
+		arguments: &[&OsStr],
+		limits: &Limits,
+		input: &[u8],
+	) -> io::Result<Run> {
+		self.run_process(problem, arguments, limits, Stdin::Bytes(input), false)
+	}
+
+	fn run_process(
+		self,
+		problem: &str,
+		arguments: &[&OsStr],
+		limits: &Limits,
+		input: Stdin,
+		spill: bool,
+	) -> io::Result<Run> {
+		thread::scope(|scope| {
+			// The input is read from another handle once the program exited, which gives what it
+			// left unread.
+			let (mut process, mut unread, writer): (_, Box<dyn Read>, _) = match input {
+				Stdin::Bytes(input) => {
+					let (reader, mut writer) = io::pipe()?;
+					let unread = reader.try_clone()?;
+					let process = self.spawn_reading(problem, arguments, limits, reader.into())?;
+					// Writing blocks rather than failing when the program exits without reading
+					// all of it, as the pipe is still open for reading.
+					let writer = scope.spawn(move || writer.write_all(input));
+					(process, Box::new(unread), Some(writer))
+				}
+				Stdin::Staged(staged) => {
+					let file = staged.open()?;
+					// Shares the position of the program in the file.
+					let unread = file.try_clone()?;
+					let process = self.spawn_reading(problem, arguments, limits, file.into())?;
+					(process, Box::new(unread), None)
+				}
+			};
+			let reader = process.child.stdout.as_mut().expect("is piped");
+			let mut stdout = Vec::new();
+			let limit = if spill { SPILL_THRESHOLD } else { u64::MAX };
+			reader.take(limit).read_to_end(&mut stdout)?;
+			let spilled = if stdout.len() as u64 == limit {
+				let file = TempFile::new("output", &stdout)?;
+				let mut writer = BufWriter::new(OpenOptions::new().append(true).open(file.path())?);
+				io::copy(reader, &mut writer)?;
+				writer.flush()?;
+				stdout.clear();
+				Some(file)
+			} else {
+				None
+			};
+			let (exit, stderr) = process.wait(limits)?;
+			let unconsumed = unconsumed(&mut unread)?;
+			if let Some(writer) = writer {
+				writer.join().expect("does not panic")?;
+			}
+			Ok(Run {
+				exit,
+				stdout,
+				spilled,
+				stderr,
+				unconsumed,
+			})
+		})
+	}
+
+	fn run_interactee(
+		self,
+		problem: &str,
+		limits: &Limits,
+	) -> io::Result<(ChildStdin, ChildStdout, Process)> {
+		let mut process = self.spawn(problem, &[], limits)?;
+		let stdin = process.child.stdin.take().expect("is piped");
+		let stdout = process.child.stdout.take().expect("is piped");
+		Ok((stdin, stdout, process))
+	}
+
+	fn run_interacter(
+		self,
+		interactor: &Interactor,
+		limits: &Limits,
+		test: &Test,
+		child_stdin: ChildStdin,
+		child_stdout: ChildStdout,
+		interactee: Process,
+	) -> Result<(Outcome, Vec<u8>), Error> {
+		let input = &test.input;
+		// The feedback directory an output validator of a problem package writes to.
+		let mut feedback = None;
+		let files = match interactor.protocol {
+			InteractorProtocol::Stdin => Vec::new(),
+			InteractorProtocol::Files => vec![
+				TempFile::new("input", input)?,
+				TempFile::new("verdict", &[])?,
+			],
+			InteractorProtocol::Kattis => {
+				feedback = Some(TempDir::new("feedback")?);
+				let answer = test.answer.as_deref().unwrap_or_default();
+				vec![
+					TempFile::new("input", input)?,
+					TempFile::new("answer", answer)?,
+				]
+			}
+		};
+		let arguments: Vec<_> = files
+			.iter()
+			.map(|file| file.path().as_os_str())
+			.chain(feedback.iter().map(|feedback| feedback.path().as_os_str()))
+			.chain(interactor.flags.iter().map(OsStr::new))
+			.collect();
+		let mut process = interactor
+			.language
+			.spawn(&interactor.program, &arguments, limits)
+			.running(interactor.component(), &interactor.program)?;
+		let mut stdin = process.child.stdin.take().expect("is piped");
+		let stdout = process.child.stdout.take().expect("is piped");
+		if let InteractorProtocol::Stdin = interactor.protocol {
+			stdin.write_all(input).ignore_broken_pipe()?;
+		}
+		let mut transcript = Vec::new();
+		let channels = [
+			relay::Channel::new(stdout, child_stdin, Party::Interactee)?,
+			relay::Channel::new(child_stdout, stdin, Party::Interactor)?,
+		];
+		let ids = [process.child.id(), interactee.child.id()];
+		let ending = relay::relay(channels, &mut transcript, interactor.buffer, limits, ids)?;
+		let program = process.program.clone();
+		let interactee_program = interactee.program.clone();
+		let (exit, stderr) = process
+			.wait(limits)
+			.running(interactor.component(), &interactor.program)?;
+		let (interactee_exit, interactee_stderr) = interactee.wait(limits)?;
+		let answer = final_answer(&transcript);
+		let verdict = match (interactor.protocol, &feedback) {
+			(InteractorProtocol::Files, _) => {
+				Verdict::parse(&std::fs::read_to_string(files[1].path())?)
+			}
+			(InteractorProtocol::Kattis, Some(feedback)) => kattis::verdict(&exit, feedback.path()),
+			_ => None,
+		};
+		// An output validator judges by its exit code alone, which must accept or reject.
+		let judged =
+			verdict.is_some() || !matches!(interactor.protocol, InteractorProtocol::Kattis);
+		let (status, stderr) = if ending.stalled {
+			(Status::InteractionStalled, interactee_stderr)
+		} else if let Exit::MemoryLimit = exit {
+			// The interactor is a judge, so running out of memory does not fail the solution.
+			(testlib::out_of_memory(&program), stderr)
+		} else if let Exit::TimeLimit(limit) = interactee_exit {
+			let program = interactee_program;
+			(Status::TimeLimit { program, limit }, interactee_stderr)
+		} else if let Exit::MemoryLimit = interactee_exit {
+			let program = interactee_program;
+			(Status::MemoryLimit { program }, interactee_stderr)
+		} else if let Some(Verdict::WrongAnswer(message)) = verdict {
+			(Status::WrongAnswer { message }, interactee_stderr)
+		} else if let Some(Verdict::ProtocolViolation(message)) = verdict {
+			(Status::ProtocolViolation { message }, interactee_stderr)
+		} else if let Exit::Signal(signal) = exit {
+			(Status::JudgeError { program, signal }, stderr)
+		} else if verdict.is_none() && (!exit.success() || !judged) {
+			// The interactee failing first is what made the interactor fail when it expected more.
+			match ending.closed_first {
+				Some(Party::Interactee) if !interactee_exit.success() => {
+					let program = interactee_program;
+					let party = Party::Interactee;
+					(Status::ClosedEarly { program, party }, stderr)
+				}
+				_ => (Status::InteractorFailed, stderr),
+			}
+		} else if !interactee_exit.success() {
+			match ending.closed_first {
+				Some(Party::Interactor) => {
+					let party = Party::Interactor;
+					(Status::ClosedEarly { program, party }, interactee_stderr)
+				}
+				_ => (Status::InteracteeFailed, interactee_stderr),
+			}
+		} else {
+			(Status::Ok, interactee_stderr)
+		};
+		let outcome = Outcome {
+			transcript,
+			..Outcome::new(status, stderr)
+		};
+		Ok((outcome, answer))
+	}
+
+	fn interact(
+		self,
+		problem: &str,
+		component: Component,
+		interactor: &Interactor,
+		limits: &Limits,
+		test: &Test,
+	) -> Result<(Outcome, Vec<u8>), Error> {
+		let (chid_stdin, child_stdout, process) = self
+			.run_interactee(problem, limits)
+			.running(component, problem)?;
+		self.run_interacter(interactor, limits, test, chid_stdin, child_stdout, process)
+	}
+}
+
+#[derive(Clone)]
+struct Interactor {
+	program: String,
+	protocol: InteractorProtocol,
+	/// The language of the interactor, which is C++ for the output validator of a problem package.
+	language: Language,
+	/// The validator flags of a problem package, given to its output validator.
+	flags: Vec<String>,
+	/// Size of the buffer of the transfers between the parties.
+	buffer: usize,
+}
+
+impl Interactor {
+	/// The interactor given with --interactive, or otherwise the output validator of an
+	/// interactive package.
+	fn new(args: &args::Args, package: Option<&Package>) -> Result<Option<Interactor>, Error> {
+		let Some(program) = &args.interactive else {
+			return Ok(package.and_then(|package| package.interactor.clone()));
+		};
+		args.language
+			.build(program)
+			.building(Component::Interactor, program)?;
+		Ok(Some(Interactor {
+			program: program.clone(),
+			protocol: args.interactor_protocol,
+			language: args.language,
+			flags: Vec::new(),
+			buffer: Interactor::buffer(args),
+		}))
+	}
+
+	/// The size of the buffer given with --pipe-buffer.
+	fn buffer(args: &args::Args) -> usize {
+		args.pipe_buffer.clamp(1, usize::MAX as u64) as usize
+	}
+
+	/// The output validator of an interactive package talks with the Kattis protocol.
+	fn component(&self) -> Component {
+		match self.protocol {
+			InteractorProtocol::Kattis => Component::OutputValidator,
+			_ => Component::Interactor,
+		}
+	}
+}
+
+/// The verdict an interactor using the files protocol wrote to the verdict file.
+enum Verdict {
+	Accepted,
+	WrongAnswer(String),
+	ProtocolViolation(String),
+}
+
+impl Verdict {
+	/// Parses the first line of the verdict file, returning none if it is not a verdict.
+	fn parse(src: &str) -> Option<Verdict> {
+		let line = src.lines().next()?.trim();
+		let (keyword, message) = line.split_once(' ').unwrap_or((line, ""));
+		let message = message.trim().to_string();
+		match keyword {
+			"OK" | "AC" => Some(Verdict::Accepted),
+			"WA" => Some(Verdict::WrongAnswer(message)),
+			"PV" | "PE" => Some(Verdict::ProtocolViolation(message)),
+			_ => None,
+		}
+	}
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Party {
+	Interactor,
+	Interactee,
+}
+
+impl Party {
+	fn name(self) -> &'static str {
+		match self {
+			Party::Interactor => "interactor",
+			Party::Interactee => "interactee",
+		}
+	}
+
+	fn other(self) -> Party {
+		match self {
+			Party::Interactor => Party::Interactee,
+			Party::Interactee => Party::Interactor,
+		}
+	}
+}
+
+/// Reads what a program left of its input to the end, returning whether it is more than
+/// whitespace.
+fn unconsumed(mut unread: impl Read) -> io::Result<bool> {
+	let mut buffer = vec![0; 1 << 16];
+	let mut unconsumed = false;
+	loop {
+		match unread.read(&mut buffer) {
+			Ok(0) => return Ok(unconsumed),
+			Ok(n) => unconsumed |= !buffer[..n].iter().all(u8::is_ascii_whitespace),
+			Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+			Err(e) => return Err(e),
+		}
+	}
+}
+
+/// The last line the interactee wrote to the interactor, which is taken as its final answer.
+fn final_answer(transcript: &[(Party, Vec<u8>)]) -> Vec<u8> {
+	// Data is tagged with the party receiving it.
+	let written: Vec<u8> = transcript
+		.iter()
+		.filter(|(party, _)| matches!(party, Party::Interactor))
+		.flat_map(|(_, data)| data.iter().copied())
+		.collect();
+	compare::lines(&written)
+		.rfind(|line| !line.trim_ascii().is_empty())
+		.unwrap_or_default()
+		.to_vec()
+}
+
+enum Status {
+	Ok,
+	Failed,
+	PrimaryFailed,
+	SecondaryFailed {
+		program: String,
+	},
+	VerifierFailed,
+	CheckerFailed,
+	DifferentOutputs,
+	Nondeterministic,
+	/// The solution passed, but took longer than allowed by --fail-if-slower-than or
+	/// --fail-if-ratio.
+	Slow {
+		wall: Duration,
+		limit: Duration,
+	},
+	/// A program was terminated by a signal, such as a segmentation fault or a failed assertion.
+	Crashed {
+		program: String,
+		signal: i32,
+	},
+	MemoryLimit {
+		program: String,
+	},
+	TimeLimit {
+		program: String,
+		limit: Duration,
+	},
+	InteractorFailed,
+	InteracteeFailed,
+	/// The verifier, checker or interactor crashed instead of judging the solution.
+	JudgeError {
+		program: String,
+		signal: i32,
+	},
+	/// The interaction was killed after running too long or neither party writing anything.
+	InteractionStalled,
+	/// The interactor reported that the solution answered wrong.
+	WrongAnswer {
+		message: String,
+	},
+	/// The interactor reported that the solution did not follow the protocol.
+	ProtocolViolation {
+		message: String,
+	},
+	/// The solution exited without reading all of the input, with --require-consume.
+	Unconsumed {
+		program: String,
+	},
+	/// A party of the interaction closed its output while the other still expected data, which
+	/// the other failing after it tells.
+	ClosedEarly {
+		program: String,
+		party: Party,
+	},
+	/// A testlib.h checker or verifier rejected the output, saying why.
+	Rejected {
+		program: String,
+		message: String,
+	},
+	/// A testlib.h checker or verifier failed instead of judging the solution, such as on a wrong
+	/// answer file.
+	JudgeFailed {
+		program: String,
+		message: String,
+	},
+	/// The outputs of three or more solutions, grouped by equality.
+	Disagreement {
+		groups: Vec<Vec<String>>,
+	},
+	/// A failure of a secondary solution where the primary succeeded.
+	Secondary {
+		program: String,
+		status: Box<Status>,
+	},
+}
+
+impl Status {
+	fn message(&self) -> String {
+		let message = match self {
+			Status::Ok => "Passed",
+			Status::Failed => "Exited with non-zero exit code",
+			Status::PrimaryFailed => "Primary exited with non-zero exit code",
+			Status::SecondaryFailed { program } => {
+				&format!("Secondary {program} exited with non-zero exit code")
+			}
+			Status::VerifierFailed => "Verifier rejected the output",
+			Status::CheckerFailed => "Checker rejected the output",
+			Status::DifferentOutputs => "Different outputs",
+			Status::Nondeterministic => "Different outputs when run twice",
+			Status::Slow { wall, limit } => {
+				&format!("Took {wall:.1?}, more than the allowed {limit:.1?}")
+			}
+			Status::Crashed { program, signal } => {
+				&format!("{program} crashed with {}", signal::describe(*signal))
+			}
+			Status::TimeLimit { program, limit } => {
+				&format!("{program} exceeded the time limit of {limit:.1?}")
+			}
+			Status::MemoryLimit { program } => &format!("{program} exceeded the memory limit"),
+			Status::InteractorFailed => "Interactor exited with non-zero exit code",
+			Status::InteracteeFailed => "Interactee exited with non-zero exit code",
+			Status::JudgeError { program, signal } => &format!(
+				"Judge {program} crashed with {}, the solution was not judged",
+				signal::describe(*signal)
+			),
+			Status::InteractionStalled => "Interaction stalled and was killed",
+			Status::WrongAnswer { message } => {
+				&format!("Interactor judged the answer wrong: {message}")
+			}
+			Status::ProtocolViolation { message } => {
+				&format!("Interactor reported a protocol violation: {message}")
+			}
+			Status::Unconsumed { program } => {
+				&format!("{program} exited without reading all of the input")
+			}
+			Status::ClosedEarly { program, party } => &format!(
+				"The {} {program} closed its output while the {} still expected data",
+				party.name(),
+				party.other().name()
+			),
+			Status::Rejected { program, message } => {
+				&format!("{program} rejected the output: {message}")
+			}
+			Status::JudgeFailed { program, message } => {
+				&format!("Judge {program} failed, the solution was not judged: {message}")
+			}
+			Status::Disagreement { groups } => &format!(
+				"Outputs disagree: {}",
+				groups
+					.iter()
+					.map(|group| group.join(", "))
+					.collect::<Vec<_>>()
+					.join(" | ")
+			),
+			Status::Secondary { program, status } => {
+				&format!("Secondary {program}: {}", status.message())
+			}
+		};
+		message.to_string()
+	}
+
+	/// Identifies the status in the machine-readable report.
+	fn name(&self) -> &'static str {
+		match self {
+			Status::Ok => "ok",
+			Status::Failed => "failed",
+			Status::PrimaryFailed => "primary_failed",
+			Status::SecondaryFailed { .. } => "secondary_failed",
+			Status::VerifierFailed => "verifier_failed",
+			Status::CheckerFailed => "checker_failed",
+			Status::DifferentOutputs => "different_outputs",
+			Status::Nondeterministic => "nondeterministic",
+			Status::Slow { .. } => "slow",
+			Status::Crashed { .. } => "crashed",
+			Status::MemoryLimit { .. } => "memory_limit",
+			Status::TimeLimit { .. } => "time_limit",
+			Status::InteractorFailed => "interactor_failed",
+			Status::InteracteeFailed => "interactee_failed",
+			Status::JudgeError { .. } => "judge_error",
+			Status::InteractionStalled => "interaction_stalled",
+			Status::WrongAnswer { .. } => "wrong_answer",
+			Status::ProtocolViolation { .. } => "protocol_violation",
+			Status::Unconsumed { .. } => "unconsumed",
+			Status::ClosedEarly { .. } => "closed_early",
+			Status::Rejected { .. } => "rejected",
+			Status::JudgeFailed { .. } => "judge_failed",
+			Status::Disagreement { .. } => "disagreement",
+			Status::Secondary { .. } => "secondary",
+		}
+	}
+
+	fn report(&self) {
+		if self.failed() {
+			eprint!("\n{} {}", style::failed("FAILED"), self.message());
+		}
+	}
+
+	fn failed(&self) -> bool {
+		!matches!(self, Status::Ok)
+	}
+
+	/// The exit code of cpfuzz when this is the failure found, as documented in the help.
+	fn exit_code(&self) -> u8 {
+		match self {
+			Status::Ok => 0,
+			Status::DifferentOutputs
+			| Status::Disagreement { .. }
+			| Status::Nondeterministic
+			| Status::CheckerFailed
+			| Status::WrongAnswer { .. }
+			| Status::Rejected { .. } => 10,
+			Status::Failed
+			| Status::PrimaryFailed
+			| Status::Crashed { .. }
+			| Status::Unconsumed { .. }
+			| Status::InteracteeFailed => 11,
+			Status::Slow { .. } | Status::TimeLimit { .. } | Status::InteractionStalled => 12,
+			Status::VerifierFailed => 13,
+			Status::MemoryLimit { .. } => 14,
+			Status::ProtocolViolation { .. } | Status::ClosedEarly { .. } => 15,
+			Status::SecondaryFailed { .. }
+			| Status::InteractorFailed
+			| Status::JudgeError { .. }
+			| Status::JudgeFailed { .. }
+			| Status::Secondary { .. } => 16,
+		}
+	}
+
+	fn same_kind(&self, other: &Status) -> bool {
+		mem::discriminant(self) == mem::discriminant(other)
+	}
+}
+
+/// The status of a test together with what the programs wrote. The stderr is that of the program
+/// which failed or otherwise of the primary.
+struct Outcome {
+	status: Status,
+	stderr: Vec<u8>,
+	/// The output of the primary, if it got to write one.
+	output: Option<Vec<u8>>,
+	/// The output the primary was compared against.
+	expected: Option<Vec<u8>>,
+	/// The data exchanged in an interaction, tagged with the party receiving it.
+	transcript: Vec<(Party, Vec<u8>)>,
+	/// The programs which exited without reading all of the input, which often means they read
+	/// another format than the generator writes.
+	unconsumed: Vec<String>,
+}
+
+impl Outcome {
+	fn new(status: Status, stderr: Vec<u8>) -> Outcome {
+		Outcome {
+			status,
+			stderr,
+			output: None,
+			expected: None,
+			transcript: Vec::new(),
+			unconsumed: Vec::new(),
+		}
+	}
+}
+
+impl Run {
+	/// Reads the output back into memory if it was spilled to a file.
+	fn load(&mut self) -> io::Result<()> {
+		if let Some(file) = self.spilled.take() {
+			self.stdout = std::fs::read(file.path())?;
+		}
+		Ok(())
+	}
+
+	/// Reads the output from wherever it is.
+	fn reader(&self) -> io::Result<Box<dyn BufRead + '_>> {
+		Ok(match &self.spilled {
+			Some(file) => Box::new(BufReader::new(File::open(file.path())?)),
+			None => Box::new(&self.stdout[..]),
+		})
+	}
+
+	/// The outcome of this run, with `status` unless the program itself failed.
+	fn outcome(
+		self,
+		program: &str,
+		failed: Status,
+		status: impl FnOnce(&[u8]) -> Status,
+	) -> Outcome {
+		let status = match self.exit.failure(program, failed) {
+			Some(status) => status,
+			None => status(&self.stdout),
+		};
+		Outcome {
+			output: Some(self.stdout),
+			..Outcome::new(status, self.stderr)
+		}
+	}
+}
+
+/// Runs the programs concurrently as they are independent, returning their runs in the same
+/// order.
+fn run_concurrently(
+	programs: &[&str],
+	run: impl Fn(&str) -> Result<Run, Error> + Sync,
+) -> Result<Vec<Run>, Error> {
+	let runs: Vec<_> = thread::scope(|scope| {
+		let handles: Vec<_> = programs
+			.iter()
+			.map(|program| {
+				let run = &run;
+				// The usage and coverage are recorded by the thread running the program, so hand
+				// them over.
+				scope.spawn(move || (run(program), usage::take(), coverage::take()))
+			})
+			.collect();
+		handles
+			.into_iter()
+			.map(|handle| handle.join().expect("does not panic"))
+			.collect()
+	});
+	runs.into_iter()
+		.map(|(run, recorded, covered)| {
+			for (program, usage) in recorded {
+				usage::record(&program, usage);
+			}
+			coverage::merge(&covered);
+			run
+		})
+		.collect()
+}
+
+/// The status of comparing the outputs of the solutions, where the first is the primary.
+fn agreement(comparison: &Comparison, outputs: &[(&str, Vec<u8>)]) -> Status {
+	let outputs: Vec<_> = outputs
+		.iter()
+		.map(|(label, output)| (*label, &output[..]))
+		.collect();
+	let groups = comparison.group(&outputs);
+	match groups.len() {
+		1 => Status::Ok,
+		_ if outputs.len() == 2 => Status::DifferentOutputs,
+		_ => Status::Disagreement {
+			groups: groups
+				.into_iter()
+				.map(|group| group.into_iter().map(str::to_string).collect())
+				.collect(),
+		},
+	}
+}
+
+#[derive(Clone)]
+struct Verifier {
+	program: String,
+	protocol: VerifyProtocol,
+	/// The language of the verifier, which is C++ for the output validator of a problem package.
+	language: Language,
+	/// The validator flags of a problem package, given to its output validator.
+	flags: Vec<String>,
+}
+
+impl Verifier {
+	/// The verifier given with --verify, or otherwise the output validator of the package.
+	fn new(args: &args::Args, package: Option<&Package>) -> Result<Option<Verifier>, Error> {
+		let Some(program) = &args.verify else {
+			return Ok(package.and_then(|package| package.verifier.clone()));
+		};
+		args.language
+			.build(program)
+			.building(Component::Verifier, program)?;
+		Ok(Some(Verifier {
+			program: program.clone(),
+			protocol: args.verify_protocol,
+			language: args.language,
+			flags: Vec::new(),
+		}))
+	}
+
+	/// Judges the output of a solution, which is rejected unless the verifier accepts it. The
+	/// answer is only given to testlib verifiers, which are given the output itself without one.
+	fn judge(
+		&self,
+		limits: &Limits,
+		stdin: &[u8],
+		stdout: &[u8],
+		answer: Option<&[u8]>,
+	) -> Result<Status, Error> {
+		// The output validator of a package judges with the Kattis protocol.
+		let component = match self.protocol {
+			VerifyProtocol::Kattis => Component::OutputValidator,
+			_ => Component::Verifier,
+		};
+		let running = |run: io::Result<Run>| run.running(component, &self.program);
+		let run = match self.protocol {
+			VerifyProtocol::Concatenate => {
+				let mut new_stdin = stdin.to_vec();
+				new_stdin.push(b'\n');
+				new_stdin.extend_from_slice(stdout);
+				running(self.language.run(&self.program, limits, &new_stdin))?
+			}
+			VerifyProtocol::Files => {
+				let input = TempFile::new("input", stdin)?;
+				running(self.language.run_with_arguments(
+					&self.program,
+					&[input.path().as_os_str()],
+					limits,
+					stdout,
+				))?
+			}
+			VerifyProtocol::Testlib => {
+				let input = TempFile::new("input", stdin)?;
+				let output = TempFile::new("output", stdout)?;
+				let answer = TempFile::new("answer", answer.unwrap_or(stdout))?;
+				let arguments = [input.path(), output.path(), answer.path()].map(Path::as_os_str);
+				running(
+					self.language
+						.run_with_arguments(&self.program, &arguments, limits, &[]),
+				)?
+			}
+			VerifyProtocol::Kattis => {
+				let input = TempFile::new("input", stdin)?;
+				let answer = TempFile::new("answer", answer.unwrap_or(stdout))?;
+				let feedback = TempDir::new("feedback")?;
+				let arguments: Vec<_> = [input.path(), answer.path(), feedback.path()]
+					.map(Path::as_os_str)
+					.into_iter()
+					.chain(self.flags.iter().map(OsStr::new))
+					.collect();
+				let run = running(self.language.run_with_arguments(
+					&self.program,
+					&arguments,
+					limits,
+					stdout,
+				))?;
+				return Ok(kattis::status(&self.program, &run, feedback.path()));
+			}
+		};
+		Ok(testlib::status(&self.program, &run, Status::VerifierFailed))
+	}
+}
+
+enum Runner {
+	Single {
+		problem: String,
+	},
+	Compare {
+		primary: String,
+		secondaries: Vec<String>,
+		comparison: Comparison,
+		verifier: Option<Verifier>,
+	},
+	Interactive {
+		problem: String,
+		interactor: Interactor,
+		secondaries: Vec<String>,
+		comparison: Comparison,
+	},
+	Verify {
+		problem: String,
+		verifier: Verifier,
+	},
+	Checker {
+		primary: String,
+		reference: String,
+		checker: String,
+	},
+	Answer {
+		problem: String,
+		comparison: Comparison,
+	},
+	SelfCheck {
+		problem: String,
+		comparison: Comparison,
+	},
+}
+
+impl Runner {
+	fn new(args: &args::Args, package: Option<&Package>) -> Result<Runner, Error> {
+		args.language
+			.build(&args.name)
+			.building(Component::Solution, &args.name)?;
+		// Dear Bærbak, this if else switch is so beautiful, and nothing you ever have said
+		// or will ever say will convince me otherwise.
+		Ok(if let Some(interactor) = Interactor::new(args, package)? {
+			for comparator in &args.compare {
+				args.language
+					.build(comparator)
+					.building(Component::Comparator, comparator)?;
+			}
+			Runner::Interactive {
+				problem: args.name.clone(),
+				interactor,
+				secondaries: args.compare.clone(),
+				comparison: Comparison::new(args),
+			}
+		} else if args.answer {
+			Runner::Answer {
+				problem: args.name.clone(),
+				comparison: Comparison::new(args),
+			}
+		} else if args.self_check {
+			Runner::SelfCheck {
+				problem: args.name.clone(),
+				comparison: Comparison::new(args),
+			}
+		} else if let Some(checker) = &args.checker {
+			let [reference] = &args.compare[..] else {
+				return Err(Error::Arguments(format!(
+					"--checker judges the output against the answer of one comparator, but {} \
+					 were given",
+					args.compare.len()
+				)));
+			};
+			args.language
+				.build(reference)
+				.building(Component::Reference, reference)?;
+			args.language
+				.build(checker)
+				.building(Component::Checker, checker)?;
+			Runner::Checker {
+				primary: args.name.clone(),
+				reference: reference.clone(),
+				checker: checker.clone(),
+			}
+		} else if !args.compare.is_empty() {
+			for comparator in &args.compare {
+				args.language
+					.build(comparator)
+					.building(Component::Comparator, comparator)?;
+			}
+			Runner::Compare {
+				primary: args.name.clone(),
+				secondaries: args.compare.clone(),
+				comparison: Comparison::new(args),
+				verifier: Verifier::new(args, package)?,
+			}
+		} else if let Some(verifier) = Verifier::new(args, package)? {
+			Runner::Verify {
+				problem: args.name.clone(),
+				verifier,
+			}
+		} else {
+			Runner::Single {
+				problem: args.name.clone(),
+			}
+		})
+	}
+
+	/// Whether the output is judged by another solution or a verifier, which the solution leaving
+	/// input unread only passes by coincidence.
+	fn compares(&self) -> bool {
+		matches!(
+			self,
+			Runner::Compare { .. } | Runner::Verify { .. } | Runner::Checker { .. }
+		)
+	}
+
+	fn run(
+		&self,
+		languge: &Language,
+		limits: &Limits,
+		persistent: Option<&Persistent>,
+		test: &Test,
+	) -> Result<Outcome, Error> {
+		let unconsumed = Mutex::new(Vec::new());
+		let mut outcome = self.judge(languge, limits, persistent, test, &unconsumed)?;
+		outcome.unconsumed = unconsumed.into_inner().expect("not poisoned");
+		outcome.unconsumed.sort();
+		outcome.unconsumed.dedup();
+		Ok(outcome)
+	}
+
+	/// Runs the programs on the test and judges them, adding those which did not read all of the
+	/// input to `unconsumed`.
+	fn judge(
+		&self,
+		languge: &Language,
+		limits: &Limits,
+		persistent: Option<&Persistent>,
+		test: &Test,
+		unconsumed: &Mutex<Vec<String>>,
+	) -> Result<Outcome, Error> {
+		let stdin = &test.input;
+		let staged = Staged::new(stdin)?;
+		let input = staged.as_ref().map_or(Stdin::Bytes(stdin), Stdin::Staged);
+		// The solutions other than the one fuzzed are those it is compared with.
+		let component = |program: &str| match self {
+			Runner::Compare { primary, .. } if primary != program => Component::Comparator,
+			Runner::Interactive { problem, .. } if problem != program => Component::Comparator,
+			Runner::Checker { reference, .. } if reference == program => Component::Reference,
+			_ => Component::Solution,
+		};
+		let consumed = |program: &str, run: io::Result<Run>| {
+			if run.as_ref().is_ok_and(|run| run.unconsumed) {
+				let mut unconsumed = unconsumed.lock().expect("not poisoned");
+				unconsumed.push(program.to_string());
+			}
+			run.running(component(program), program)
+		};
+		let solution = |program: &str| match persistent {
+			Some(persistent) if persistent.program == program => persistent
+				.run(*languge, limits, stdin)
+				.running(component(program), program),
+			_ => consumed(
+				program,
+				languge.run_process(program, &[], limits, input, false),
+			),
+		};
+		match self {
+			Runner::Single { problem } => {
+				let run = solution(problem)?;
+				Ok(run.outcome(problem, Status::Failed, |_| Status::Ok))
+			}
+			Runner::Compare {
+				primary,
+				secondaries,
+				comparison,
+				verifier,
+			} => {
+				let programs: Vec<_> = iter::once(primary)
+					.chain(secondaries)
+					.map(String::as_str)
+					.collect();
+				// Large outputs are spilled, so they are compared as they are read back.
+				let spilling = |program: &str| match persistent {
+					Some(persistent) if persistent.program == program => persistent
+						.run(*languge, limits, stdin)
+						.running(component(program), program),
+					_ => consumed(program, languge.run_spilling(program, limits, input)),
+				};
+				let mut runs = run_concurrently(&programs, spilling)?;
+				let equal = match &runs[..] {
+					[primary_run, secondary_run]
+						if verifier.is_none()
+							&& (primary_run.spilled.is_some()
+								|| secondary_run.spilled.is_some())
+							&& primary_run.exit.success()
+							&& secondary_run.exit.success() =>
+					{
+						let (primary, secondary) = (primary_run.reader()?, secondary_run.reader()?);
+						comparison.equal_streams(primary, secondary)?
+					}
+					_ => None,
+				};
+				if let Some(true) = equal {
+					let primary_run = runs.swap_remove(0);
+					return Ok(Outcome::new(Status::Ok, primary_run.stderr));
+				}
+				for run in &mut runs {
+					run.load()?;
+				}
+				let mut runs = runs.into_iter();
+				let primary_run = runs.next().expect("the primary is run");
+				if let Some(status) = primary_run.exit.failure(primary, Status::PrimaryFailed) {
+					return Ok(Outcome {
+						output: Some(primary_run.stdout),
+						..Outcome::new(status, primary_run.stderr)
+					});
+				}
+				let mut outputs = vec![(primary.as_str(), primary_run.stdout)];
+				for (secondary, run) in secondaries.iter().zip(runs) {
+					let failed = Status::SecondaryFailed {
+						program: secondary.clone(),
+					};
+					if let Some(status) = run.exit.failure(secondary, failed) {
+						return Ok(Outcome {
+							output: Some(outputs.swap_remove(0).1),
+							expected: Some(run.stdout),
+							..Outcome::new(status, run.stderr)
+						});
+					}
+					outputs.push((secondary, run.stdout));
+				}
+				let status = match verifier {
+					Some(verifier) => {
+						let answer = outputs.get(1).map(|(_, answer)| &answer[..]);
+						verifier.judge(limits, stdin, &outputs[0].1, answer)?
+					}
+					None => Status::Ok,
+				};
+				let status = match status {
+					Status::Ok => agreement(comparison, &outputs),
+					status => status,
+				};
+				let mut outputs = outputs.into_iter().map(|(_, output)| output);
+				Ok(Outcome {
+					output: outputs.next(),
+					expected: outputs.next(),
+					..Outcome::new(status, primary_run.stderr)
+				})
+			}
+			Runner::Interactive {
+				problem,
+				interactor,
+				secondaries,
+				comparison,
+			} => {
+				let (outcome, answer) =
+					languge.interact(problem, Component::Solution, interactor, limits, test)?;
+				if outcome.status.failed() {
+					return Ok(outcome);
+				}
+				let mut answers = vec![(problem.as_str(), answer)];
+				for secondary in secondaries {
+					let (secondary_outcome, answer) = languge.interact(
+						secondary,
+						Component::Comparator,
+						interactor,
+						limits,
+						test,
+					)?;
+					if secondary_outcome.status.failed() {
+						return Ok(Outcome {
+							status: Status::Secondary {
+								program: secondary.clone(),
+								status: Box::new(secondary_outcome.status),
+							},
+							output: Some(answers.swap_remove(0).1),
+							..secondary_outcome
+						});
+					}
+					answers.push((secondary, answer));
+				}
+				let status = agreement(comparison, &answers);
+				let mut answers = answers.into_iter().map(|(_, answer)| answer);
+				Ok(Outcome {
+					status,
+					output: answers.next(),
+					expected: answers.next(),
+					..outcome
+				})
+			}
+			Runner::Verify { problem, verifier } => {
+				let run = solution(problem)?;
+				let status = if run.exit.success() {
+					verifier.judge(limits, stdin, &run.stdout, test.answer.as_deref())?
+				} else {
+					Status::Ok
+				};
+				Ok(run.outcome(problem, Status::Failed, |_| status))
+			}
+			Runner::Checker {
+				primary,
+				reference,
+				checker,
+			} => {
+				let primary_run = solution(primary)?;
+				let reference_run = solution(reference)?;
+				let failed = Status::SecondaryFailed {
+					program: reference.clone(),
+				};
+				let (status, stderr) = if let Some(status) =
+					primary_run.exit.failure(primary, Status::PrimaryFailed)
+				{
+					(status, primary_run.stderr)
+				} else if let Some(status) = reference_run.exit.failure(reference, failed) {
+					(status, reference_run.stderr)
+				} else {
+					let input = TempFile::new("input", stdin)?;
+					let output = TempFile::new("output", &primary_run.stdout)?;
+					let answer = TempFile::new("answer", &reference_run.stdout)?;
+					let arguments =
+						[input.path(), output.path(), answer.path()].map(Path::as_os_str);
+					let run = languge
+						.run_with_arguments(checker, &arguments, limits, &[])
+						.running(Component::Checker, checker)?;
+					let status = testlib::status(checker, &run, Status::CheckerFailed);
+					(status, primary_run.stderr)
+				};
+				Ok(Outcome {
+					output: Some(primary_run.stdout),
+					expected: Some(reference_run.stdout),
+					..Outcome::new(status, stderr)
+				})
+			}
+			Runner::Answer {
+				problem,
+				comparison,
+			} => {
+				let run = solution(problem)?;
+				let outcome = run.outcome(problem, Status::Failed, |stdout| {
+					// Tests read from disk without an answer can only be checked for crashes.
+					match &test.answer {
+						Some(answer) if !comparison.equal(stdout, answer) => {
+							Status::DifferentOutputs
+						}
+						_ => Status::Ok,
+					}
+				});
+				Ok(Outcome {
+					expected: test.answer.clone(),
+					..outcome
+				})
+			}
+			Runner::SelfCheck {
+				problem,
+				comparison,
+			} => {
+				let first = solution(problem)?;
+				if first.exit.failure(problem, Status::Failed).is_some() {
+					return Ok(first.outcome(problem, Status::Failed, |_| Status::Ok));
+				}
+				let second = solution(problem)?;
+				let outcome = second.outcome(problem, Status::Failed, |stdout| {
+					if comparison.equal(&first.stdout, stdout) {
+						Status::Ok
+					} else {
+						Status::Nondeterministic
+					}
+				});
+				Ok(Outcome {
+					output: Some(first.stdout),
+					expected: outcome.output,
+					..outcome
+				})
+			}
+		}
+	}
+}
+
+struct Failure {
+	outcome: Outcome,
+	/// How long every program ran on the test.
+	timings: Vec<(String, Usage)>,
+	/// Absent for saved inputs and mutated inputs, which no seed regenerates.
+	seed: Option<u64>,
+	/// The seed of the session and the iteration of it the seed was derived for, absent for
+	/// tests regenerated from their own seed with --replay-seed.
+	iteration: Option<(u64, u64)>,
+	/// Whether the input was generated by mutating another input.
+	mutated: bool,
+	/// Whether the input was enumerated with --exhaustive-upto.
+	enumerated: bool,
+	/// The size the test was generated at, if the session has one.
+	size: Option<i64>,
+	test: Test,
+	choices: Vec<u64>,
+	minimized: Option<Test>,
+}
+
+impl Failure {
+	/// A failure of a test which was read from disk rather than generated.
+	fn saved(outcome: Outcome, timings: Vec<(String, Usage)>, test: Test) -> Failure {
+		Failure {
+			outcome,
+			timings,
+			seed: None,
+			iteration: None,
+			mutated: false,
+			enumerated: false,
+			size: None,
+			test,
+			choices: Vec::new(),
+			minimized: None,
+		}
+	}
+
+	/// Whether the input was generated by the session rather than read from a file, which it
+	/// already exists as.
+	fn generated(&self) -> bool {
+		self.seed.is_some() || self.mutated || self.enumerated
+	}
+
+	/// Shrinks the failing input while it keeps failing the same way.
+	fn minimize(&mut self, session: &Session) -> Result<(), Error> {
+		if self.choices.is_empty() {
+			return Ok(());
+		}
+		let mut heartbeat = Heartbeat::new(!verbosity::quiet());
+		if !verbosity::quiet() {
+			eprint!("\nMinimizing");
+		}
+		let minimized = shrink::shrink(self.choices.clone(), self.test.clone(), |candidate| {
+			let mut choices = Choices::replay(candidate).sized(self.size);
+			let Ok(test) = session.generator().generate(&mut choices, Vec::new()) else {
+				return Ok::<_, Error>(None);
+			};
+			if interrupt::interrupted() {
+				return Ok(None);
+			}
+			heartbeat.beat();
+			// Smaller inputs the validators reject are not counterexamples.
+			if session
+				.validators
+				.validate(&session.limits, &test.input)?
+				.is_some()
+			{
+				return Ok(None);
+			}
+			let status = session.run(&test)?.0.status;
+			Ok(self
+				.outcome
+				.status
+				.same_kind(&status)
+				.then(|| (choices.into_recorded(), test)))
+		})?;
+		self.minimized = Some(minimized);
+		Ok(())
+	}
+
+	/// Prints the transcript of an interactive test, coloured by the party writing.
+	fn print_transcript(&self) {
+		for (party, data) in &self.outcome.transcript {
+			let color = match party {
+				Party::Interactor => Color::Red,
+				Party::Interactee => Color::Blue,
+			};
+			eprint!(
+				"{}",
+				style::paint(color.normal(), &String::from_utf8_lossy(data))
+			);
+		}
+	}
+
+	/// The transcript of an interactive test with every line prefixed by the party writing it.
+	fn transcript(&self) -> String {
+		let mut transcript = String::new();
+		let mut previous = None;
+		for (party, data) in &self.outcome.transcript {
+			let prefix = match party {
+				Party::Interactor => "solution> ",
+				Party::Interactee => "interactor> ",
+			};
+			for line in String::from_utf8_lossy(data).split_inclusive('\n') {
+				if previous != Some(prefix) || transcript.ends_with('\n') {
+					if !transcript.is_empty() && !transcript.ends_with('\n') {
+						transcript.push('\n');
+					}
+					transcript.push_str(prefix);
+				}
+				transcript.push_str(line);
+				previous = Some(prefix);
+			}
+		}
+		transcript
+	}
+
+	/// Saves the test to `{stem}.in` and what the programs wrote next to it, returning the paths
+	/// of the saved files.
+	fn save(&self, stem: &str) -> io::Result<Artifacts> {
+		let mut saved = vec![("input", format!("{stem}.in"))];
+		self.test.save(stem)?;
+		let mut write = |name, extension: &str, contents: &[u8]| {
+			let path = format!("{stem}.{extension}");
+			std::fs::write(&path, contents)?;
+			saved.push((name, path));
+			io::Result::Ok(())
+		};
+		if !self.outcome.transcript.is_empty() {
+			write("transcript", "transcript", self.transcript().as_bytes())?;
+		}
+		if !self.outcome.stderr.is_empty() {
+			write("stderr", "err", &self.outcome.stderr)?;
+		}
+		if let Some(output) = &self.outcome.output {
+			write("output", "out", output)?;
+		}
+		if let Some(expected) = &self.outcome.expected {
+			write("expected", "expected", expected)?;
+		}
+		if let Some(seed) = self.seed {
+			write("seed", "seed", self.seed_file(seed).as_bytes())?;
+		}
+		if let Some(minimized) = &self.minimized {
+			minimized.save(&format!("{stem}.min"))?;
+			saved.push(("minimized", format!("{stem}.min.in")));
+		}
+		Ok(saved)
+	}
+
+	/// Where the test came from and the arguments regenerating it, for sharing it without the
+	/// input.
+	fn seed_file(&self, seed: u64) -> String {
+		let mut file = format!("seed {seed}\n");
+		if let Some((session, iteration)) = self.iteration {
+			file.push_str(&format!(
+				"iteration {iteration} of session seed {session}\n"
+			));
+		}
+		let mut arguments = format!("--replay-seed {seed}");
+		if let Some(size) = self.size {
+			file.push_str(&format!("size {size}\n"));
+			arguments.push_str(&format!(" --size {size}"));
+		}
+		file.push_str(&format!("regenerate with {arguments}\n"));
+		file
+	}
+
+	/// Reports the failure and saves the test to `{stem}.in`, returning the saved files.
+	fn report(&self, stem: &str, comparison: &Comparison) -> io::Result<Artifacts> {
+		let saved = self.save(stem)?;
+		self.outcome.status.report();
+		if verbosity::quiet() {
+			eprintln!();
+			for (_, path) in &saved {
+				eprintln!("{path}");
+			}
+			return Ok(saved);
+		}
+		match self.seed {
+			Some(seed) => match self.size {
+				Some(size) => eprintln!(
+					"\nSeed {seed} at size {size}, saved to {stem}.seed (regenerate with \
+					 --replay-seed {seed} --size {size})"
+				),
+				None => eprintln!(
+					"\nSeed {seed}, saved to {stem}.seed (regenerate with --replay-seed {seed})"
+				),
+			},
+			None if self.mutated => {
+				eprintln!("\nMutated from another input (rerun with --input)")
+			}
+			None if self.enumerated => {
+				eprintln!("\nEnumerated by --exhaustive-upto (rerun with --input)")
+			}
+			None => eprintln!(),
+		}
+		// The status already says so when it is the solution alone.
+		let reported = matches!(self.outcome.status, Status::Unconsumed { .. })
+			&& self.outcome.unconsumed.len() == 1;
+		if !self.outcome.unconsumed.is_empty() && !reported {
+			let programs = self.outcome.unconsumed.join(", ");
+			eprintln!("{programs} exited without reading all of the input");
+		}
+		style::section(&format!("INPUT, saved to {stem}.in"));
+		print_block(&self.test.input, &format!("{stem}.in"))?;
+		if !self.outcome.transcript.is_empty() {
+			style::section(&format!("TRANSCRIPT, saved to {stem}.transcript"));
+			self.print_transcript();
+		}
+		if !self.outcome.stderr.is_empty() {
+			style::section(&format!("STDERR, saved to {stem}.err"));
+			print_block(&self.outcome.stderr, &format!("{stem}.err"))?;
+		}
+		style::section("RESOURCE USAGE");
+		for (program, usage) in &self.timings {
+			eprintln!("  {program}: {}", usage.describe());
+		}
+		let divergence = match (
+			&self.outcome.status,
+			&self.outcome.output,
+			&self.outcome.expected,
+		) {
+			(
+				Status::DifferentOutputs | Status::Disagreement { .. } | Status::Nondeterministic,
+				Some(output),
+				Some(expected),
+			) => comparison.divergence(output, expected),
+			_ => None,
+		};
+		if let Some(output) = &self.outcome.output {
+			style::section(&format!("OUTPUT, saved to {stem}.out"));
+			if let Some(divergence) = &divergence {
+				eprintln!("{}", divergence.describe());
+				print_context(output, divergence.line, style::failed);
+			}
+		}
+		if let Some(expected) = &self.outcome.expected {
+			style::section(&format!("EXPECTED, saved to {stem}.expected"));
+			if let Some(divergence) = &divergence {
+				print_context(expected, divergence.line, style::passed);
+			}
+		}
+		if let Some(minimized) = &self.minimized {
+			style::section(&format!("MINIMIZED INPUT, saved to {stem}.min.in"));
+			print_block(&minimized.input, &format!("{stem}.min.in"))?;
+		}
+		Ok(saved)
+	}
+}
+
+/// Lines printed from the start and from the end of a long section.
+const PREVIEW_LINES: usize = 20;
+
+/// Bytes printed of a long line.
+const PREVIEW_LINE_BYTES: usize = 200;
+
+/// Prints the contents of a section, which is saved in full to `path`, ending it with a newline.
+/// Only the first and last lines are printed of a long section, and only the start of a long line.
+fn print_block(data: &[u8], path: &str) -> io::Result<()> {
+	let lines: Vec<&[u8]> = data.split_inclusive(|&b| b == b'\n').collect();
+	let omitted = lines.len().saturating_sub(2 * PREVIEW_LINES);
+	let mut stderr = std::io::stderr();
+	for (i, line) in lines.iter().enumerate() {
+		if omitted > 0 && i == PREVIEW_LINES {
+			let marker = format!(
+				"… {} lines omitted (full contents in {path})",
+				style::separate_thousands(omitted)
+			);
+			writeln!(stderr, "{}", style::dimmed(&marker)).ignore_broken_pipe()?;
+		}
+		if omitted > 0 && (PREVIEW_LINES..PREVIEW_LINES + omitted).contains(&i) {
+			continue;
+		}
+		let text = line.strip_suffix(b"\n").unwrap_or(line);
+		if text.len() > PREVIEW_LINE_BYTES {
+			stderr
+				.write_all(&text[..PREVIEW_LINE_BYTES])
+				.ignore_broken_pipe()?;
+			let marker = format!(" … {} more bytes", text.len() - PREVIEW_LINE_BYTES);
+			writeln!(stderr, "{}", style::dimmed(&marker)).ignore_broken_pipe()?;
+		} else {
+			stderr.write_all(text).ignore_broken_pipe()?;
+			writeln!(stderr).ignore_broken_pipe()?;
+		}
+	}
+	Ok(())
+}
+
+/// Lines of context printed around the first difference of two outputs.
+const CONTEXT_LINES: usize = 2;
+
+/// Prints the lines of the output around `line`, which counts from one and is highlighted.
+fn print_context(output: &[u8], line: usize, highlight: fn(&str) -> String) {
+	let first = line.saturating_sub(CONTEXT_LINES).max(1);
+	for (i, text) in compare::lines(output)
+		.map(String::from_utf8_lossy)
+		.enumerate()
+		.skip(first - 1)
+		.take(line + CONTEXT_LINES + 1 - first)
+	{
+		let number = style::dimmed(&format!("{:>6} |", i + 1));
+		if i + 1 == line {
+			eprintln!("{number} {}", highlight(&text));
+		} else {
+			eprintln!("{number} {text}");
+		}
+	}
+}
+
+/// The failures found so far, deduplicated by status and input.
+struct Failures {
+	capacity: usize,
+	seen: Mutex<HashSet<(Discriminant<Status>, u64)>>,
+	failures: Mutex<Vec<Failure>>,
+}
+
+impl Failures {
+	fn new(args: &args::Args) -> Failures {
+		Failures {
+			capacity: args.keep_going.map_or(1, NonZeroUsize::get),
+			seen: Mutex::new(HashSet::new()),
+			failures: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Records a failure unless it is a duplicate, returning true once enough failures are found.
+	fn record(&self, failure: Failure) -> bool {
+		let mut hasher = DefaultHasher::new();
+		failure.test.input.hash(&mut hasher);
+		let key = (mem::discriminant(&failure.outcome.status), hasher.finish());
+		if !self.seen.lock().expect("not poisoned").insert(key) {
+			return false;
+		}
+		let mut failures = self.failures.lock().expect("not poisoned");
+		if failures.len() < self.capacity {
+			failures.push(failure);
+		}
+		failures.len() >= self.capacity
+	}
+
+	fn is_empty(&self) -> bool {
+		self.failures.lock().expect("not poisoned").is_empty()
+	}
+
+	fn into_inner(self) -> Vec<Failure> {
+		self.failures.into_inner().expect("not poisoned")
+	}
+}
+
+/// Number of generated inputs remembered to skip their duplicates, which bounds the memory used.
+const REMEMBERED_INPUTS: usize = 1 << 20;
+
+/// The inputs generated so far by hash, so an input generated again is not run again. Small
+/// specifications generate the same few inputs over and over.
+struct Generated {
+	seen: Mutex<HashSet<u64>>,
+	duplicates: AtomicU64,
+}
+
+impl Generated {
+	fn new() -> Generated {
+		Generated {
+			seen: Mutex::new(HashSet::new()),
+			duplicates: AtomicU64::new(0),
+		}
+	}
+
+	/// Records the input of a test, returning false if it was generated before.
+	fn record(&self, test: &Test) -> bool {
+		let mut hasher = DefaultHasher::new();
+		test.input.hash(&mut hasher);
+		let hash = hasher.finish();
+		let mut seen = self.seen.lock().expect("not poisoned");
+		if seen.contains(&hash) {
+			self.duplicates.fetch_add(1, Ordering::Relaxed);
+			return false;
+		}
+		if seen.len() < REMEMBERED_INPUTS {
+			seen.insert(hash);
+		}
+		true
+	}
+
+	/// Number of generated inputs which were skipped as duplicates.
+	fn duplicates(&self) -> u64 {
+		self.duplicates.load(Ordering::Relaxed)
+	}
+}
+
+/// The generated tests in which a program exited without reading all of the input, which passing
+/// tests would otherwise hide.
+struct Unconsumed {
+	tests: AtomicU64,
+	/// The programs which did, in the order they first did.
+	programs: Mutex<Vec<String>>,
+}
+
+impl Unconsumed {
+	fn new() -> Unconsumed {
+		Unconsumed {
+			tests: AtomicU64::new(0),
+			programs: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Records the programs which did not read all of the input of a test, returning whether it
+	/// is the first such test.
+	fn record(&self, programs: &[String]) -> bool {
+		let mut known = self.programs.lock().expect("not poisoned");
+		for program in programs {
+			if !known.contains(program) {
+				known.push(program.clone());
+			}
+		}
+		self.tests.fetch_add(1, Ordering::Relaxed) == 0
+	}
+}
+
+struct Session {
+	args: args::Args,
+	/// Absent when running a directory of inputs.
+	generator: Option<generator::Generator>,
+	runner: Runner,
+	/// The problem package given with --package.
+	package: Option<Package>,
+	/// The validators of the generated inputs.
+	validators: Validators,
+	coverage: Option<Coverage>,
+	/// The inputs to mutate with --mutate.
+	mutations: Option<Pool>,
+	schedule: Option<Schedule>,
+	/// The solution kept running between tests, with --persistent.
+	persistent: Option<Persistent>,
+	limits: Limits,
+	budget: Budget,
+	statistics: Statistics,
+	progress: Progress,
+	dashboard: Option<Dashboard>,
+	log: Option<InputLog>,
+	events: Option<EventLog>,
+	generated: Generated,
+	unconsumed: Unconsumed,
+	/// Whether the solution leaving input unread fails the test.
+	require_consume: bool,
+	/// Inputs of tests which have been run, reused for generating new ones.
+	buffers: Mutex<Vec<Vec<u8>>>,
+	/// The counterexamples of the previous session with --watch, run before the corpus.
+	replay: Vec<PathBuf>,
+	/// The path every saved file starts with.
+	stem: String,
+	/// When the programs had been built and testing started.
+	started: Instant,
+	stop: AtomicBool,
+	/// Whether the input of a test stopped by Ctrl-C was saved to `{stem}.last.in`.
+	saved_last: AtomicBool,
+	seed: u64,
+	/// Saves the state of the session for --resume, when run from the command line.
+	checkpoint: Option<Checkpoint>,
+}
+
+impl Session {
+	fn new(args: args::Args) -> Result<Session, Error> {
+		Self::resume(args, None)
+	}
+
+	/// Starts the session, continuing from the saved state of the session it resumes.
+	fn resume(mut args: args::Args, resumed: Option<&Saved>) -> Result<Session, Error> {
+		let package = Package::load(&mut args)?;
+		args.language
+			.build(&args.name)
+			.building(Component::Solution, &args.name)?;
+		let generator = match args.inputs {
+			Some(_) => None,
+			None => Some(generator::Generator::new(&args)?),
+		};
+		let runner = Runner::new(&args, package.as_ref())?;
+		let validators = Validators::new(&args, package.as_ref())?;
+		// Interrupted after the last build, so nothing is run.
+		interrupt::check()?;
+		let resumed_mutations = resumed.and_then(|saved| saved.mutations.clone());
+		let mutations = match (&generator, &args.corpus, resumed_mutations) {
+			_ if !args.mutate => None,
+			// The inputs of the corpus are among those saved.
+			(_, _, Some(inputs)) => Some(Pool::new(inputs)),
+			(Some(generator), Some(dir), None) => {
+				let mut inputs = Vec::new();
+				for path in corpus::inputs(dir)? {
+					inputs.extend(generator.choices(&std::fs::read(path)?, args.size));
+				}
+				Some(Pool::new(inputs))
+			}
+			_ => Some(Pool::new(Vec::new())),
+		};
+		let dashboard = match args.tui {
+			true => Dashboard::new(args.jobs.get()),
+			false => None,
+		};
+		let coverage = Coverage::new(&args)?;
+		let mut budget = Budget::new(&args);
+		let statistics = Statistics::new();
+		if let Some(saved) = resumed {
+			budget.resume(
+				saved.first,
+				&saved.finished,
+				saved.duplicates,
+				saved.elapsed,
+			);
+			statistics.resume(saved.tests, saved.slowest);
+			if let (Some(coverage), Some((covered, kept))) = (&coverage, &saved.coverage) {
+				coverage.resume(covered, kept.clone());
+			}
+		}
+		let require_consume = args.require_consume.unwrap_or(runner.compares());
+		Ok(Session {
+			generator,
+			runner,
+			package,
+			validators,
+			coverage,
+			mutations,
+			schedule: Schedule::new(&args),
+			persistent: Persistent::new(&args),
+			limits: Limits::new(&args),
+			budget,
+			statistics,
+			progress: Progress::new(
+				!verbosity::quiet() && !verbosity::verbose() && dashboard.is_none(),
+			),
+			dashboard,
+			log: match &args.log_inputs {
+				Some(dir) => Some(InputLog::new(dir, args.log_max_files, args.log_max_size)?),
+				None => None,
+			},
+			events: match &args.event_log {
+				Some(path) => Some(EventLog::new(path)?),
+				None => None,
+			},
+			generated: Generated::new(),
+			unconsumed: Unconsumed::new(),
+			require_consume,
+			buffers: Mutex::new(Vec::new()),
+			replay: Vec::new(),
+			stem: output::stem(&args)?,
+			started: Instant::now(),
+			stop: AtomicBool::new(false),
+			saved_last: AtomicBool::new(false),
+			seed: match resumed {
+				Some(saved) => saved.seed,
+				None => args.seed.unwrap_or_else(|| fastrand::u64(..)),
+			},
+			checkpoint: None,
+			args,
+		})
+	}
+
+	/// Runs the test, returning its outcome and how long every program ran.
+	fn run(&self, test: &Test) -> Result<(Outcome, Vec<(String, Usage)>), Error> {
+		self.run_with(&self.runner, test)
+	}
+
+	/// Runs the test like [`Self::run`] with another runner.
+	fn run_with(
+		&self,
+		runner: &Runner,
+		test: &Test,
+	) -> Result<(Outcome, Vec<(String, Usage)>), Error> {
+		// Discard what was recorded by a previous test which failed with an error.
+		usage::take();
+		let mut outcome = runner.run(
+			&self.args.language,
+			&self.limits,
+			self.persistent.as_ref(),
+			test,
+		)?;
+		let timings = usage::take();
+		if !outcome.status.failed()
+			&& self.require_consume
+			&& outcome.unconsumed.contains(&self.args.name)
+		{
+			let program = self.args.name.clone();
+			outcome.status = Status::Unconsumed { program };
+		}
+		if !outcome.status.failed()
+			&& let Some(limit) = self.slow_limit(&timings)
+			&& let Some(wall) = Self::wall_time(&timings, &self.args.name)
+			&& wall > limit
+		{
+			outcome.status = Status::Slow { wall, limit };
+		}
+		Ok((outcome, timings))
+	}
+
+	fn wall_time(timings: &[(String, Usage)], program: &str) -> Option<Duration> {
+		timings
+			.iter()
+			.find(|(name, _)| name == program)
+			.map(|(_, usage)| usage.wall)
+	}
+
+	/// How long the solution may run before the run is treated as a failure, if at all.
+	fn slow_limit(&self, timings: &[(String, Usage)]) -> Option<Duration> {
+		let relative = self.args.fail_if_ratio.and_then(|ratio| {
+			let comparator = self.args.compare.first()?;
+			Some(Self::wall_time(timings, comparator)?.mul_f64(ratio))
+		});
+		[self.args.fail_if_slower_than, relative]
+			.into_iter()
+			.flatten()
+			.min()
+	}
+
+	/// Generates the test from the seed.
+	fn generate(&self, seed: u64) -> Result<(Choices, Test), Error> {
+		self.generate_from(Choices::random(seed).sized(self.size(seed)))
+	}
+
+	/// The size of the test with this seed, if the session has one.
+	fn size(&self, seed: u64) -> Option<i64> {
+		match &self.schedule {
+			Some(schedule) => Some(schedule.size(seed)),
+			None => self.args.size,
+		}
+	}
+
+	fn generate_from(&self, mut choices: Choices) -> Result<(Choices, Test), Error> {
+		let buffer = self.buffers.lock().expect("not poisoned").pop();
+		let test = self
+			.generator()
+			.generate(&mut choices, buffer.unwrap_or_default())?;
+		Ok((choices, test))
+	}
+
+	/// Keeps the input of a test which is done with for generating another into.
+	fn recycle(&self, input: Vec<u8>) {
+		let mut buffers = self.buffers.lock().expect("not poisoned");
+		if buffers.len() < self.args.jobs.get() * (PIPELINE_DEPTH + 1) {
+			buffers.push(input);
+		}
+	}
+
+	/// Generates and runs a single test, returning the failure if it failed.
+	fn run_test(&self, seed: u64) -> Result<Option<Failure>, Error> {
+		let (choices, test) = self.generate(seed)?;
+		self.run_generated(None, Some(seed), choices, test)
+	}
+
+	/// Runs a generated test, returning the failure if it failed. The iteration of the session is
+	/// absent if the test was regenerated from its seed, and the seed if it was generated by
+	/// mutation.
+	fn run_generated(
+		&self,
+		iteration: Option<u64>,
+		seed: Option<u64>,
+		choices: Choices,
+		test: Test,
+	) -> Result<Option<Failure>, Error> {
+		if let Some(message) = self.validators.validate(&self.limits, &test.input)? {
+			// Only the first rejected input is saved, which is enough to fix the generator.
+			if self.validators.reject() || self.validators.fail() {
+				test.save(&format!("{}.invalid", self.stem))?;
+			}
+			if !self.validators.fail() {
+				return Ok(None);
+			}
+			return Err(io::Error::other(format!(
+				"The input validator rejected the input generated from {}, saved to {}.invalid.in: \
+				 {message}",
+				origin(seed),
+				self.stem
+			))
+			.into());
+		}
+		coverage::take();
+		let (outcome, timings) = self.run(&test)?;
+		if let Some(coverage) = &self.coverage {
+			coverage.record(&coverage::take(), choices.recorded());
+		}
+		if interrupt::interrupted() {
+			// The programs were killed, so the outcome is meaningless.
+			test.save(&format!("{}.last", self.stem))?;
+			self.saved_last.store(true, Ordering::Relaxed);
+			return Ok(None);
+		}
+		// Only the first is saved, which is enough to see which format the program read.
+		if !outcome.unconsumed.is_empty() && self.unconsumed.record(&outcome.unconsumed) {
+			test.save(&format!("{}.unconsumed", self.stem))?;
+		}
+		if let Some(wall) = Self::wall_time(&timings, &self.args.name) {
+			self.statistics.record(wall, seed);
+			if let (Some(schedule), Some(size)) = (&self.schedule, choices.size()) {
+				schedule.record(size, wall);
+			}
+		}
+		if let Some(log) = &self.log {
+			log.record(seed, &test, &outcome.status.message())?;
+		}
+		if let Some(events) = &self.events {
+			let duration = Self::wall_time(&timings, &self.args.name);
+			events.record(&Json::Object(vec![
+				("seed", seed.map(|seed| seed.to_string()).into()),
+				("status", outcome.status.name().into()),
+				("message", outcome.status.message().into()),
+				(
+					"duration_seconds",
+					duration.map(|duration| duration.as_secs_f64()).into(),
+				),
+				("input_bytes", test.input.len().into()),
+			]))?;
+		}
+		if verbosity::verbose() {
+			let timings: Vec<_> = timings
+				.iter()
+				.map(|(program, usage)| format!("{program} {:.1?}", usage.wall))
+				.collect();
+			eprintln!(
+				"{}: {} ({})",
+				capitalize(&origin(seed)),
+				outcome.status.message(),
+				timings.join(", ")
+			);
+		}
+		if !outcome.status.failed() {
+			self.recycle(test.input);
+			return Ok(None);
+		}
+		if !self.reproduces(&outcome.status, &test)? {
+			return Ok(None);
+		}
+		Ok(Some(Failure {
+			outcome,
+			timings,
+			seed,
+			iteration: seed.and(iteration).map(|iteration| (self.seed, iteration)),
+			mutated: seed.is_none(),
+			enumerated: false,
+			size: choices.size(),
+			test,
+			choices: choices.into_recorded(),
+			minimized: None,
+		}))
+	}
+
+	/// Reruns a failing test as many times as requested, reporting it as flaky and returning false
+	/// unless it failed the same way every time.
+	fn reproduces(&self, status: &Status, test: &Test) -> Result<bool, Error> {
+		let mut reproduced = 0;
+		for _ in 0..self.args.recheck {
+			if self.run(test)?.0.status.same_kind(status) {
+				reproduced += 1;
+			}
+		}
+		if reproduced == self.args.recheck {
+			return Ok(true);
+		}
+		self.notify(format!(
+			"Flaky: {} reproduced in {reproduced} of {} reruns, saved to {}.flaky.in",
+			status.message(),
+			self.args.recheck,
+			self.stem
+		));
+		test.save(&format!("{}.flaky", self.stem))?;
+		Ok(false)
+	}
+
+	fn generator(&self) -> &generator::Generator {
+		self.generator
+			.as_ref()
+			.expect("tests are only generated without --inputs")
+	}
+
+	/// Runs every input of the corpus, returning whether all of them passed.
+	fn check_corpus(&self, failures: &Failures) -> Result<bool, Error> {
+		let Some(dir) = &self.args.corpus else {
+			return Ok(true);
+		};
+		self.run_inputs(&self.runner, &corpus::inputs(dir)?, failures)?;
+		Ok(failures.is_empty())
+	}
+
+	/// Runs every input of the specification whose numbers are within --exhaustive-upto of zero
+	/// until the time is up, returning whether all of them passed.
+	fn check_exhaustive(&self, failures: &Failures) -> Result<bool, Error> {
+		let Some(upto) = self.args.exhaustive_upto else {
+			return Ok(true);
+		};
+		let size = self.args.size.map(|size| size.min(upto));
+		let mut next = Some(Vec::new());
+		let mut tests = 0;
+		while let Some(choices) = next.take()
+			&& !self.stopped()
+			&& !self.budget.expired()
+		{
+			let choices = Choices::exhaustive(choices, upto).sized(size);
+			let (choices, test) = self.generate_from(choices)?;
+			next = choices.next_exhaustive();
+			// Shuffling the same values differently can give the same input.
+			if !self.generated.record(&test) {
+				self.recycle(test.input);
+				continue;
+			}
+			tests += 1;
+			if let Some(mut failure) = self.run_generated(None, None, choices, test)? {
+				(failure.mutated, failure.enumerated) = (false, true);
+				if failures.record(failure) {
+					break;
+				}
+			}
+			self.progress.update(&self.statistics)?;
+		}
+		if next.is_none() && failures.is_empty() && !verbosity::quiet() {
+			self.notify(format!(
+				"Exhaustive: all {} inputs with numbers up to {upto} passed",
+				style::separate_thousands(tests)
+			));
+		}
+		Ok(failures.is_empty())
+	}
+
+	/// Runs the counterexamples of the previous session, returning whether all of them passed.
+	fn check_replay(&self, failures: &Failures) -> Result<bool, Error> {
+		self.run_inputs(&self.runner, &self.replay, failures)?;
+		Ok(failures.is_empty())
+	}
+
+	/// Runs the solution on every sample and compares its output with the answer, returning
+	/// whether all of them passed. The output validator of a problem package judges the output
+	/// instead, as the answer need not be the only correct one.
+	fn check_samples(&self, failures: &Failures) -> Result<bool, Error> {
+		let Some(dir) = &self.args.samples else {
+			return Ok(true);
+		};
+		let problem = self.args.name.clone();
+		let runner = match self.package.as_ref().and_then(|p| p.verifier.clone()) {
+			Some(verifier) => Runner::Verify { problem, verifier },
+			None => Runner::Answer {
+				problem,
+				comparison: Comparison::new(&self.args),
+			},
+		};
+		self.run_inputs(&runner, &corpus::inputs(dir)?, failures)?;
+		Ok(failures.is_empty())
+	}
+
+	/// Runs the saved inputs, reporting the verdict of each.
+	fn run_inputs(
+		&self,
+		runner: &Runner,
+		paths: &[PathBuf],
+		failures: &Failures,
+	) -> Result<(), Error> {
+		for path in paths {
+			let test = Test::read(path)?;
+			let (outcome, timings) = self.run_with(runner, &test)?;
+			let message = outcome.status.message();
+			let message = if outcome.status.failed() {
+				style::failed(&message)
+			} else {
+				style::passed(&message)
+			};
+			if !verbosity::quiet() {
+				eprintln!("{}: {message}", path.display());
+			}
+			if outcome.status.failed() {
+				failures.record(Failure::saved(outcome, timings, test));
+			}
+		}
+		Ok(())
+	}
+
+	/// Prints the totals of the session, which help tuning the sizes of the generated tests.
+	fn print_summary(&self) {
+		let (tests, slowest) = self.statistics.slowest();
+		let elapsed = match &self.checkpoint {
+			Some(checkpoint) => checkpoint.elapsed(self.started),
+			None => self.started.elapsed(),
+		};
+		style::section("SUMMARY");
+		let duplicates = match self.generated.duplicates() {
+			0 => String::new(),
+			duplicates => format!(
+				", {} duplicate inputs skipped",
+				style::separate_thousands(duplicates as usize)
+			),
+		};
+		eprintln!(
+			"Tests: {} in {elapsed:.1?} ({:.0}/s){duplicates}",
+			style::separate_thousands(tests),
+			tests as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+		);
+		let builds: Vec<_> = usage::builds()
+			.into_iter()
+			.map(|(program, elapsed)| format!("{program} {elapsed:.1?}"))
+			.collect();
+		if !builds.is_empty() {
+			eprintln!("Builds: {}", builds.join(", "));
+		}
+		if self.budget.exhausted() {
+			eprintln!(
+				"Ran out of inputs: the last {} generated were all duplicates",
+				style::separate_thousands(budget::MAX_CONSECUTIVE_DUPLICATES as usize)
+			);
+		}
+		if let Some((wall, seed)) = slowest {
+			eprintln!("Slowest test: {wall:.1?} ({})", origin(seed));
+		}
+		match self.validators.rejected() {
+			0 => {}
+			rejected => eprintln!(
+				"Invalid inputs: {} skipped, the first saved to {}.invalid.in",
+				style::separate_thousands(rejected as usize),
+				self.stem
+			),
+		}
+		match self.unconsumed.tests.load(Ordering::Relaxed) {
+			0 => {}
+			tests => eprintln!(
+				"Unconsumed inputs: {} tests in which {} exited without reading all of the input, \
+				 the first saved to {}.unconsumed.in",
+				style::separate_thousands(tests as usize),
+				self.unconsumed
+					.programs
+					.lock()
+					.expect("not poisoned")
+					.join(", "),
+				self.stem
+			),
+		}
+		if let Some(coverage) = &self.coverage {
+			eprintln!(
+				"Coverage: {} blocks, {} inputs kept for covering new ones",
+				coverage.blocks(),
+				coverage.kept.len()
+			);
+		}
+		if let Some(schedule) = &self.schedule {
+			let (reached, largest) = schedule.reached();
+			eprintln!(
+				"Size: reached {} of {}",
+				style::separate_thousands(reached as usize),
+				style::separate_thousands(largest as usize)
+			);
+		}
+		eprintln!("Seed {}", self.seed);
+	}
+
+	/// Runs the tests of the session, returning the failures found after minimizing them.
+	fn find_failures(&self) -> Result<Vec<Failure>, Error> {
+		let failures = Failures::new(&self.args);
+		if let Some(input) = &self.args.input {
+			let test = Test::read(input)?;
+			let (outcome, timings) = self.run(&test)?;
+			if outcome.status.failed() {
+				failures.record(Failure::saved(outcome, timings, test));
+			}
+		} else if let Some(dir) = &self.args.inputs {
+			self.run_inputs(&self.runner, &corpus::files(dir)?, &failures)?;
+		} else if let Some(seed) = self.args.replay_seed {
+			if let Some(failure) = self.run_test(seed)? {
+				failures.record(failure);
+			}
+		} else if self.check_samples(&failures)?
+			&& self.check_replay(&failures)?
+			&& self.check_corpus(&failures)?
+			&& self.check_exhaustive(&failures)?
+		{
+			thread::scope(|scope| {
+				let (session, failures) = (self, &failures);
+				let (done, finished) = mpsc::channel();
+				let dashboard = session.dashboard.as_ref().map(|dashboard| {
+					scope.spawn(move || dashboard.run(finished, &session.statistics))
+				});
+				let workers: Vec<_> = (0..session.args.jobs.get())
+					.map(|worker| {
+						scope.spawn(move || {
+							let result = session.fuzz(worker, failures);
+							if result.is_err() {
+								session.stop.store(true, Ordering::Relaxed);
+							}
+							result
+						})
+					})
+					.collect();
+				let result = workers
+					.into_iter()
+					.try_for_each(|worker| worker.join().expect("does not panic"));
+				drop(done);
+				if let Some(dashboard) = dashboard {
+					dashboard.join().expect("does not panic")?;
+				}
+				result
+			})?;
+			self.progress.finish(&self.statistics)?;
+		}
+		let mut failures = failures.into_inner();
+		if !self.args.no_minimize {
+			for failure in &mut failures {
+				failure.minimize(self)?;
+			}
+		}
+		Ok(failures)
+	}
+
+	/// Reports something which happened during fuzzing, in the event log of the dashboard if it is
+	/// shown.
+	fn notify(&self, message: String) {
+		match &self.dashboard {
+			Some(dashboard) => dashboard.event(message),
+			None => eprintln!("\n{message}"),
+		}
+	}
+
+	/// The choices of the test with this seed if it should mutate an input rather than be
+	/// generated from scratch, which half of the tests do once there are inputs to mutate.
+	fn mutation(&self, seed: u64) -> Option<Choices> {
+		let pools: Vec<_> = [
+			self.coverage.as_ref().map(|c| &c.kept),
+			self.mutations.as_ref(),
+		]
+		.into_iter()
+		.flatten()
+		.filter(|pool| !pool.is_empty())
+		.collect();
+		let choices = mutate::mutation(&pools, seed)?;
+		Some(choices.sized(self.size(seed)))
+	}
+
+	/// Whether fuzzing should stop, because another worker found enough failures or the session
+	/// was interrupted.
+	fn stopped(&self) -> bool {
+		self.stop.load(Ordering::Relaxed) || interrupt::interrupted()
+	}
+
+	/// Runs tests until the budget is exhausted or another worker stops the session. The tests
+	/// are generated on another thread while the previous ones run.
+	fn fuzz(&self, worker: usize, failures: &Failures) -> Result<(), Error> {
+		thread::scope(|scope| {
+			let (queue, tests) = mpsc::sync_channel(PIPELINE_DEPTH);
+			scope.spawn(move || {
+				while !self.stopped()
+					&& let Some(index) = self.budget.next()
+				{
+					let seed = choices::test_seed(self.seed, index);
+					let (seed, test) = match self.mutation(seed) {
+						Some(choices) => (None, self.generate_from(choices)),
+						None => (Some(seed), self.generate(seed)),
+					};
+					if let Ok((choices, generated)) = &test
+						&& !self.generated.record(generated)
+					{
+						if let (Some(schedule), Some(size)) = (&self.schedule, choices.size()) {
+							schedule.duplicate(size);
+						}
+						if let Ok((_, test)) = test {
+							self.recycle(test.input);
+						}
+						self.budget.duplicate(index);
+						continue;
+					}
+					self.budget.distinct();
+					// The worker stops receiving once it stops, which ends generation.
+					if queue.send((index, seed, test)).is_err() {
+						break;
+					}
+				}
+			});
+			for (index, seed, test) in tests {
+				if self.stopped() {
+					break;
+				}
+				let (choices, test) = test?;
+				self.run_pipelined(worker, index, seed, choices, test, failures)?;
+				// The test of an interrupted run was killed, so it is run again when resumed.
+				if !interrupt::interrupted() {
+					self.budget.finish(index);
+				}
+				if let Some(checkpoint) = &self.checkpoint {
+					checkpoint.update(self)?;
+				}
+			}
+			if let Some(dashboard) = &self.dashboard {
+				dashboard.stopped(worker);
+			}
+			Ok(())
+		})
+	}
+
+	/// Runs a generated test of the worker, recording the failure if it failed.
+	fn run_pipelined(
+		&self,
+		worker: usize,
+		iteration: u64,
+		seed: Option<u64>,
+		choices: Choices,
+		test: Test,
+		failures: &Failures,
+	) -> Result<(), Error> {
+		if let Some(dashboard) = &self.dashboard {
+			dashboard.started(worker, seed);
+		}
+		let failure = self.run_generated(Some(iteration), seed, choices, test)?;
+		if let Some(dashboard) = &self.dashboard {
+			dashboard.finished(worker);
+			if let Some(failure) = &failure {
+				let message = failure.outcome.status.message();
+				let origin = origin(seed);
+				dashboard.event(format!("worker {}: {message} ({origin})", worker + 1));
+			}
+		}
+		if let (Some(failure), Some(mutations)) = (&failure, &self.mutations) {
+			mutations.add(failure.choices.clone());
+		}
+		if let Some(failure) = failure
+			&& failures.record(failure)
+		{
+			self.stop.store(true, Ordering::Relaxed);
+		}
+		self.progress.update(&self.statistics)?;
+		Ok(())
+	}
+}
+
+/// Describes where a generated test came from, by the seed generating it or as a mutation.
+fn origin(seed: Option<u64>) -> String {
+	match seed {
+		Some(seed) => format!("seed {seed}"),
+		None => "mutated input".to_string(),
+	}
+}
+
+fn capitalize(s: &str) -> String {
+	let mut chars = s.chars();
+	chars
+		.next()
+		.map_or_else(String::new, |c| c.to_uppercase().chain(chars).collect())
+}
+
+/// Number of tests generated ahead of the one running, so generation overlaps running.
+const PIPELINE_DEPTH: usize = 2;
+
+/// Exit code when interrupted by Ctrl-C, as is conventional for SIGINT.
+const INTERRUPTED: u8 = 130;
+
+/// Runs cpfuzz as invoked from the command line, returning its exit code.
+pub fn cli(cli: args::Cli) -> Result<ExitCode, Error> {
+	let _cleanup = temp::Cleanup;
+	// Installed for every command before anything is built, as the programs they run do not
+	// receive Ctrl-C themselves.
+	interrupt::install();
+	match command(cli) {
+		// The error of a build or run stopped by Ctrl-C is the interrupt.
+		Err(_) if interrupt::interrupted() => {
+			eprintln!("\nInterrupted");
+			Ok(ExitCode::from(INTERRUPTED))
+		}
+		result => result,
+	}
+}
+
+/// Runs the command of the command line, returning the exit code of cpfuzz.
+fn command(cli: args::Cli) -> Result<ExitCode, Error> {
+	let mut args = match cli.command {
+		Some(args::Command::Run(args)) => *args,
+		Some(args::Command::Replay(replay)) => replayed(replay)?,
+		Some(args::Command::Diff(args)) => return diff::diff(&args),
+		Some(args::Command::Sample(mut args)) => {
+			prepare(&mut args)?;
+			return sample::sample(&args);
+		}
+		Some(args::Command::Init(args)) => return init::init(&args),
+		Some(args::Command::Bench(mut args)) => {
+			prepare(&mut args)?;
+			return bench::bench(&args);
+		}
+		Some(args::Command::Listen(args)) => return listen::listen(&args),
+		Some(args::Command::Export(args)) => return export::export(&args),
+		Some(args::Command::Completions(args)) => return completions::completions(&args),
+		Some(args::Command::SpecFromUrl(args)) => return statement::spec_from_url(&args),
+		Some(args::Command::Bundle(args)) => return bundle::bundle(&args),
+		None => cli.args.expect("required without a subcommand"),
+	};
+	prepare(&mut args)?;
+	if args.watch {
+		return watch::watch(args);
+	}
+	let resumed = match args.resume {
+		true => Some(resume::load()?),
+		false => None,
+	};
+	let mut session = Session::resume(args, resumed.as_ref())?;
+	session.checkpoint = Checkpoint::new(&session.args, resumed.as_ref());
+	if let Some(saved) = &resumed {
+		eprintln!(
+			"Resuming the session of seed {} after {} tests in {:.0?}",
+			saved.seed,
+			style::separate_thousands(saved.tests),
+			saved.elapsed
+		);
+	}
+	let failures = session.find_failures()?;
+	if let Some(checkpoint) = &session.checkpoint {
+		checkpoint.save(&session)?;
+	}
+	let (exit_code, _) = conclude(&session, &failures)?;
+	Ok(ExitCode::from(exit_code))
+}
+
+/// The arguments of `cpfuzz replay`, which runs the saved test with the arguments of its session:
+/// the fuzz.seed of a failure like --replay-seed at its size, a directory like --inputs and any
+/// other file like --input.
+fn replayed(replay: args::ReplayArgs) -> io::Result<args::Args> {
+	let mut args = *replay.args;
+	if args.input.is_some() || args.inputs.is_some() || args.replay_seed.is_some() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"replay runs the saved test it is given, not --input, --inputs or --replay-seed",
+		));
+	}
+	let saved = replay.saved;
+	if saved.is_dir() {
+		args.inputs = Some(saved);
+	} else if saved
+		.extension()
+		.is_some_and(|extension| extension == "seed")
+	{
+		let file = std::fs::read_to_string(&saved)?;
+		let value = |key: &str| {
+			file.lines()
+				.find_map(|line| line.strip_prefix(key)?.strip_prefix(' '))
+		};
+		let seed = value("seed").and_then(|seed| seed.parse().ok());
+		let Some(seed) = seed else {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("{} does not give a seed", saved.display()),
+			));
+		};
+		args.replay_seed = Some(seed);
+		if let Some(size) = value("size").and_then(|size| size.parse().ok()) {
+			args.size = Some(size);
+			args.adaptive_size = false;
+		}
+	} else {
+		args.input = Some(saved);
+	}
+	Ok(args)
+}
+
+/// Applies the profile and the settings of the report of a session, and checks its
+/// specification, the same for the command line and sessions set up with [`FuzzSession`].
+fn prepare(args: &mut args::Args) -> io::Result<()> {
+	style::init(args.color);
+	config::apply(args)?;
+	verbosity::init(args);
+	read_specification(args)
+}
+
+/// Reads a specification given as `-` from stdin, as if it was given inline, so it is read once
+/// however many sessions use it. Otherwise it is checked to exist.
+fn read_specification(args: &mut args::Args) -> io::Result<()> {
+	check_sources(args)?;
+	if args.specification.as_deref() != Some("-") {
+		return check_specification(args);
+	}
+	if args.generate || args.testlib_generator {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"a generator can not be read from stdin, only a specification",
+		));
+	}
+	args.spec_inline = Some(io::read_to_string(io::stdin())?);
+	args.specification = None;
+	Ok(())
+}
+
+/// Checks that the specifications of --spec exist, and the C++ sources of the generators of --gen,
+/// which may be given with or without `.cpp`.
+fn check_sources(args: &mut args::Args) -> io::Result<()> {
+	let invalid = |message: String| Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+	for source in &args.specifications {
+		if !Path::new(&source.path).is_file() {
+			return invalid(format!(
+				"the specification '{}' does not exist",
+				source.path
+			));
+		}
+	}
+	for source in &mut args.generators {
+		if let Some(stem) = source.path.strip_suffix(".cpp") {
+			source.path = stem.to_string();
+		}
+		let file = format!("{}.cpp", source.path);
+		if !Path::new(&file).is_file() {
+			return invalid(format!(
+				"the source of the generator, {file}, does not exist"
+			));
+		}
+	}
+	Ok(())
+}
+
+/// Checks that the specification exists, or with --generate the C++ source of the generator,
+/// which may be given with or without `.cpp`. The mistakes of mixing them up are told apart.
+fn check_specification(args: &mut args::Args) -> io::Result<()> {
+	let Some(specification) = &mut args.specification else {
+		return Ok(());
+	};
+	if args.testlib_generator {
+		return Ok(());
+	}
+	let invalid = |message: String| Err(io::Error::new(io::ErrorKind::InvalidInput, message));
+	if args.generate {
+		if let Some(stem) = specification.strip_suffix(".cpp") {
+			*specification = stem.to_string();
+		}
+		let source = format!("{specification}.cpp");
+		if Path::new(&source).is_file() {
+			return Ok(());
+		}
+		if Path::new(specification).is_file() {
+			return invalid(format!(
+				"--generate builds a generator from C++ source, but {source} does not exist. \
+				 '{specification}' is given without --generate if it is a specification"
+			));
+		}
+		return invalid(format!(
+			"the source of the generator, {source}, does not exist"
+		));
+	}
+	if specification.ends_with(".cpp") && Path::new(specification).is_file() {
+		return invalid(format!(
+			"'{specification}' is C++ source rather than a specification. Build it as a generator \
+			 with --generate, or with --testlib-generator if it uses testlib.h"
+		));
+	}
+	if !Path::new(specification).exists() {
+		let source = format!("{specification}.cpp");
+		return match Path::new(&source).is_file() {
+			true => invalid(format!(
+				"the specification '{specification}' does not exist, but {source} does. Build it \
+				 as a generator with --generate"
+			)),
+			false => invalid(format!(
+				"the specification '{specification}' does not exist"
+			)),
+		};
+	}
+	Ok(())
+}
+
+/// Reports the failures found by the session, returning the exit code of cpfuzz and the paths of
+/// the saved inputs of the failures, minimized where they were.
+fn conclude(session: &Session, failures: &[Failure]) -> Result<(u8, Vec<PathBuf>), Error> {
+	if session.args.timing
+		&& let Some(summary) = session.statistics.summary()
+	{
+		eprintln!("\n{summary}");
+	}
+	if interrupt::interrupted() {
+		let (tests, _) = session.statistics.maximum();
+		// Nothing is saved when interrupted before or between running generated tests.
+		match session.saved_last.load(Ordering::Relaxed) {
+			true => eprintln!(
+				"\nInterrupted after {tests} tests, the last input is saved to {}.last.in (seed {})",
+				session.stem, session.seed
+			),
+			false => eprintln!("\nInterrupted after {tests} tests (seed {})", session.seed),
+		}
+		if session.checkpoint.is_some() {
+			eprintln!("Continue where it stopped with: cpfuzz --resume");
+		}
+	}
+	let mut reported = Vec::new();
+	let exit_code = if failures.is_empty() {
+		if interrupt::interrupted() {
+			INTERRUPTED
+		} else {
+			if session.args.until_pass.is_some() {
+				let (tests, maximum) = session.statistics.maximum();
+				let maximum = maximum.unwrap_or_default();
+				let summary = format!("{tests} tests passed, max runtime {maximum:.1?}");
+				eprintln!("\n{}", style::passed(&summary));
+			} else {
+				eprintln!("\n{}", style::passed("No counterexample found"));
+			}
+			0
+		}
+	} else {
+		let comparison = Comparison::new(&session.args);
+		if session.args.keep_going.is_some() {
+			for (i, failure) in failures.iter().enumerate() {
+				let heading = format!("Failure {}:", i + 1);
+				report::begin_group(&session.args, &heading);
+				eprint!("\n{}", style::paint(Style::new().bold(), &heading));
+				let saved = failure.report(&format!("{}.{}", session.stem, i + 1), &comparison)?;
+				report::end_group(&session.args);
+				reported.push((failure, saved));
+			}
+		} else {
+			report::begin_group(&session.args, "Failure");
+			let saved = failures[0].report(&session.stem, &comparison)?;
+			report::end_group(&session.args);
+			reported.push((&failures[0], saved));
+		}
+		for (failure, saved) in reported.iter().filter(|(failure, _)| failure.generated()) {
+			let dir = archive::archive(session, failure, saved)?;
+			if !verbosity::quiet() {
+				eprintln!("Archived to {}", dir.display());
+			}
+		}
+		if session.args.save_corpus
+			&& let Some(dir) = &session.args.corpus
+		{
+			for failure in failures.iter().filter(|failure| failure.generated()) {
+				let test = failure.minimized.as_ref().unwrap_or(&failure.test);
+				eprintln!("Saved to {}", corpus::save(dir, test)?.display());
+			}
+		}
+		if let Some(how) = session.args.record_crash {
+			for (failure, saved) in &reported {
+				record::record(how, session.args.language, &failure.outcome.status, saved)?;
+			}
+		}
+		failures[0].outcome.status.exit_code()
+	};
+	if !verbosity::quiet() {
+		session.print_summary();
+	}
+	let path = session.args.report_file.as_deref();
+	match session.args.report {
+		args::ReportFormat::Human => {}
+		args::ReportFormat::Json => {
+			report::write(&report::json(session, &reported, exit_code), path)?;
+		}
+		args::ReportFormat::Github => {
+			report::write(&report::github(session, &reported, exit_code), path)?;
+		}
+	}
+	let inputs = reported
+		.iter()
+		.filter_map(|(_, saved)| {
+			let path = |name| saved.iter().find(|(n, _)| *n == name);
+			path("minimized").or(path("input"))
+		})
+		.map(|(_, path)| PathBuf::from(path))
+		.collect();
+	Ok((exit_code, inputs))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn verdicts() {
+		assert!(matches!(Verdict::parse("OK\n"), Some(Verdict::Accepted)));
+		assert!(matches!(
+			Verdict::parse(" AC 5 queries"),
+			Some(Verdict::Accepted)
+		));
+		assert!(matches!(
+			Verdict::parse("WA  expected 3, got 4 \nignored"),
+			Some(Verdict::WrongAnswer(message)) if message == "expected 3, got 4"
+		));
+		assert!(matches!(
+			Verdict::parse("PE"),
+			Some(Verdict::ProtocolViolation(message)) if message.is_empty()
+		));
+		assert!(matches!(
+			Verdict::parse("PV too many queries"),
+			Some(Verdict::ProtocolViolation(message)) if message == "too many queries"
+		));
+		assert!(Verdict::parse("").is_none());
+		assert!(Verdict::parse("ok").is_none());
+		assert!(Verdict::parse("\nOK").is_none());
+	}
+}
@@ -0,0 +1,233 @@
+use std::{
+	ffi::c_int,
+	io,
+	os::unix::process::CommandExt as _,
+	process::Command,
+	sync::mpsc::{self, RecvTimeoutError, Sender},
+	thread::{self, JoinHandle},
+	time::Duration,
+};
+
+use crate::args::{self, Language};
+
+#[cfg(target_os = "linux")]
+const RLIMIT_AS: c_int = 9;
+#[cfg(not(target_os = "linux"))]
+const RLIMIT_AS: c_int = 5;
+
+const RLIMIT_CORE: c_int = 4;
+
+#[repr(C)]
+struct RLimit {
+	current: u64,
+	maximum: u64,
+}
+
+const SIGKILL: c_int = 9;
+
+unsafe extern "C" {
+	fn setrlimit(resource: c_int, rlim: *const RLimit) -> c_int;
+	fn kill(pid: c_int, signal: c_int) -> c_int;
+}
+
+/// Kills every process in the process group led by the process, which is spawned in its own group
+/// so this includes the processes it spawned itself.
+pub fn kill_group(id: u32) {
+	// The processes may already have exited, in which case there is nothing to do.
+	unsafe {
+		kill(-(id as c_int), SIGKILL);
+	}
+}
+
+/// Lets the process dump its core however large, as the soft limit of zero usually forbids.
+pub fn enable_core_dumps(command: &mut Command) {
+	unsafe {
+		command.pre_exec(|| {
+			let limit = RLimit {
+				current: u64::MAX,
+				maximum: u64::MAX,
+			};
+			if setrlimit(RLIMIT_CORE, &limit) != 0 {
+				return Err(io::Error::last_os_error());
+			}
+			Ok(())
+		});
+	}
+}
+
+/// Messages printed by the runtimes when an allocation fails. A child which failed and printed one
+/// of these has ran out of memory rather than crashed for some other reason.
+const OUT_OF_MEMORY_MARKERS: &[&str] = &[
+	"std::bad_alloc",
+	"memory allocation of",
+	"rss limit exhausted",
+	"AddressSanitizer: out of memory",
+];
+
+/// Kills a process once its time limit is exceeded, unless stopped first.
+pub struct Timer {
+	stop: Sender<()>,
+	expired: JoinHandle<bool>,
+}
+
+impl Timer {
+	/// Stops the timer, returning whether the process was killed.
+	pub fn stop(self) -> bool {
+		drop(self.stop);
+		self.expired.join().expect("does not panic")
+	}
+}
+
+pub struct Limits {
+	memory: Option<u64>,
+	time: Option<Duration>,
+	compare_time: Option<Duration>,
+	solution: String,
+	comparators: Vec<String>,
+	interaction: Option<Duration>,
+	idle: Duration,
+}
+
+impl Limits {
+	pub fn new(args: &args::Args) -> Limits {
+		Limits {
+			memory: args.memory_limit,
+			time: args.time_limit,
+			compare_time: args.compare_time_limit.or(args.time_limit),
+			solution: args.name.clone(),
+			comparators: args.compare.clone(),
+			interaction: args.interaction_timeout,
+			idle: args.idle_timeout,
+		}
+	}
+
+	/// No limits at all, for running a trusted program outside of a session.
+	pub fn none() -> Limits {
+		Limits {
+			memory: None,
+			time: None,
+			compare_time: None,
+			solution: String::new(),
+			comparators: Vec::new(),
+			interaction: None,
+			idle: Duration::MAX,
+		}
+	}
+
+	/// The time limit of the program. Only the solution and the comparators are limited, the
+	/// programs judging them are trusted to finish.
+	pub fn time_limit(&self, program: &str) -> Option<Duration> {
+		if program == self.solution {
+			self.time
+		} else if self
+			.comparators
+			.iter()
+			.any(|comparator| comparator == program)
+		{
+			self.compare_time
+		} else {
+			None
+		}
+	}
+
+	/// Starts the timer of the spawned process of the program, if it has a time limit.
+	pub fn start_timer(&self, program: &str, id: u32) -> Option<Timer> {
+		let limit = self.time_limit(program)?;
+		let (stop, stopped) = mpsc::channel::<()>();
+		let expired = thread::spawn(move || match stopped.recv_timeout(limit) {
+			Err(RecvTimeoutError::Timeout) => {
+				kill_group(id);
+				true
+			}
+			_ => false,
+		});
+		Some(Timer { stop, expired })
+	}
+
+	/// Whether an interaction which has run for `elapsed` and been idle for `idle` should be
+	/// killed.
+	pub fn stalled(&self, elapsed: Duration, idle: Duration) -> bool {
+		idle >= self.idle
+			|| self
+				.interaction
+				.is_some_and(|interaction| elapsed >= interaction)
+	}
+
+	/// The memory limit of the program, which like the time limit only applies to the solution and
+	/// the comparators.
+	pub fn memory_limit(&self, program: &str) -> Option<u64> {
+		let limited = program == self.solution
+			|| self
+				.comparators
+				.iter()
+				.any(|comparator| comparator == program);
+		self.memory.filter(|_| limited)
+	}
+
+	/// Limits the memory of the command running the program, if it has a memory limit.
+	pub fn apply(&self, program: &str, language: Language, command: &mut Command) {
+		let Some(memory) = self.memory_limit(program) else {
+			return;
+		};
+		match language {
+			// The sanitizers reserve terabytes of address space up front, so an address space limit
+			// would make every run fail. Let the sanitizer runtime enforce the limit instead.
+			Language::CppSanitize => {
+				let megabytes = memory.div_ceil(1 << 20);
+				let limit = format!("hard_rss_limit_mb={megabytes}");
+				// Appended to the options of the user, overriding a limit of theirs.
+				let options = match std::env::var("ASAN_OPTIONS") {
+					Ok(options) if !options.is_empty() => format!("{options}:{limit}"),
+					_ => limit,
+				};
+				command.env("ASAN_OPTIONS", options);
+			}
+			_ => unsafe {
+				command.pre_exec(move || {
+					let limit = RLimit {
+						current: memory,
+						maximum: memory,
+					};
+					if setrlimit(RLIMIT_AS, &limit) != 0 {
+						return Err(io::Error::last_os_error());
+					}
+					Ok(())
+				});
+			},
+		}
+	}
+
+	/// Whether the program, which failed, did so by exceeding its memory limit. A program dying of
+	/// a failed allocation without a message, such as by a segmentation fault, is told by its peak
+	/// memory usage coming close to the limit, as the address space the limit applies to is always
+	/// somewhat larger than the memory in use. Otherwise it is told by what the runtime printed.
+	pub fn exceeded_memory(&self, program: &str, peak_memory: u64, stderr: &[u8]) -> bool {
+		let Some(limit) = self.memory_limit(program) else {
+			return false;
+		};
+		if peak_memory >= limit / 10 * 9 {
+			return true;
+		}
+		let stderr = String::from_utf8_lossy(stderr);
+		OUT_OF_MEMORY_MARKERS
+			.iter()
+			.any(|marker| stderr.contains(marker))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn exceeded_memory() {
+		let mut args = args::Args::new(Language::Cpp, "solution").unwrap();
+		args.memory_limit = Some(100 << 20);
+		let limits = Limits::new(&args);
+		assert!(limits.exceeded_memory("solution", 95 << 20, b""));
+		assert!(!limits.exceeded_memory("solution", 50 << 20, b"Segmentation fault"));
+		assert!(limits.exceeded_memory("solution", 50 << 20, b"std::bad_alloc"));
+		// The judges have no memory limit.
+		assert!(!limits.exceeded_memory("checker", 95 << 20, b"std::bad_alloc"));
+	}
+}
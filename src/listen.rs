@@ -0,0 +1,194 @@
+use std::{
+	collections::HashMap,
+	fmt::Write as _,
+	io::{self, BufRead as _, BufReader, Read as _, Write as _},
+	net::{TcpListener, TcpStream},
+	path::{Path, PathBuf},
+	process::ExitCode,
+};
+
+use crate::{
+	args, corpus,
+	error::Error,
+	generator::Test,
+	json::Value,
+	statement::{self, Constraints},
+};
+
+/// A problem as sent by Competitive Companion.
+struct Problem {
+	name: String,
+	url: String,
+	interactive: bool,
+	/// In milliseconds.
+	time_limit: Option<f64>,
+	/// In megabytes.
+	memory_limit: Option<f64>,
+	tests: Vec<Test>,
+	/// The id of the batch of problems sent at once, and how many it has.
+	batch: Option<(String, usize)>,
+}
+
+impl Problem {
+	fn parse(body: &str) -> Option<Problem> {
+		let value = Value::parse(body)?;
+		let text = |key| value.get(key).and_then(Value::as_str).map(str::to_string);
+		let tests = value
+			.get("tests")?
+			.as_array()?
+			.iter()
+			.map(|test| {
+				let input = test.get("input")?.as_str()?;
+				let output = test.get("output").and_then(Value::as_str);
+				Some(Test {
+					input: input.as_bytes().to_vec(),
+					answer: output.map(|output| output.as_bytes().to_vec()),
+				})
+			})
+			.collect::<Option<_>>()?;
+		let batch = value.get("batch").and_then(|batch| {
+			let id = batch.get("id")?.as_str()?.to_string();
+			Some((id, batch.get("size")?.as_f64()? as usize))
+		});
+		Some(Problem {
+			name: text("name")?,
+			url: text("url").unwrap_or_default(),
+			interactive: value
+				.get("interactive")
+				.and_then(Value::as_bool)
+				.unwrap_or(false),
+			time_limit: value.get("timeLimit").and_then(Value::as_f64),
+			memory_limit: value.get("memoryLimit").and_then(Value::as_f64),
+			tests,
+			batch,
+		})
+	}
+}
+
+/// The name of the solution of a problem, such as `a-watermelon` for "A. Watermelon".
+pub fn slug(name: &str) -> String {
+	let mut slug = String::new();
+	for c in name.chars() {
+		if c.is_ascii_alphanumeric() {
+			slug.push(c.to_ascii_lowercase());
+		} else if !slug.is_empty() && !slug.ends_with('-') {
+			slug.push('-');
+		}
+	}
+	let slug = slug.trim_end_matches('-');
+	match slug.is_empty() {
+		true => "problem".to_string(),
+		false => slug.to_string(),
+	}
+}
+
+/// Saves the samples and scaffolds the specification of a problem, printing how to fuzz it.
+fn save(problem: &Problem, language: args::Language) -> io::Result<()> {
+	let slug = slug(&problem.name);
+	let samples = PathBuf::from(format!("{slug}.samples"));
+	for test in &problem.tests {
+		corpus::save(&samples, test)?;
+	}
+	eprintln!("\n{} ({})", problem.name, problem.url);
+	eprintln!(
+		"Saved {} samples to {}",
+		problem.tests.len(),
+		samples.display()
+	);
+	let spec = format!("{slug}.spec");
+	let scaffolded = problem.tests.first().and_then(|test| {
+		let sample = String::from_utf8_lossy(&test.input);
+		statement::scaffold(&sample, &Constraints::default(), &mut Vec::new())
+	});
+	if Path::new(&spec).exists() {
+		eprintln!("Kept the existing specification {spec}");
+	} else if let Some(scaffolded) = scaffolded {
+		std::fs::write(&spec, scaffolded)?;
+		eprintln!(
+			"Scaffolded {spec} from the first sample, fill in the constraints of the statement"
+		);
+	} else {
+		eprintln!(
+			"The samples contain words, which a specification can not generate: write a generator"
+		);
+	}
+	let mut command = format!(
+		"cpfuzz {} {slug} {spec} --samples {}",
+		clap::ValueEnum::to_possible_value(&language)
+			.expect("no variant is skipped")
+			.get_name(),
+		samples.display()
+	);
+	if let Some(time_limit) = problem.time_limit {
+		write!(command, " --time-limit {time_limit}ms").expect("write to memory");
+	}
+	if let Some(memory_limit) = problem.memory_limit {
+		write!(command, " --memory-limit {memory_limit}M").expect("write to memory");
+	}
+	if problem.interactive {
+		command.push_str(" --interactive INTERACTOR");
+	}
+	eprintln!("Fuzz with: {command}");
+	Ok(())
+}
+
+/// Reads the body of an HTTP request and answers it.
+fn receive(stream: TcpStream) -> io::Result<String> {
+	let mut reader = BufReader::new(&stream);
+	let mut length = 0;
+	loop {
+		let mut line = String::new();
+		if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+			break;
+		}
+		if let Some((name, value)) = line.split_once(':')
+			&& name.eq_ignore_ascii_case("content-length")
+		{
+			length = value.trim().parse().map_err(|_| {
+				io::Error::new(io::ErrorKind::InvalidData, "invalid Content-Length")
+			})?;
+		}
+	}
+	let mut body = vec![0; length];
+	reader.read_exact(&mut body)?;
+	(&stream).write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\nConnection: close\r\n\r\n")?;
+	String::from_utf8(body).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
+
+/// Receives problems from the Competitive Companion browser extension, saving their samples for
+/// --samples and scaffolding their specifications.
+pub fn listen(args: &args::ListenArgs) -> Result<ExitCode, Error> {
+	let listener = TcpListener::bind(("127.0.0.1", args.port))?;
+	eprintln!(
+		"Listening on port {}, add it to the custom ports of Competitive Companion",
+		args.port
+	);
+	// How many problems of every batch are still to come.
+	let mut batches: HashMap<String, usize> = HashMap::new();
+	for stream in listener.incoming() {
+		let body = match stream.and_then(receive) {
+			Ok(body) => body,
+			Err(e) => {
+				eprintln!("Failed to receive a problem: {e}");
+				continue;
+			}
+		};
+		let Some(problem) = Problem::parse(&body) else {
+			eprintln!("Received something which is not a problem from Competitive Companion");
+			continue;
+		};
+		save(&problem, args.language)?;
+		let remaining = match &problem.batch {
+			Some((id, size)) => {
+				let remaining = batches.entry(id.clone()).or_insert(*size);
+				*remaining = remaining.saturating_sub(1);
+				*remaining
+			}
+			None => 0,
+		};
+		if args.once && remaining == 0 {
+			break;
+		}
+	}
+	Ok(ExitCode::SUCCESS)
+}
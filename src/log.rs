@@ -0,0 +1,97 @@
+use std::{
+	collections::VecDeque,
+	fs::File,
+	io::{self, Write as _},
+	path::{Path, PathBuf},
+	sync::Mutex,
+};
+
+use crate::{generator::Test, json::Json};
+
+/// Name of the file listing the seed and verdict of every logged input.
+const VERDICTS: &str = "verdicts.txt";
+
+/// Every generated input of the session, written to a directory so the generator can be audited.
+/// Only the most recent inputs are kept, the verdicts of all of them are listed in
+/// `verdicts.txt`.
+pub struct InputLog {
+	dir: PathBuf,
+	max_files: usize,
+	max_bytes: u64,
+	state: Mutex<State>,
+}
+
+struct State {
+	verdicts: File,
+	logged: u64,
+	/// The retained inputs, oldest first, with the number of bytes they occupy.
+	files: VecDeque<(PathBuf, u64)>,
+	bytes: u64,
+}
+
+impl InputLog {
+	pub fn new(dir: &Path, max_files: usize, max_bytes: u64) -> io::Result<InputLog> {
+		std::fs::create_dir_all(dir)?;
+		let verdicts = File::create(dir.join(VERDICTS))?;
+		Ok(InputLog {
+			dir: dir.to_path_buf(),
+			max_files,
+			max_bytes,
+			state: Mutex::new(State {
+				verdicts,
+				logged: 0,
+				files: VecDeque::new(),
+				bytes: 0,
+			}),
+		})
+	}
+
+	/// Writes the input generated from the seed, or by mutation without one, removing the oldest
+	/// inputs if the caps are exceeded.
+	pub fn record(&self, seed: Option<u64>, test: &Test, verdict: &str) -> io::Result<()> {
+		let mut state = self.state.lock().expect("not poisoned");
+		let name = format!("{:06}", state.logged);
+		state.logged += 1;
+		writeln!(state.verdicts, "{name} {}: {verdict}", crate::origin(seed))?;
+		let stem = self.dir.join(&name);
+		test.save(&stem.to_string_lossy())?;
+		let bytes = (test.input.len() + test.answer.as_ref().map_or(0, Vec::len)) as u64;
+		state.files.push_back((stem, bytes));
+		state.bytes += bytes;
+		while state.files.len() > self.max_files
+			|| (state.bytes > self.max_bytes && state.files.len() > 1)
+		{
+			let (stem, bytes) = state.files.pop_front().expect("not empty");
+			state.bytes -= bytes;
+			std::fs::remove_file(stem.with_extension("in"))?;
+			match std::fs::remove_file(stem.with_extension("ans")) {
+				Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+				_ => {}
+			}
+		}
+		Ok(())
+	}
+}
+
+/// A JSON line appended to a file for every test, for analysing a session afterwards.
+pub struct EventLog {
+	file: Mutex<File>,
+}
+
+impl EventLog {
+	pub fn new(path: &Path) -> io::Result<EventLog> {
+		let file = File::options().create(true).append(true).open(path)?;
+		Ok(EventLog {
+			file: Mutex::new(file),
+		})
+	}
+
+	pub fn record(&self, event: &Json) -> io::Result<()> {
+		// A single write per line keeps the lines of concurrent workers whole.
+		let line = format!("{event}\n");
+		self.file
+			.lock()
+			.expect("not poisoned")
+			.write_all(line.as_bytes())
+	}
+}
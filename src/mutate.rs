@@ -0,0 +1,139 @@
+use std::sync::Mutex;
+
+use crate::{choices::Choices, random::Random};
+
+/// Most mutations applied to an input at once.
+const MAX_MUTATIONS: usize = 3;
+
+/// Most choices duplicated or removed by a single mutation.
+const MAX_RUN: usize = 8;
+
+/// Mixed into the seed of a test to decide whether and how it mutates an input, as a test
+/// generated from scratch uses the stream of the seed itself. Deciding with that same stream would
+/// leave the tests generated from scratch only the values it did not decide to mutate with.
+const STREAM: u64 = 0x6d75_7461_7465_2121;
+
+/// The choices of the test with this seed if it should mutate an input of one of the pools
+/// rather than be generated from scratch, which half of the tests do while there are any.
+pub fn mutation(pools: &[&Pool], seed: u64) -> Option<Choices> {
+	let mut rng = Random::with_seed(seed ^ STREAM);
+	if pools.is_empty() || rng.bool() {
+		return None;
+	}
+	pools[rng.below(pools.len())].mutation(&mut rng)
+}
+
+/// Inputs which are worth mutating, by the choices generating them.
+pub struct Pool {
+	inputs: Mutex<Vec<Vec<u64>>>,
+}
+
+impl Pool {
+	pub fn new(inputs: Vec<Vec<u64>>) -> Pool {
+		Pool {
+			inputs: Mutex::new(inputs),
+		}
+	}
+
+	pub fn add(&self, choices: Vec<u64>) {
+		self.inputs.lock().expect("not poisoned").push(choices);
+	}
+
+	pub fn inputs(&self) -> Vec<Vec<u64>> {
+		self.inputs.lock().expect("not poisoned").clone()
+	}
+
+	pub fn len(&self) -> usize {
+		self.inputs.lock().expect("not poisoned").len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	/// The choices of a mutation of one of the inputs, or None if there are none. The inputs
+	/// added later are picked more often, as they tend to be the more interesting ones.
+	pub fn mutation(&self, rng: &mut Random) -> Option<Choices> {
+		let inputs = self.inputs.lock().expect("not poisoned");
+		if inputs.is_empty() {
+			return None;
+		}
+		let picked = rng.below(inputs.len()).max(rng.below(inputs.len()));
+		let mutated = mutate(&inputs[picked], rng);
+		Some(Choices::mutate(mutated, rng.u64()))
+	}
+}
+
+/// Applies a few random mutations to the choices, where None marks a choice made at random. The
+/// mutations work on the choices rather than the input, so the mutated input is generated by
+/// the specification or generator like any other and is valid by construction: changing a
+/// length grows or shrinks the array, and swapping or duplicating choices swaps or duplicates
+/// the numbers or lines they generate.
+fn mutate(choices: &[u64], rng: &mut Random) -> Vec<Option<u64>> {
+	let mut mutated: Vec<_> = choices.iter().copied().map(Some).collect();
+	for _ in 0..1 + rng.below(MAX_MUTATIONS) {
+		let len = mutated.len();
+		if len == 0 {
+			mutated.push(None);
+			continue;
+		}
+		let i = rng.below(len);
+		match rng.below(6) {
+			// Choose one number anew.
+			0 => mutated[i] = None,
+			// Nudge one number, as boundaries are often off by one.
+			1 => {
+				let offset = mutated[i].unwrap_or(0);
+				let delta = 1 + rng.up_to(1);
+				mutated[i] = Some(match rng.bool() {
+					true => offset.saturating_add(delta),
+					false => offset.saturating_sub(delta),
+				});
+			}
+			// Swap two numbers.
+			2 => mutated.swap(i, rng.below(len)),
+			// Duplicate a run of numbers, such as a line.
+			3 => {
+				let end = (i + 1 + rng.below(MAX_RUN)).min(len);
+				let run: Vec<_> = mutated[i..end].to_vec();
+				mutated.splice(end..end, run);
+			}
+			// Remove a run of numbers.
+			4 => {
+				let end = (i + 1 + rng.below(MAX_RUN)).min(len);
+				mutated.drain(i..end);
+			}
+			// Insert numbers chosen at random.
+			_ => {
+				let count = 1 + rng.below(MAX_RUN);
+				mutated.splice(i..i, std::iter::repeat_n(None, count));
+			}
+		}
+	}
+	mutated
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The tests generated from scratch while there are inputs to mutate must cover the whole
+	/// range of their first choice.
+	#[test]
+	fn generated_tests_are_unbiased() {
+		let pool = Pool::new(vec![vec![1, 2, 3]]);
+		let (mut low, mut high, mut mutated) = (0, 0, 0);
+		for seed in 0..1000 {
+			if mutation(&[&pool], seed).is_some() {
+				mutated += 1;
+				continue;
+			}
+			match Choices::random(seed).i64(1, 100) {
+				..=50 => low += 1,
+				_ => high += 1,
+			}
+		}
+		assert!((400..=600).contains(&mutated), "{mutated}");
+		assert!(low > 200 && high > 200, "{low} {high}");
+	}
+}
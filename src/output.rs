@@ -0,0 +1,51 @@
+use std::{
+	io,
+	path::Path,
+	time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::args;
+
+/// The path every saved file of the session starts with, such as `fuzz` for `fuzz.in` and
+/// `fuzz.min.in`, from --out-dir and --out-prefix. The directory is created if it is missing.
+pub fn stem(args: &args::Args) -> io::Result<String> {
+	let timestamp = timestamp(SystemTime::now());
+	let expand = |template: &str| {
+		template
+			.replace("{problem}", &args.name)
+			.replace("{timestamp}", &timestamp)
+	};
+	let prefix = expand(&args.out_prefix);
+	let Some(dir) = &args.out_dir else {
+		return Ok(prefix);
+	};
+	let dir = expand(&dir.to_string_lossy());
+	std::fs::create_dir_all(&dir)?;
+	Ok(Path::new(&dir).join(prefix).to_string_lossy().into_owned())
+}
+
+/// The time in UTC formatted as `20240131-235959`, which sorts chronologically.
+pub fn timestamp(time: SystemTime) -> String {
+	let seconds = time
+		.duration_since(UNIX_EPOCH)
+		.map_or(0, |since| since.as_secs());
+	let (days, time) = (seconds / 86400, seconds % 86400);
+	// Converts days since the epoch to a civil date, see
+	// https://howardhinnant.github.io/date_algorithms.html#civil_from_days.
+	let z = days as i64 + 719468;
+	let era = z.div_euclid(146097);
+	let day_of_era = z.rem_euclid(146097);
+	let year_of_era =
+		(day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+	let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+	let mp = (5 * day_of_year + 2) / 153;
+	let day = day_of_year - (153 * mp + 2) / 5 + 1;
+	let month = if mp < 10 { mp + 3 } else { mp - 9 };
+	let year = year_of_era + era * 400 + i64::from(month <= 2);
+	format!(
+		"{year:04}{month:02}{day:02}-{:02}{:02}{:02}",
+		time / 3600,
+		time / 60 % 60,
+		time % 60
+	)
+}
@@ -0,0 +1,179 @@
+use std::{
+	ffi::c_int,
+	io::{self, BufRead as _, BufReader, Read as _, Write as _},
+	process::{ChildStdin, ChildStdout},
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use crate::{
+	Exit, Process, Run,
+	args::{self, Language},
+	interrupt,
+	limits::{self, Limits, Timer},
+	usage::{self, Usage},
+};
+
+#[cfg(target_os = "linux")]
+const SC_CLK_TCK: c_int = 2;
+#[cfg(not(target_os = "linux"))]
+const SC_CLK_TCK: c_int = 3;
+
+unsafe extern "C" {
+	fn sysconf(name: c_int) -> i64;
+}
+
+/// A process of the solution waiting for its next test.
+struct Warm {
+	process: Process,
+	stdin: ChildStdin,
+	stdout: BufReader<ChildStdout>,
+}
+
+impl Warm {
+	fn spawn(language: Language, program: &str, limits: &Limits) -> io::Result<Warm> {
+		let mut process = language.spawn(program, &[], limits)?;
+		// Every test is timed on its own instead.
+		if let Some(timer) = process.timer.take() {
+			timer.stop();
+		}
+		let stdin = process.child.stdin.take().expect("is piped");
+		let stdout = process.child.stdout.take().expect("is piped");
+		Ok(Warm {
+			process,
+			stdin,
+			stdout: BufReader::new(stdout),
+		})
+	}
+
+	/// Writes the framed input and reads the framed output the harness answers with.
+	fn answer(&mut self, input: &[u8]) -> io::Result<Vec<u8>> {
+		writeln!(self.stdin, "{}", input.len())?;
+		self.stdin.write_all(input)?;
+		self.stdin.flush()?;
+		let mut header = String::new();
+		if self.stdout.read_line(&mut header)? == 0 {
+			return Err(io::ErrorKind::UnexpectedEof.into());
+		}
+		let length: usize = header.trim().parse().map_err(|_| {
+			io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!("expected the length of the output, got {header:?}"),
+			)
+		})?;
+		let mut output = vec![0; length];
+		self.stdout.read_exact(&mut output)?;
+		Ok(output)
+	}
+}
+
+/// The solution kept running between tests by --persistent, with an idle process for every worker
+/// which is not running a test. A process is replaced once it dies.
+pub struct Persistent {
+	pub program: String,
+	idle: Mutex<Vec<Warm>>,
+}
+
+impl Persistent {
+	pub fn new(args: &args::Args) -> Option<Persistent> {
+		args.persistent.then(|| Persistent {
+			program: args.name.clone(),
+			idle: Mutex::new(Vec::new()),
+		})
+	}
+
+	/// Runs the solution on the input like spawning it would, with an idle process if there is
+	/// one.
+	pub fn run(&self, language: Language, limits: &Limits, input: &[u8]) -> io::Result<Run> {
+		let idle = self.idle.lock().expect("not poisoned").pop();
+		let mut warm = match idle {
+			Some(warm) => warm,
+			None => Warm::spawn(language, &self.program, limits)?,
+		};
+		let id = warm.process.child.id();
+		let (cpu, _) = resources(id);
+		let timer = limits.start_timer(&self.program, id);
+		let started = Instant::now();
+		let answer = warm.answer(input);
+		let wall = started.elapsed();
+		let timed_out = timer.is_some_and(Timer::stop);
+		let error = match answer {
+			Ok(stdout) if !timed_out => {
+				let (total, memory) = resources(id);
+				let cpu = total.saturating_sub(cpu);
+				usage::record(&self.program, Usage { wall, cpu, memory });
+				self.idle.lock().expect("not poisoned").push(warm);
+				return Ok(Run {
+					exit: Exit::Success,
+					stdout,
+					spilled: None,
+					stderr: Vec::new(),
+					unconsumed: false,
+				});
+			}
+			Ok(_) => None,
+			Err(e) => Some(e),
+		};
+		// The process died, or broke the protocol in which case it is killed.
+		let Warm { process, stdin, .. } = warm;
+		let broke_protocol = error
+			.as_ref()
+			.is_some_and(|e| e.kind() == io::ErrorKind::InvalidData);
+		if broke_protocol {
+			limits::kill_group(id);
+		}
+		drop(stdin);
+		let (exit, mut stderr) = process.wait(limits)?;
+		let exit = match (limits.time_limit(&self.program), exit) {
+			(Some(limit), _) if timed_out => Exit::TimeLimit(limit),
+			(_, Exit::Success) => Exit::Failure(None),
+			(_, _) if broke_protocol => Exit::Failure(None),
+			(_, exit) => exit,
+		};
+		if let Some(error) = error.filter(|_| broke_protocol) {
+			stderr.extend_from_slice(format!("\ncpfuzz: {error}\n").as_bytes());
+		}
+		Ok(Run {
+			exit,
+			stdout: Vec::new(),
+			spilled: None,
+			stderr,
+			unconsumed: false,
+		})
+	}
+}
+
+impl Drop for Persistent {
+	fn drop(&mut self) {
+		for mut warm in self.idle.get_mut().expect("not poisoned").drain(..) {
+			let id = warm.process.child.id();
+			limits::kill_group(id);
+			interrupt::unregister(id);
+			let _ = warm.process.child.wait();
+		}
+	}
+}
+
+/// The cpu time the process used so far and its peak resident set size, which the kernel only
+/// reports for running processes through /proc. Zero where there is no /proc.
+fn resources(id: u32) -> (Duration, u64) {
+	let cpu = std::fs::read_to_string(format!("/proc/{id}/stat"))
+		.ok()
+		.and_then(|stat| {
+			// The fields after the parenthesized command name, starting with the third.
+			let (_, fields) = stat.rsplit_once(')')?;
+			let fields: Vec<_> = fields.split_whitespace().collect();
+			let ticks: u64 =
+				fields.get(11)?.parse::<u64>().ok()? + fields.get(12)?.parse::<u64>().ok()?;
+			let per_second = unsafe { sysconf(SC_CLK_TCK) }.max(1) as u64;
+			Some(Duration::from_secs_f64(ticks as f64 / per_second as f64))
+		});
+	let memory = std::fs::read_to_string(format!("/proc/{id}/status"))
+		.ok()
+		.and_then(|status| {
+			let line = status.lines().find(|line| line.starts_with("VmHWM:"))?;
+			let kilobytes: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+			Some(kilobytes * 1024)
+		});
+	(cpu.unwrap_or_default(), memory.unwrap_or_default())
+}
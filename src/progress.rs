@@ -0,0 +1,100 @@
+use std::{
+	io::{self, IsTerminal as _, Write as _},
+	sync::Mutex,
+	time::{Duration, Instant},
+};
+
+use crate::{style::separate_thousands, usage::Statistics};
+
+/// How often the progress line is redrawn at most.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(100);
+
+/// How often a heartbeat is printed at most.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_millis(250);
+
+/// A single line of stderr showing how far fuzzing has come, redrawn in place. It is only shown
+/// on a terminal, where it does not end up in logs, and not when every test is reported anyway.
+pub struct Progress {
+	enabled: bool,
+	started: Instant,
+	drawn: Mutex<Option<Instant>>,
+}
+
+impl Progress {
+	pub fn new(enabled: bool) -> Progress {
+		Progress {
+			enabled: enabled && io::stderr().is_terminal(),
+			started: Instant::now(),
+			drawn: Mutex::new(None),
+		}
+	}
+
+	/// Redraws the line unless it was drawn recently or another worker is drawing it.
+	pub fn update(&self, statistics: &Statistics) -> io::Result<()> {
+		if !self.enabled {
+			return Ok(());
+		}
+		let Ok(mut drawn) = self.drawn.try_lock() else {
+			return Ok(());
+		};
+		if drawn.is_some_and(|drawn| drawn.elapsed() < REDRAW_INTERVAL) {
+			return Ok(());
+		}
+		*drawn = Some(Instant::now());
+		self.draw(statistics)
+	}
+
+	/// Redraws the line with the final numbers.
+	pub fn finish(&self, statistics: &Statistics) -> io::Result<()> {
+		if !self.enabled {
+			return Ok(());
+		}
+		let _drawn = self.drawn.lock().expect("not poisoned");
+		self.draw(statistics)
+	}
+
+	fn draw(&self, statistics: &Statistics) -> io::Result<()> {
+		let (tests, maximum) = statistics.maximum();
+		let elapsed = self.started.elapsed();
+		let rate = statistics.rate(elapsed);
+		let mut line = format!(
+			"tests: {} | rate: {rate:.0}/s | elapsed: {} s",
+			separate_thousands(tests),
+			elapsed.as_secs()
+		);
+		if let Some(maximum) = maximum {
+			line.push_str(&format!(" | max runtime: {} ms", maximum.as_millis()));
+		}
+		// Clear the rest of the previous line, which may have been longer.
+		let mut stderr = io::stderr();
+		write!(stderr, "\r{line}\x1b[K")?;
+		stderr.flush()
+	}
+}
+
+/// Dots showing that a long running step is still making progress, printed a few times per second
+/// however fast the step goes.
+pub struct Heartbeat {
+	enabled: bool,
+	beaten: Option<Instant>,
+}
+
+impl Heartbeat {
+	pub fn new(enabled: bool) -> Heartbeat {
+		Heartbeat {
+			enabled: enabled && io::stderr().is_terminal(),
+			beaten: None,
+		}
+	}
+
+	pub fn beat(&mut self) {
+		if self.enabled
+			&& self
+				.beaten
+				.is_none_or(|beaten| beaten.elapsed() >= HEARTBEAT_INTERVAL)
+		{
+			self.beaten = Some(Instant::now());
+			eprint!(".");
+		}
+	}
+}
@@ -0,0 +1,174 @@
+use std::{
+	fs::File,
+	io,
+	os::unix::process::ExitStatusExt as _,
+	path::{Path, PathBuf},
+	process::{Command, Stdio},
+};
+
+use crate::{
+	Status,
+	args::{Language, RecordCrash},
+	limits,
+	report::Artifacts,
+	style, verbosity,
+};
+
+/// The binary to run the crashing input with. C++ is built again with debug information, which
+/// does not change the generated code, so the debugger shows the source of the crash.
+fn binary(language: Language, program: &str) -> PathBuf {
+	let path = language.binary(program);
+	if !matches!(language, Language::Cpp) {
+		return path;
+	}
+	let debug = format!("{program}.debug");
+	let mut command = Command::new("g++");
+	command.args(["-g", "-O2", &format!("{program}.cpp"), "-o", &debug]);
+	verbosity::command(&command);
+	match command.stderr(Stdio::null()).status() {
+		Ok(status) if status.success() => Path::new(".").join(debug),
+		_ => path,
+	}
+}
+
+/// Records the crash with rr to `{stem}.rr`, for `rr replay` to debug it back and forth in time.
+fn rr(binary: &Path, stem: &str, input: &str) -> io::Result<()> {
+	let trace = format!("{stem}.rr");
+	// rr does not record to a directory which exists.
+	match std::fs::remove_dir_all(&trace) {
+		Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+		_ => {}
+	}
+	let mut command = Command::new("rr");
+	command
+		.arg("record")
+		.arg("--output-trace-dir")
+		.arg(&trace)
+		.arg(binary)
+		.stdin(File::open(input)?)
+		.stdout(Stdio::null())
+		.stderr(Stdio::piped());
+	verbosity::command(&command);
+	let output = match command.output() {
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {
+			eprintln!("Could not record the crash: rr is not installed");
+			return Ok(());
+		}
+		output => output?,
+	};
+	if !Path::new(&trace).is_dir() {
+		let stderr = String::from_utf8_lossy(&output.stderr);
+		let reason = stderr.lines().find(|line| !line.trim().is_empty());
+		eprintln!(
+			"Could not record the crash: rr failed{}",
+			reason.map_or(String::new(), |reason| format!(": {}", reason.trim()))
+		);
+		return Ok(());
+	}
+	if output.status.success() {
+		eprintln!("The program did not crash again under rr, the recording is saved to {trace}");
+		return Ok(());
+	}
+	eprintln!("Recorded the crash to {trace}, debug it with: rr replay {trace}");
+	Ok(())
+}
+
+/// Where the kernel writes the core dump of the process, by `/proc/sys/kernel/core_pattern`. None
+/// if it is piped to a handler or the pattern is not understood.
+fn core_path(pattern: &str, binary: &Path, pid: u32) -> Option<PathBuf> {
+	let mut path = String::new();
+	let mut chars = pattern.chars();
+	while let Some(c) = chars.next() {
+		if c != '%' {
+			path.push(c);
+			continue;
+		}
+		match chars.next()? {
+			'%' => path.push('%'),
+			'p' | 'P' => path.push_str(&pid.to_string()),
+			// The name the kernel knows the process by, which is cut to 15 bytes.
+			'e' => path.extend(binary.file_name()?.to_str()?.chars().take(15)),
+			_ => return None,
+		}
+	}
+	let uses_pid = std::fs::read_to_string("/proc/sys/kernel/core_uses_pid")
+		.is_ok_and(|uses_pid| uses_pid.trim() == "1");
+	if uses_pid && !pattern.contains("%p") {
+		path.push_str(&format!(".{pid}"));
+	}
+	Some(PathBuf::from(path))
+}
+
+/// Runs the input again with core dumps enabled, saving the core to `{stem}.core` for gdb.
+fn core(binary: &Path, stem: &str, input: &str) -> io::Result<()> {
+	let pattern = std::fs::read_to_string("/proc/sys/kernel/core_pattern")
+		.unwrap_or_else(|_| "core".to_string());
+	let pattern = pattern.trim();
+	let mut command = Command::new(binary);
+	command
+		.stdin(File::open(input)?)
+		.stdout(Stdio::null())
+		.stderr(Stdio::null());
+	limits::enable_core_dumps(&mut command);
+	verbosity::command(&command);
+	let mut child = command.spawn()?;
+	let pid = child.id();
+	let status = child.wait()?;
+	if status.signal().is_none() {
+		eprintln!("The program did not crash again, so there is no core dump");
+		return Ok(());
+	}
+	if let Some(handler) = pattern.strip_prefix('|') {
+		let handler = handler.split_ascii_whitespace().next().unwrap_or(handler);
+		match handler.ends_with("systemd-coredump") {
+			true => eprintln!("Dumped the core to systemd, debug it with: coredumpctl debug {pid}"),
+			false => eprintln!("The core dump was passed to {handler}, which keeps it"),
+		}
+		return Ok(());
+	}
+	let Some(path) = core_path(pattern, binary, pid).filter(|_| status.core_dumped()) else {
+		eprintln!("No core dump was written, see /proc/sys/kernel/core_pattern");
+		return Ok(());
+	};
+	let saved = PathBuf::from(format!("{stem}.core"));
+	let path = match std::fs::rename(&path, &saved) {
+		Ok(()) => saved,
+		// Elsewhere on another filesystem, where it is left.
+		Err(_) if path.exists() => path,
+		Err(_) => {
+			eprintln!("The core dump was not found at {}", path.display());
+			return Ok(());
+		}
+	};
+	eprintln!(
+		"Dumped the core to {}, debug it with: gdb {} {}",
+		path.display(),
+		binary.display(),
+		path.display()
+	);
+	Ok(())
+}
+
+/// Runs the input of a crash of a program again as --record-crash says, for a debugger to show
+/// where it crashed. Crashes are recorded from the minimized input if there is one.
+pub fn record(
+	how: RecordCrash,
+	language: Language,
+	status: &Status,
+	saved: &Artifacts,
+) -> io::Result<()> {
+	let Status::Crashed { program, .. } = status else {
+		return Ok(());
+	};
+	let path = |name| saved.iter().find(|(n, _)| *n == name);
+	let Some((_, input)) = path("minimized").or(path("input")) else {
+		return Ok(());
+	};
+	let stem = input.strip_suffix(".in").unwrap_or(input);
+	style::section("DEBUGGING");
+	let binary = binary(language, program);
+	match how {
+		RecordCrash::Rr => rr(&binary, stem, input),
+		RecordCrash::Core => core(&binary, stem, input),
+	}
+}
@@ -0,0 +1,197 @@
+use std::{
+	ffi::{c_int, c_short, c_ulong},
+	io::{self, Read as _, Write as _},
+	os::fd::{AsRawFd as _, RawFd},
+	process::{ChildStdin, ChildStdout},
+	time::{Duration, Instant},
+};
+
+use crate::{
+	Party,
+	limits::{self, Limits},
+};
+
+const POLLIN: c_short = 0x1;
+const POLLOUT: c_short = 0x4;
+
+const F_GETFL: c_int = 3;
+const F_SETFL: c_int = 4;
+#[cfg(target_os = "linux")]
+const O_NONBLOCK: c_int = 0x800;
+#[cfg(not(target_os = "linux"))]
+const O_NONBLOCK: c_int = 0x4;
+
+#[repr(C)]
+struct PollFd {
+	fd: c_int,
+	events: c_short,
+	revents: c_short,
+}
+
+unsafe extern "C" {
+	fn poll(fds: *mut PollFd, count: c_ulong, timeout: c_int) -> c_int;
+	fn fcntl(fd: c_int, command: c_int, ...) -> c_int;
+}
+
+/// How often the relay checks whether the interaction stalled while nothing happens.
+const WATCHDOG_INTERVAL: Duration = Duration::from_millis(10);
+
+fn set_nonblocking(fd: RawFd) -> io::Result<()> {
+	let flags = unsafe { fcntl(fd, F_GETFL) };
+	if flags < 0 || unsafe { fcntl(fd, F_SETFL, flags | O_NONBLOCK) } < 0 {
+		return Err(io::Error::last_os_error());
+	}
+	Ok(())
+}
+
+/// Data written by one party on its way to the other.
+pub struct Channel {
+	source: Option<ChildStdout>,
+	/// Absent once closed, or once the receiving party closed its end.
+	sink: Option<ChildStdin>,
+	/// The party receiving the data.
+	party: Party,
+	pending: Vec<u8>,
+}
+
+impl Channel {
+	pub fn new(source: ChildStdout, sink: ChildStdin, party: Party) -> io::Result<Channel> {
+		// Writes must not block, as the relay then could not serve the other direction.
+		set_nonblocking(sink.as_raw_fd())?;
+		Ok(Channel {
+			source: Some(source),
+			sink: Some(sink),
+			party,
+			pending: Vec::new(),
+		})
+	}
+
+	/// The file descriptor to wait for and what to wait for, until the channel is done.
+	fn interest(&mut self) -> Option<PollFd> {
+		if self.pending.is_empty() {
+			let Some(source) = &self.source else {
+				// Everything was passed on, so the receiving party sees the end of its input.
+				self.sink = None;
+				return None;
+			};
+			Some(PollFd {
+				fd: source.as_raw_fd(),
+				events: POLLIN,
+				revents: 0,
+			})
+		} else {
+			let sink = self
+				.sink
+				.as_ref()
+				.expect("data is only pending for an open sink");
+			Some(PollFd {
+				fd: sink.as_raw_fd(),
+				events: POLLOUT,
+				revents: 0,
+			})
+		}
+	}
+
+	/// Reads or writes what polling found ready, returning whether data was read.
+	fn serve(
+		&mut self,
+		buf: &mut [u8],
+		transcript: &mut Vec<(Party, Vec<u8>)>,
+	) -> io::Result<bool> {
+		if self.pending.is_empty() {
+			let source = self.source.as_mut().expect("polled for reading");
+			return match source.read(buf) {
+				Ok(0) => {
+					self.source = None;
+					Ok(false)
+				}
+				Ok(n) => {
+					transcript.push((self.party, buf[..n].to_vec()));
+					if self.sink.is_some() {
+						self.pending.extend_from_slice(&buf[..n]);
+					}
+					Ok(true)
+				}
+				Err(e) if e.kind() == io::ErrorKind::Interrupted => Ok(false),
+				Err(e) => Err(e),
+			};
+		}
+		let sink = self.sink.as_mut().expect("polled for writing");
+		match sink.write(&self.pending) {
+			Ok(n) => {
+				self.pending.drain(..n);
+			}
+			Err(e) if e.kind() == io::ErrorKind::BrokenPipe => {
+				// The party stopped reading, what it is sent from now on is discarded.
+				self.sink = None;
+				self.pending.clear();
+			}
+			Err(e)
+				if matches!(
+					e.kind(),
+					io::ErrorKind::WouldBlock | io::ErrorKind::Interrupted
+				) => {}
+			Err(e) => return Err(e),
+		}
+		Ok(false)
+	}
+}
+
+/// How an interaction ended.
+pub struct Ending {
+	/// Whether the interaction stalled, in which case both parties were killed.
+	pub stalled: bool,
+	/// The party which closed its output first, which tells the party that stopped first when
+	/// the other fails after it.
+	pub closed_first: Option<Party>,
+}
+
+/// Passes data between the parties of an interaction on the current thread until both channels
+/// are closed, recording it in the transcript. The process groups of `ids` are killed if the
+/// interaction stalls.
+pub fn relay(
+	mut channels: [Channel; 2],
+	transcript: &mut Vec<(Party, Vec<u8>)>,
+	buffer: usize,
+	limits: &Limits,
+	ids: [u32; 2],
+) -> io::Result<Ending> {
+	let mut buf = vec![0; buffer];
+	let started = Instant::now();
+	let mut last = started;
+	let mut ending = Ending {
+		stalled: false,
+		closed_first: None,
+	};
+	loop {
+		let (polled, mut fds): (Vec<_>, Vec<_>) = channels
+			.iter_mut()
+			.enumerate()
+			.filter_map(|(i, channel)| Some((i, channel.interest()?)))
+			.unzip();
+		if fds.is_empty() {
+			return Ok(ending);
+		}
+		let timeout = WATCHDOG_INTERVAL.as_millis() as c_int;
+		if unsafe { poll(fds.as_mut_ptr(), fds.len() as c_ulong, timeout) } < 0 {
+			let error = io::Error::last_os_error();
+			if error.kind() != io::ErrorKind::Interrupted {
+				return Err(error);
+			}
+		}
+		for (i, fd) in polled.into_iter().zip(&fds) {
+			if fd.revents != 0 && channels[i].serve(&mut buf, transcript)? {
+				last = Instant::now();
+			}
+			if channels[i].source.is_none() && ending.closed_first.is_none() {
+				// The data is tagged with the party receiving it, so the other one closed.
+				ending.closed_first = Some(channels[i].party.other());
+			}
+		}
+		if !ending.stalled && limits.stalled(started.elapsed(), last.elapsed()) {
+			ending.stalled = true;
+			// Closes the pipes of both, which ends the channels.
+			ids.into_iter().for_each(limits::kill_group);
+		}
+	}
+}
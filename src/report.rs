@@ -0,0 +1,150 @@
+use std::{
+	fmt,
+	io::{self, Write as _},
+	path::Path,
+};
+
+use crate::{
+	Failure, Session,
+	args::{self, ReportFormat},
+	json::Json,
+};
+
+/// The files saved for a failure, named by what they contain.
+pub type Artifacts = Vec<(&'static str, String)>;
+
+/// The machine-readable report of the session. Seeds are strings, as they do not fit in the
+/// integers many JSON parsers support.
+pub fn json(session: &Session, failures: &[(&Failure, Artifacts)], exit_code: u8) -> Json {
+	let verdict = if !failures.is_empty() {
+		"failed"
+	} else if exit_code == 0 {
+		"passed"
+	} else {
+		"interrupted"
+	};
+	let (tests, _) = session.statistics.maximum();
+	Json::Object(vec![
+		("verdict", verdict.into()),
+		("exit_code", u64::from(exit_code).into()),
+		("seed", session.seed.to_string().into()),
+		("tests", tests.into()),
+		(
+			"failures",
+			Json::Array(
+				failures
+					.iter()
+					.map(|(failure, artifacts)| failure_json(failure, artifacts))
+					.collect(),
+			),
+		),
+	])
+}
+
+pub fn failure_json(failure: &Failure, artifacts: &Artifacts) -> Json {
+	let status = &failure.outcome.status;
+	Json::Object(vec![
+		("status", status.name().into()),
+		("message", status.message().into()),
+		("exit_code", u64::from(status.exit_code()).into()),
+		("seed", failure.seed.map(|seed| seed.to_string()).into()),
+		(
+			"iteration",
+			failure.iteration.map(|(_, iteration)| iteration).into(),
+		),
+		(
+			"artifacts",
+			Json::Object(
+				artifacts
+					.iter()
+					.map(|(name, path)| (*name, path.as_str().into()))
+					.collect(),
+			),
+		),
+		(
+			"timings",
+			Json::Array(
+				failure
+					.timings
+					.iter()
+					.map(|(program, usage)| {
+						Json::Object(vec![
+							("program", program.as_str().into()),
+							("wall_seconds", usage.wall.as_secs_f64().into()),
+							("cpu_seconds", usage.cpu.as_secs_f64().into()),
+							("memory_bytes", usage.memory.into()),
+						])
+					})
+					.collect(),
+			),
+		),
+	])
+}
+
+/// Escapes the message of a GitHub Actions workflow command, and with `property` also the value
+/// of one of its properties.
+fn escape(data: &str, property: bool) -> String {
+	let mut escaped = String::new();
+	for c in data.chars() {
+		match c {
+			'%' => escaped.push_str("%25"),
+			'\r' => escaped.push_str("%0D"),
+			'\n' => escaped.push_str("%0A"),
+			':' if property => escaped.push_str("%3A"),
+			',' if property => escaped.push_str("%2C"),
+			c => escaped.push(c),
+		}
+	}
+	escaped
+}
+
+/// Starts a collapsible group of the log when reporting to GitHub Actions. The commands are
+/// written to stderr like the report they group, so they stay in order.
+pub fn begin_group(args: &args::Args, title: &str) {
+	if let ReportFormat::Github = args.report {
+		eprintln!("\n::group::{}", escape(title, false));
+	}
+}
+
+pub fn end_group(args: &args::Args) {
+	if let ReportFormat::Github = args.report {
+		eprintln!("::endgroup::");
+	}
+}
+
+/// A GitHub Actions annotation of every failure on the source of the solution, or a notice
+/// that the session passed.
+pub fn github(session: &Session, failures: &[(&Failure, Artifacts)], exit_code: u8) -> String {
+	let file = escape(&session.args.language.source(&session.args.name), true);
+	let (tests, _) = session.statistics.maximum();
+	let mut annotations: Vec<_> = failures
+		.iter()
+		.map(|(failure, artifacts)| {
+			let input = artifacts
+				.iter()
+				.find(|(name, _)| *name == "input")
+				.map_or("", |(_, path)| path.as_str());
+			let message = format!(
+				"Counterexample found: {} ({input})",
+				failure.outcome.status.message()
+			);
+			format!("::error file={file}::{}", escape(&message, false))
+		})
+		.collect();
+	if failures.is_empty() {
+		let message = match exit_code {
+			0 => format!("No counterexample found in {tests} tests"),
+			_ => format!("Interrupted after {tests} tests"),
+		};
+		annotations.push(format!("::notice file={file}::{message}"));
+	}
+	annotations.join("\n")
+}
+
+/// Writes the report to the file, or to stdout if there is none.
+pub fn write(report: &impl fmt::Display, path: Option<&Path>) -> io::Result<()> {
+	match path {
+		Some(path) => std::fs::write(path, format!("{report}\n")),
+		None => writeln!(io::stdout(), "{report}"),
+	}
+}
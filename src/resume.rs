@@ -0,0 +1,305 @@
+use std::{
+	ffi::OsString,
+	io,
+	path::Path,
+	sync::{Mutex, OnceLock},
+	time::{Duration, Instant},
+};
+
+use crate::{
+	Session, args,
+	error::Error,
+	json::{Json, Value},
+};
+
+/// Where the state of the last session of the directory is saved while it runs.
+const PATH: &str = ".cpfuzz/session";
+
+/// How often the state is saved while fuzzing, which is how much an interrupted session loses at
+/// most when it is killed without a chance to save it.
+const SAVE_INTERVAL: Duration = Duration::from_secs(10);
+
+/// The state of a session saved for resuming it.
+pub struct Saved {
+	/// The arguments of cpfuzz the session was started with.
+	pub arguments: Vec<String>,
+	pub seed: u64,
+	/// Every test before this one had finished.
+	pub first: u64,
+	/// The tests after the first unfinished one which had finished.
+	pub finished: Vec<u64>,
+	/// The finished tests which were skipped as duplicates without counting against --max-tests.
+	pub duplicates: u64,
+	/// How long the session had been fuzzing.
+	pub elapsed: Duration,
+	pub tests: usize,
+	pub slowest: Option<(Duration, Option<u64>)>,
+	/// The inputs to mutate with --mutate, by their choices.
+	pub mutations: Option<Vec<Vec<u64>>>,
+	/// The blocks covered with --coverage and the inputs kept for covering new ones.
+	pub coverage: Option<(Vec<usize>, Vec<Vec<u64>>)>,
+}
+
+/// The arguments of cpfuzz after those of the saved session replaced a lone `--resume`.
+static ARGUMENTS: OnceLock<Vec<OsString>> = OnceLock::new();
+
+/// The arguments cpfuzz runs with, without `--resume` and the name of the subcommand cargo gives.
+pub fn current() -> Vec<String> {
+	let arguments = match ARGUMENTS.get() {
+		Some(arguments) => arguments.clone(),
+		None => std::env::args_os().collect(),
+	};
+	arguments
+		.into_iter()
+		.skip(1)
+		.enumerate()
+		.filter(|(i, argument)| !(*i == 0 && argument == "cpfuzz") && argument != "--resume")
+		.map(|(_, argument)| argument.to_string_lossy().into_owned())
+		.collect()
+}
+
+/// The choices of an input as a string of numbers, which unlike JSON numbers keeps all 64 bits.
+fn choices_json(choices: &[u64]) -> Json {
+	let numbers: Vec<_> = choices.iter().map(u64::to_string).collect();
+	numbers.join(" ").into()
+}
+
+fn choices(value: &Value) -> Option<Vec<u64>> {
+	value
+		.as_str()?
+		.split_ascii_whitespace()
+		.map(|number| number.parse().ok())
+		.collect()
+}
+
+fn inputs_json(inputs: &[Vec<u64>]) -> Json {
+	Json::Array(inputs.iter().map(|choices| choices_json(choices)).collect())
+}
+
+fn inputs(value: &Value) -> Option<Vec<Vec<u64>>> {
+	value.as_array()?.iter().map(choices).collect()
+}
+
+fn numbers<T: TryFrom<u64>>(value: &Value) -> Option<Vec<T>> {
+	value
+		.as_array()?
+		.iter()
+		.map(|number| T::try_from(number.as_f64()? as u64).ok())
+		.collect()
+}
+
+impl Saved {
+	fn json(&self) -> Json {
+		let seconds = |duration: Duration| Json::from(duration.as_secs_f64());
+		Json::Object(vec![
+			(
+				"arguments",
+				Json::Array(self.arguments.iter().map(|a| a.as_str().into()).collect()),
+			),
+			("seed", self.seed.to_string().into()),
+			("first", self.first.into()),
+			(
+				"finished",
+				Json::Array(self.finished.iter().map(|&test| test.into()).collect()),
+			),
+			("duplicates", self.duplicates.into()),
+			("elapsed_seconds", seconds(self.elapsed)),
+			("tests", self.tests.into()),
+			(
+				"slowest",
+				match self.slowest {
+					Some((wall, seed)) => Json::Object(vec![
+						("seconds", seconds(wall)),
+						("seed", seed.map(|seed| seed.to_string()).into()),
+					]),
+					None => Json::Null,
+				},
+			),
+			(
+				"mutations",
+				self.mutations.as_deref().map_or(Json::Null, inputs_json),
+			),
+			(
+				"coverage",
+				match &self.coverage {
+					Some((covered, kept)) => Json::Object(vec![
+						(
+							"covered",
+							Json::Array(covered.iter().map(|&block| block.into()).collect()),
+						),
+						("kept", inputs_json(kept)),
+					]),
+					None => Json::Null,
+				},
+			),
+		])
+	}
+
+	fn parse(value: &Value) -> Option<Saved> {
+		let seconds = |value: &Value| Duration::try_from_secs_f64(value.as_f64()?).ok();
+		let slowest = match value.get("slowest")? {
+			Value::Null => None,
+			slowest => Some((
+				seconds(slowest.get("seconds")?)?,
+				match slowest.get("seed")? {
+					Value::Null => None,
+					seed => Some(seed.as_str()?.parse().ok()?),
+				},
+			)),
+		};
+		let coverage = match value.get("coverage")? {
+			Value::Null => None,
+			coverage => Some((
+				numbers(coverage.get("covered")?)?,
+				inputs(coverage.get("kept")?)?,
+			)),
+		};
+		Some(Saved {
+			arguments: value
+				.get("arguments")?
+				.as_array()?
+				.iter()
+				.map(|argument| Some(argument.as_str()?.to_string()))
+				.collect::<Option<_>>()?,
+			seed: value.get("seed")?.as_str()?.parse().ok()?,
+			first: value.get("first")?.as_f64()? as u64,
+			finished: numbers(value.get("finished")?)?,
+			duplicates: value.get("duplicates")?.as_f64()? as u64,
+			elapsed: seconds(value.get("elapsed_seconds")?)?,
+			tests: value.get("tests")?.as_f64()? as usize,
+			slowest,
+			mutations: match value.get("mutations")? {
+				Value::Null => None,
+				mutations => Some(inputs(mutations)?),
+			},
+			coverage,
+		})
+	}
+}
+
+fn read() -> io::Result<Saved> {
+	let src = match std::fs::read_to_string(PATH) {
+		Err(e) if e.kind() == io::ErrorKind::NotFound => {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!("there is no session to resume, as {PATH} does not exist"),
+			));
+		}
+		src => src?,
+	};
+	Value::parse(&src)
+		.as_ref()
+		.and_then(Saved::parse)
+		.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("{PATH} is corrupt")))
+}
+
+/// The arguments of cpfuzz with those of the saved session in place of `--resume` when it is the
+/// only one, so `cpfuzz --resume` continues the last session.
+pub fn arguments(arguments: impl IntoIterator<Item = OsString>) -> io::Result<Vec<OsString>> {
+	let mut arguments: Vec<OsString> = arguments.into_iter().collect();
+	// Cargo gives the name of the subcommand as the first argument.
+	let start = match arguments.get(1) {
+		Some(argument) if argument == "cpfuzz" => 2,
+		_ => 1,
+	};
+	if arguments.len() == start + 1 && arguments[start] == "--resume" {
+		let saved = read()?;
+		arguments.splice(
+			start..start,
+			saved.arguments.into_iter().map(OsString::from),
+		);
+	}
+	let _ = ARGUMENTS.set(arguments.clone());
+	Ok(arguments)
+}
+
+/// Reads the state of the session to resume, which must have been started with the arguments
+/// cpfuzz runs with.
+pub fn load() -> Result<Saved, Error> {
+	let saved = read()?;
+	if saved.arguments != current() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			format!(
+				"the session to resume was started with other arguments: cpfuzz {}\n\
+				 Run `cpfuzz --resume` alone to continue it",
+				saved.arguments.join(" ")
+			),
+		)
+		.into());
+	}
+	Ok(saved)
+}
+
+/// Saves the state of the session to .cpfuzz/session as it runs.
+pub struct Checkpoint {
+	arguments: Vec<String>,
+	/// How long the session had been fuzzing before it was resumed.
+	resumed: Duration,
+	/// When the state was last saved.
+	saved: Mutex<Instant>,
+}
+
+impl Checkpoint {
+	/// A checkpoint of a session fuzzing generated tests, which are the ones it can resume.
+	pub fn new(args: &args::Args, resumed: Option<&Saved>) -> Option<Checkpoint> {
+		if args.inputs.is_some() || args.input.is_some() || args.replay_seed.is_some() {
+			return None;
+		}
+		Some(Checkpoint {
+			arguments: current(),
+			resumed: resumed.map_or(Duration::ZERO, |saved| saved.elapsed),
+			saved: Mutex::new(Instant::now()),
+		})
+	}
+
+	/// Saves the state of the session if it was not saved recently and another worker is not
+	/// saving it.
+	pub fn update(&self, session: &Session) -> io::Result<()> {
+		let Ok(mut saved) = self.saved.try_lock() else {
+			return Ok(());
+		};
+		if saved.elapsed() < SAVE_INTERVAL {
+			return Ok(());
+		}
+		*saved = Instant::now();
+		self.write(session)
+	}
+
+	/// Saves the state of the session as it stopped.
+	pub fn save(&self, session: &Session) -> io::Result<()> {
+		let _saved = self.saved.lock().expect("not poisoned");
+		self.write(session)
+	}
+
+	/// How long the session has been fuzzing over all of its runs, of which this one started then.
+	pub fn elapsed(&self, started: Instant) -> Duration {
+		self.resumed + started.elapsed()
+	}
+
+	fn write(&self, session: &Session) -> io::Result<()> {
+		let (first, finished) = session.budget.finished();
+		let (tests, slowest) = session.statistics.slowest();
+		let saved = Saved {
+			arguments: self.arguments.clone(),
+			seed: session.seed,
+			first,
+			finished,
+			duplicates: session.budget.duplicates(),
+			elapsed: self.elapsed(session.started),
+			tests,
+			slowest,
+			mutations: session.mutations.as_ref().map(|pool| pool.inputs()),
+			coverage: session
+				.coverage
+				.as_ref()
+				.map(|coverage| (coverage.covered(), coverage.kept.inputs())),
+		};
+		let path = Path::new(PATH);
+		std::fs::create_dir_all(path.parent().expect("has a directory"))?;
+		// Written next to it and renamed over it, so an interrupted write leaves the last state.
+		let staged = path.with_extension("tmp");
+		std::fs::write(&staged, format!("{}\n", saved.json()))?;
+		std::fs::rename(staged, path)
+	}
+}
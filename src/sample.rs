@@ -0,0 +1,63 @@
+use std::{
+	io::{self, Write as _},
+	mem,
+	process::ExitCode,
+};
+
+use crate::{
+	INTERRUPTED,
+	args::Args,
+	choices::{self, Choices},
+	error::Error,
+	generator::Generator,
+	interrupt, verbosity,
+};
+
+/// Number of inputs printed when --max-tests is not given.
+const DEFAULT_TESTS: u64 = 1;
+
+/// Prints the inputs the session generates to stdout without running the solution, with the seed
+/// of each on stderr. The test of --replay-seed is printed alone.
+pub fn sample(args: &Args) -> Result<ExitCode, Error> {
+	if args.inputs.is_some() || args.input.is_some() {
+		return Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"sample generates its inputs and can not be used with --inputs or --input",
+		)
+		.into());
+	}
+	let generator = Generator::new(args)?;
+	let seeds: Vec<u64> = match args.replay_seed {
+		Some(seed) => vec![seed],
+		None => {
+			let seed = args.seed.unwrap_or_else(|| fastrand::u64(..));
+			(0..args.max_tests.unwrap_or(DEFAULT_TESTS))
+				.map(|test| choices::test_seed(seed, test))
+				.collect()
+		}
+	};
+	let mut stdout = io::stdout().lock();
+	let mut buffer = Vec::new();
+	for seed in seeds {
+		let mut choices = Choices::random(seed).sized(args.size);
+		let test = generator.generate(&mut choices, mem::take(&mut buffer))?;
+		// A generator killed by the interrupt has not generated the whole input.
+		if interrupt::interrupted() {
+			stdout.flush()?;
+			return Ok(ExitCode::from(INTERRUPTED));
+		}
+		if !verbosity::quiet() {
+			// Flushed first, so the seed comes before its input on a terminal.
+			stdout.flush()?;
+			eprintln!("Seed {seed}");
+		}
+		stdout.write_all(&test.input)?;
+		// Ended, so the next input starts on a line of its own.
+		if !test.input.is_empty() && !test.input.ends_with(b"\n") {
+			writeln!(stdout)?;
+		}
+		buffer = test.input;
+	}
+	stdout.flush()?;
+	Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,136 @@
+use std::{sync::Mutex, time::Duration};
+
+use crate::{args, random::Random};
+
+/// Number of sizes tried, spaced geometrically from 1 to the largest.
+const LEVELS: u32 = 16;
+
+/// Tests run at the largest size tried so far before the next larger one is tried.
+const RAMP_TESTS: u64 = 100;
+
+struct Level {
+	size: i64,
+	tests: u64,
+	time: Duration,
+	/// Tests which were skipped as duplicates, of which tiny sizes have many.
+	duplicates: u64,
+}
+
+impl Level {
+	fn new(size: i64) -> Level {
+		Level {
+			size,
+			tests: 0,
+			time: Duration::ZERO,
+			duplicates: 0,
+		}
+	}
+
+	/// How often the size is picked once it is ramped up, such that every size gets about the same
+	/// share of the time and the fast ones the most tests. Sizes which mostly generate duplicates
+	/// are picked less.
+	fn weight(&self) -> f64 {
+		if self.tests == 0 {
+			return 0.0;
+		}
+		let mean = self.time.as_secs_f64() / self.tests as f64;
+		let unique = self.tests as f64 / (self.tests + self.duplicates) as f64;
+		unique / mean.max(1e-6)
+	}
+
+	fn ramped(&self) -> bool {
+		self.tests + self.duplicates >= RAMP_TESTS
+	}
+}
+
+/// The size of the generated inputs with --adaptive-size, which starts out tiny where logic bugs
+/// are found quickly and ramps up to the largest size over the session.
+pub struct Schedule {
+	/// The sizes which have been tried, and the next larger one.
+	levels: Mutex<Vec<Level>>,
+	/// Sizes which have not been tried yet, from the smallest.
+	remaining: Mutex<Vec<i64>>,
+	largest: i64,
+}
+
+impl Schedule {
+	/// The schedule if --adaptive-size is given. A replayed seed is generated at the size given
+	/// with --size instead, as printed with the seed.
+	pub fn new(args: &args::Args) -> Option<Schedule> {
+		let largest = args
+			.size
+			.filter(|_| args.adaptive_size && args.replay_seed.is_none())?;
+		let mut sizes: Vec<i64> = (0..LEVELS)
+			.map(|level| {
+				(largest as f64)
+					.powf(level as f64 / (LEVELS - 1) as f64)
+					.round() as i64
+			})
+			.map(|size| size.clamp(1, largest))
+			.collect();
+		sizes.dedup();
+		sizes.reverse();
+		let first = sizes.pop().expect("there is at least one size");
+		Some(Schedule {
+			levels: Mutex::new(vec![Level::new(first)]),
+			remaining: Mutex::new(sizes),
+			largest,
+		})
+	}
+
+	/// The size of the test with this seed. While a size is ramping up half of the tests use it,
+	/// the others are spread over the smaller sizes by how fast they run.
+	pub fn size(&self, seed: u64) -> i64 {
+		let mut rng = Random::with_seed(!seed);
+		let levels = self.levels.lock().expect("not poisoned");
+		let newest = levels.last().expect("there is at least one size");
+		if !newest.ramped() && (levels.len() == 1 || rng.bool()) {
+			return newest.size;
+		}
+		let total: f64 = levels.iter().map(Level::weight).sum();
+		let mut picked = rng.f64() * total;
+		for level in levels.iter() {
+			picked -= level.weight();
+			if picked <= 0.0 {
+				return level.size;
+			}
+		}
+		newest.size
+	}
+
+	/// Records how long the solution ran on a test of the size, trying the next larger size once
+	/// the largest one tried so far has had enough tests.
+	pub fn record(&self, size: i64, wall: Duration) {
+		self.update(size, |level| {
+			level.tests += 1;
+			level.time += wall;
+		});
+	}
+
+	/// Records that a test of the size was skipped as a duplicate.
+	pub fn duplicate(&self, size: i64) {
+		self.update(size, |level| level.duplicates += 1);
+	}
+
+	fn update(&self, size: i64, update: impl FnOnce(&mut Level)) {
+		let mut levels = self.levels.lock().expect("not poisoned");
+		let Some(level) = levels.iter_mut().find(|level| level.size == size) else {
+			return;
+		};
+		update(level);
+		if levels.last().expect("there is at least one size").ramped()
+			&& let Some(size) = self.remaining.lock().expect("not poisoned").pop()
+		{
+			levels.push(Level::new(size));
+		}
+	}
+
+	/// The largest size tried so far and the largest size to try.
+	pub fn reached(&self) -> (i64, i64) {
+		let levels = self.levels.lock().expect("not poisoned");
+		(
+			levels.last().expect("there is at least one size").size,
+			self.largest,
+		)
+	}
+}
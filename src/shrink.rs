@@ -0,0 +1,129 @@
+/// Upper bound on the number of candidates tried, as every candidate costs a full run.
+const MAX_ATTEMPTS: usize = 5000;
+
+/// Whether `a` is a simpler choice sequence than `b`, comparing by length and then
+/// lexicographically.
+fn simpler(a: &[u64], b: &[u64]) -> bool {
+	(a.len(), a) < (b.len(), b)
+}
+
+struct Shrinker<T, F> {
+	current: Vec<u64>,
+	value: T,
+	attempts: usize,
+	interesting: F,
+}
+
+impl<T, E, F: FnMut(Vec<u64>) -> Result<Option<(Vec<u64>, T)>, E>> Shrinker<T, F> {
+	/// Tries a candidate, keeping it if it is still interesting and simpler than the current one.
+	fn attempt(&mut self, candidate: Vec<u64>) -> Result<bool, E> {
+		if self.attempts >= MAX_ATTEMPTS || !simpler(&candidate, &self.current) {
+			return Ok(false);
+		}
+		self.attempts += 1;
+		match (self.interesting)(candidate)? {
+			Some((used, value)) if simpler(&used, &self.current) => {
+				self.current = used;
+				self.value = value;
+				Ok(true)
+			}
+			_ => Ok(false),
+		}
+	}
+
+	fn delete_chunks(&mut self) -> Result<bool, E> {
+		let mut improved = false;
+		for size in [8, 4, 2, 1] {
+			let mut i = 0;
+			while i + size <= self.current.len() {
+				let mut candidate = self.current.clone();
+				candidate.drain(i..i + size);
+				if self.attempt(candidate)? {
+					improved = true;
+				} else {
+					i += 1;
+				}
+			}
+		}
+		Ok(improved)
+	}
+
+	/// Binary searches each choice towards zero.
+	fn minimize_choices(&mut self) -> Result<bool, E> {
+		let mut improved = false;
+		let mut i = 0;
+		while i < self.current.len() {
+			let mut lower = 0;
+			while let Some(&value) = self.current.get(i)
+				&& lower < value
+			{
+				let mid = lower + (value - lower) / 2;
+				let mut candidate = self.current.clone();
+				candidate[i] = mid;
+				if self.attempt(candidate)? {
+					improved = true;
+				} else {
+					lower = mid + 1;
+				}
+			}
+			i += 1;
+		}
+		Ok(improved)
+	}
+}
+
+/// Shrinks a choice sequence as long as `interesting` keeps accepting the candidates.
+///
+/// `interesting` regenerates and runs a test from the candidate, returning the choices that were
+/// actually used together with the test if it still fails the same way. Returns the simplest
+/// accepted test.
+pub fn shrink<T, E>(
+	choices: Vec<u64>,
+	value: T,
+	interesting: impl FnMut(Vec<u64>) -> Result<Option<(Vec<u64>, T)>, E>,
+) -> Result<T, E> {
+	let mut shrinker = Shrinker {
+		current: choices,
+		value,
+		attempts: 0,
+		interesting,
+	};
+	while shrinker.minimize_choices()? | shrinker.delete_chunks()? {}
+	Ok(shrinker.value)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn simpler_choices() {
+		assert!(simpler(&[5], &[0, 0]));
+		assert!(simpler(&[1, 2], &[1, 3]));
+		assert!(!simpler(&[1, 3], &[1, 3]));
+	}
+
+	#[test]
+	fn shrinks_to_simplest() {
+		// Interesting while a choice is at least 10, using the choices up to the first such one.
+		let interesting = |choices: Vec<u64>| -> Result<_, ()> {
+			let used = choices
+				.iter()
+				.position(|&choice| choice >= 10)
+				.map(|i| choices[..=i].to_vec());
+			Ok(used.map(|used| (used.clone(), used)))
+		};
+		let choices = vec![3, 8, 1, 17, 20, 4];
+		assert_eq!(shrink(choices.clone(), choices, interesting), Ok(vec![10]));
+	}
+
+	#[test]
+	fn errors_stop_shrinking() {
+		let mut attempts = 0;
+		let shrunk = shrink(vec![1, 2], (), |_| {
+			attempts += 1;
+			Err::<Option<(Vec<u64>, ())>, _>("failed")
+		});
+		assert_eq!((shrunk, attempts), (Err("failed"), 1));
+	}
+}
@@ -0,0 +1,21 @@
+/// Describes the signal which terminated a process, such as `SIGSEGV (segmentation fault)`.
+pub fn describe(signal: i32) -> String {
+	let (name, meaning) = match signal {
+		4 => ("SIGILL", "illegal instruction"),
+		5 => ("SIGTRAP", "trap"),
+		6 => ("SIGABRT", "assertion failed or aborted"),
+		#[cfg(target_os = "linux")]
+		7 => ("SIGBUS", "bus error"),
+		#[cfg(not(target_os = "linux"))]
+		10 => ("SIGBUS", "bus error"),
+		8 => ("SIGFPE", "division by zero or other arithmetic error"),
+		9 => ("SIGKILL", "killed"),
+		11 => ("SIGSEGV", "segmentation fault"),
+		13 => ("SIGPIPE", "broken pipe"),
+		15 => ("SIGTERM", "terminated"),
+		#[cfg(target_os = "linux")]
+		24 => ("SIGXCPU", "cpu time limit exceeded"),
+		_ => return format!("signal {signal}"),
+	};
+	format!("{name} ({meaning})")
+}
@@ -0,0 +1,716 @@
+use std::{
+	fmt::{self, Write as _},
+	io,
+	path::{Path, PathBuf},
+	process::{Command, ExitCode, Stdio},
+};
+
+use crate::{args, error::Error, listen};
+
+/// Names given to the numbers of a specification the statement does not name, in order.
+const NAMES: [&str; 8] = ["n", "m", "k", "q", "x", "y", "z", "w"];
+
+/// A bound of a number: a constant, or an earlier number of the input.
+#[derive(Clone, PartialEq)]
+enum Bound {
+	Number(i64),
+	Name(String),
+}
+
+impl fmt::Display for Bound {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Bound::Number(number) => write!(f, "{number}"),
+			Bound::Name(name) => write!(f, "{name}"),
+		}
+	}
+}
+
+/// A number or array of the input as the statement names and bounds it.
+struct Constrained {
+	name: String,
+	/// The number an array is as long as, as in `a_1, a_2, ..., a_n`.
+	length: Option<String>,
+	/// The bounds in the order the statement gives them, of which the first bounding the number by
+	/// a constant or an earlier number is used.
+	lower: Vec<Bound>,
+	higher: Vec<Bound>,
+}
+
+/// What the input format of a statement tells about the numbers of the input.
+#[derive(Default)]
+pub struct Constraints {
+	/// The numbers in the order the statement mentions them.
+	scalars: Vec<Constrained>,
+	arrays: Vec<Constrained>,
+}
+
+impl Constraints {
+	fn entry(&mut self, name: &str, array: bool) -> &mut Constrained {
+		let list = match array {
+			true => &mut self.arrays,
+			false => &mut self.scalars,
+		};
+		let i = match list.iter().position(|constrained| constrained.name == name) {
+			Some(i) => i,
+			None => {
+				list.push(Constrained {
+					name: name.to_string(),
+					length: None,
+					lower: Vec::new(),
+					higher: Vec::new(),
+				});
+				list.len() - 1
+			}
+		};
+		&mut list[i]
+	}
+
+	fn contains(&self, name: &str) -> bool {
+		self.scalars
+			.iter()
+			.chain(&self.arrays)
+			.any(|constrained| constrained.name == name)
+	}
+
+	/// Reads the constraints of a formula such as `1 \le n, m \le 2 \cdot 10^5`. The variables of
+	/// a formula are mentioned even without constraints, unlike single letters of plain text.
+	fn read(&mut self, formula: &str, tex: bool) {
+		let tokens = lex(formula);
+		for chain in tokens.split(|token| *token == Token::Other) {
+			// The operands of the chain with the comparisons between them.
+			let mut operands = vec![Vec::new()];
+			let mut comparisons = Vec::new();
+			for token in chain {
+				match token {
+					Token::Less | Token::LessEqual | Token::Greater | Token::GreaterEqual => {
+						comparisons.push(token);
+						operands.push(Vec::new());
+					}
+					_ => operands.last_mut().expect("not empty").push(token.clone()),
+				}
+			}
+			for operand in operands.iter().filter(|_| tex || !comparisons.is_empty()) {
+				for (name, length, array) in names(operand) {
+					let entry = self.entry(&name, array);
+					if entry.length.is_none() {
+						entry.length = length;
+					}
+				}
+			}
+			for (i, comparison) in comparisons.into_iter().enumerate() {
+				let (smaller, larger) = match comparison {
+					Token::Less | Token::LessEqual => (&operands[i], &operands[i + 1]),
+					_ => (&operands[i + 1], &operands[i]),
+				};
+				let strict = matches!(comparison, Token::Less | Token::Greater);
+				let adjust = |bound: Option<Bound>, by: i64| match (bound, strict) {
+					(bound, false) => bound,
+					(Some(Bound::Number(number)), true) => {
+						number.checked_add(by).map(Bound::Number)
+					}
+					_ => None,
+				};
+				if let Some(lower) = adjust(bound(smaller), 1) {
+					for (name, _, array) in names(larger) {
+						self.entry(&name, array).lower.push(lower.clone());
+					}
+				}
+				if let Some(higher) = adjust(bound(larger), -1) {
+					for (name, _, array) in names(smaller) {
+						self.entry(&name, array).higher.push(higher.clone());
+					}
+				}
+			}
+		}
+	}
+}
+
+#[derive(Clone, PartialEq)]
+enum Token {
+	Number(i64),
+	/// A variable with its subscript, such as `a_i`.
+	Name(String, Option<String>),
+	Less,
+	LessEqual,
+	Greater,
+	GreaterEqual,
+	Comma,
+	Dots,
+	Plus,
+	Minus,
+	Times,
+	Power,
+	Open,
+	Close,
+	/// Anything else, which ends a chain of comparisons.
+	Other,
+}
+
+/// Splits a formula of TeX, or the plain text of an older statement, into tokens. Words longer than
+/// a letter are not variables in TeX.
+fn lex(formula: &str) -> Vec<Token> {
+	let chars: Vec<char> = formula.chars().collect();
+	let mut tokens = Vec::new();
+	let mut i = 0;
+	// The digits of a number may be grouped by thin spaces or commas, as in `200\,000`.
+	let grouped = |i: usize| {
+		let separator = ["\\,", ",", "{,}", "\\ "]
+			.into_iter()
+			.find(|separator| chars[i..].starts_with(&separator.chars().collect::<Vec<_>>()))?;
+		let digits = i + separator.chars().count();
+		let group = chars.get(digits..digits + 3)?;
+		let after = chars.get(digits + 3).is_some_and(char::is_ascii_digit);
+		(group.iter().all(char::is_ascii_digit) && !after).then_some(digits)
+	};
+	while i < chars.len() {
+		let c = chars[i];
+		if c.is_ascii_digit() {
+			let mut digits = String::new();
+			while i < chars.len() {
+				if chars[i].is_ascii_digit() {
+					digits.push(chars[i]);
+					i += 1;
+				} else if let Some(next) = grouped(i) {
+					i = next;
+				} else {
+					break;
+				}
+			}
+			tokens.push(digits.parse().map_or(Token::Other, Token::Number));
+			continue;
+		}
+		if c.is_ascii_alphabetic() {
+			let start = i;
+			while i < chars.len() && chars[i].is_ascii_alphabetic() {
+				i += 1;
+			}
+			if i - start > 1 {
+				tokens.push(Token::Other);
+				continue;
+			}
+			let mut subscript = None;
+			if chars.get(i) == Some(&'_') {
+				i += 1;
+				if chars.get(i) == Some(&'{') {
+					let end = chars[i..]
+						.iter()
+						.position(|&c| c == '}')
+						.map_or(chars.len(), |end| i + end);
+					subscript = Some(chars[i + 1..end].iter().collect::<String>());
+					i = (end + 1).min(chars.len());
+				} else if let Some(&c) = chars.get(i) {
+					subscript = Some(c.to_string());
+					i += 1;
+				}
+			}
+			tokens.push(Token::Name(c.to_string(), subscript));
+			continue;
+		}
+		i += 1;
+		let token = match c {
+			'\\' => {
+				let start = i;
+				while i < chars.len() && chars[i].is_ascii_alphabetic() {
+					i += 1;
+				}
+				let command: String = chars[start..i].iter().collect();
+				match command.as_str() {
+					"le" | "leq" | "leqslant" => Token::LessEqual,
+					"ge" | "geq" | "geqslant" => Token::GreaterEqual,
+					"lt" => Token::Less,
+					"gt" => Token::Greater,
+					"cdot" | "times" => Token::Times,
+					"ldots" | "dots" | "cdots" => Token::Dots,
+					// Spacing.
+					"" if matches!(chars.get(i), Some(',' | ';' | '!' | ' ')) => {
+						i += 1;
+						continue;
+					}
+					"quad" | "qquad" | "mathrm" | "mathit" | "text" => continue,
+					_ => Token::Other,
+				}
+			}
+			'<' => Token::Less,
+			'>' => Token::Greater,
+			'≤' | '⩽' => Token::LessEqual,
+			'≥' | '⩾' => Token::GreaterEqual,
+			',' => Token::Comma,
+			'…' => Token::Dots,
+			'.' if chars[i..].starts_with(&['.', '.']) => {
+				i += 2;
+				Token::Dots
+			}
+			'+' => Token::Plus,
+			'-' | '−' => Token::Minus,
+			'*' | '·' | '×' => Token::Times,
+			'^' => Token::Power,
+			'{' => Token::Open,
+			'}' => Token::Close,
+			'~' => continue,
+			c if c.is_whitespace() => continue,
+			_ => Token::Other,
+		};
+		tokens.push(token);
+	}
+	tokens
+}
+
+/// The variables an operand lists, such as `n, m` or `a_1, a_2, \ldots, a_n`, with the length of
+/// each array and whether it is one. None unless the operand is such a list.
+fn names(operand: &[Token]) -> Vec<(String, Option<String>, bool)> {
+	let mut names: Vec<(String, Option<String>, bool)> = Vec::new();
+	for item in operand.split(|token| *token == Token::Comma) {
+		match item {
+			[Token::Dots] => {}
+			[Token::Name(name, subscript)] => {
+				let length = subscript
+					.as_ref()
+					.filter(|subscript| subscript.len() == 1 && subscript != &"i")
+					.filter(|subscript| subscript.chars().all(|c| c.is_ascii_alphabetic()))
+					.cloned();
+				match names.iter_mut().find(|(other, _, _)| other == name) {
+					Some((_, other, _)) => *other = length.or(other.take()),
+					None => names.push((name.clone(), length, subscript.is_some())),
+				}
+			}
+			_ => return Vec::new(),
+		}
+	}
+	names
+}
+
+/// The value of an operand which is a constant such as `2 \cdot 10^5` or `-10^9`, or a single
+/// number of the input.
+fn bound(operand: &[Token]) -> Option<Bound> {
+	if let [Token::Name(name, None)] = operand {
+		return Some(Bound::Name(name.clone()));
+	}
+	let mut tokens = operand.iter().peekable();
+	let value = sum(&mut tokens)?;
+	tokens.next().is_none().then_some(Bound::Number(value))
+}
+
+type Tokens<'a> = std::iter::Peekable<std::slice::Iter<'a, Token>>;
+
+fn sum(tokens: &mut Tokens) -> Option<i64> {
+	let mut value = product(tokens)?;
+	loop {
+		match tokens.peek() {
+			Some(Token::Plus) => {
+				tokens.next();
+				value = value.checked_add(product(tokens)?)?;
+			}
+			Some(Token::Minus) => {
+				tokens.next();
+				value = value.checked_sub(product(tokens)?)?;
+			}
+			_ => return Some(value),
+		}
+	}
+}
+
+fn product(tokens: &mut Tokens) -> Option<i64> {
+	let mut value = power(tokens)?;
+	while tokens.next_if_eq(&&Token::Times).is_some() {
+		value = value.checked_mul(power(tokens)?)?;
+	}
+	Some(value)
+}
+
+fn power(tokens: &mut Tokens) -> Option<i64> {
+	let base = match tokens.next()? {
+		Token::Minus => return power(tokens)?.checked_neg(),
+		Token::Number(number) => *number,
+		Token::Open => {
+			let value = sum(tokens)?;
+			tokens.next_if_eq(&&Token::Close)?;
+			value
+		}
+		_ => return None,
+	};
+	if tokens.next_if_eq(&&Token::Power).is_none() {
+		return Some(base);
+	}
+	let exponent = power(tokens)?;
+	base.checked_pow(u32::try_from(exponent).ok()?)
+}
+
+/// The smallest power of ten at least the value, as a guess of the bound of a number from the
+/// values in the samples.
+fn round_up(value: i64) -> i64 {
+	let mut bound = 1i64;
+	while bound < value {
+		let Some(next) = bound.checked_mul(10) else {
+			return i64::MAX;
+		};
+		bound = next;
+	}
+	bound
+}
+
+/// Guesses a specification generating inputs shaped like the sample: a line of as many numbers as
+/// an earlier number becomes an array of that length, or a permutation if it is one, and every
+/// other number is a number of its own. The numbers are named and bounded as the constraints tell,
+/// in order, and otherwise get a range from the sample, which only pre-fills the constraints of the
+/// statement. None if the sample has tokens which are not numbers, which a specification can not
+/// generate. The names of the numbers whose range is guessed are pushed to `guessed`.
+pub fn scaffold(
+	sample: &str,
+	constraints: &Constraints,
+	guessed: &mut Vec<String>,
+) -> Option<String> {
+	let mut spec = String::new();
+	let mut scalars: Vec<(String, i64)> = Vec::new();
+	let mut arrays = 0;
+	let mut statement_scalars = constraints.scalars.iter();
+	let mut statement_arrays: Vec<_> = constraints.arrays.iter().map(Some).collect();
+	// The first bound by a constant or an earlier number.
+	let pick = |bounds: &[Bound], scalars: &[(String, i64)]| {
+		bounds
+			.iter()
+			.find(|bound| match bound {
+				Bound::Number(_) => true,
+				Bound::Name(name) => scalars.iter().any(|(scalar, _)| scalar == name),
+			})
+			.cloned()
+	};
+	let mut range =
+		|name: &str, bounds: Option<&Constrained>, scalars: &[(String, i64)], lowest, highest| {
+			let lower = bounds.and_then(|bounds| pick(&bounds.lower, scalars));
+			let higher = bounds.and_then(|bounds| pick(&bounds.higher, scalars));
+			if lower.is_none() || higher.is_none() {
+				guessed.push(name.to_string());
+			}
+			let lower = lower.unwrap_or(Bound::Number(lowest));
+			let higher = match (higher, &lower) {
+				(Some(higher), _) => higher,
+				(None, Bound::Number(lower)) => Bound::Number(round_up(highest).max(*lower)),
+				(None, _) => Bound::Number(round_up(highest)),
+			};
+			(lower, higher)
+		};
+	for line in sample.lines() {
+		let values: Vec<i64> = line
+			.split_ascii_whitespace()
+			.map(str::parse)
+			.collect::<Result<_, _>>()
+			.ok()?;
+		if values.is_empty() {
+			continue;
+		}
+		let length = scalars
+			.iter()
+			.rev()
+			.find(|(_, value)| values.len() > 1 && *value == values.len() as i64)
+			.map(|(name, _)| name.clone());
+		let mut tokens = Vec::new();
+		if let Some(length) = length {
+			let mut sorted = values.clone();
+			sorted.sort_unstable();
+			let array = statement_arrays
+				.iter_mut()
+				.find(|array| {
+					array.is_some_and(|array| array.length.as_ref().is_none_or(|l| *l == length))
+				})
+				.and_then(Option::take);
+			let name = match array {
+				Some(array) => array.name.clone(),
+				None => format!("{}{}", ["a", "b", "c", "d"][arrays % 4], arrays / 4 + 1),
+			};
+			arrays += 1;
+			let permutation = array.is_none_or(|array| {
+				array.higher.is_empty() || array.higher.contains(&Bound::Name(length.clone()))
+			});
+			if permutation && sorted.iter().copied().eq(1..=values.len() as i64) {
+				tokens.push(format!("perm {name} {length}"));
+			} else {
+				let (lower, higher) = range(
+					&name,
+					array,
+					&scalars,
+					sorted[0].min(1),
+					sorted[sorted.len() - 1],
+				);
+				tokens.push(format!("arr {name} {length} {lower} {higher}"));
+			}
+		} else {
+			for value in values {
+				let scalar = statement_scalars.next();
+				let name = match scalar {
+					Some(scalar) => scalar.name.clone(),
+					None => NAMES
+						.iter()
+						.map(|name| name.to_string())
+						.chain((scalars.len() + 1..).map(|i| format!("v{i}")))
+						.find(|name| {
+							!constraints.contains(name)
+								&& scalars.iter().all(|(scalar, _)| scalar != name)
+						})
+						.expect("infinitely many names"),
+				};
+				let (lower, higher) = range(&name, scalar, &scalars, value.min(1), value);
+				tokens.push(format!("int {name} {lower} {higher}"));
+				scalars.push((name, value));
+			}
+		}
+		writeln!(spec, "{}", tokens.join(" ")).expect("write to memory");
+	}
+	Some(spec)
+}
+
+/// The inside of the first element with the class, such as `input-specification`.
+fn element<'a>(html: &'a str, class: &str) -> Option<&'a str> {
+	let attribute = html.find(&format!("class=\"{class}\""))?;
+	let start = attribute + html[attribute..].find('>')? + 1;
+	let mut depth = 1;
+	let mut i = start;
+	while depth > 0 {
+		let next = i + html[i..].find('<')?;
+		if html[next..].starts_with("</div") {
+			depth -= 1;
+		} else if html[next..].starts_with("<div") {
+			depth += 1;
+		}
+		if depth == 0 {
+			return Some(&html[start..next]);
+		}
+		i = next + 1;
+	}
+	None
+}
+
+/// The text of HTML, in which line breaks are kept and the superscripts and subscripts of older
+/// statements become those of TeX.
+fn text(html: &str) -> String {
+	let mut text = String::new();
+	let mut rest = html;
+	while let Some(start) = rest.find('<') {
+		text.push_str(&rest[..start]);
+		let Some(end) = rest[start..].find('>') else {
+			rest = "";
+			break;
+		};
+		let tag = rest[start + 1..start + end].to_ascii_lowercase();
+		let name = tag
+			.split(|c: char| c.is_whitespace() || c == '/')
+			.find(|name| !name.is_empty());
+		match (tag.starts_with('/'), name) {
+			(_, Some("br")) | (true, Some("div" | "p")) => text.push('\n'),
+			(false, Some("sup")) => text.push_str("^{"),
+			(false, Some("sub")) => text.push_str("_{"),
+			(true, Some("sup" | "sub")) => text.push('}'),
+			_ => {}
+		}
+		rest = &rest[start + end + 1..];
+	}
+	text.push_str(rest);
+	decode(&text)
+}
+
+/// Decodes the character references of HTML.
+fn decode(text: &str) -> String {
+	let mut decoded = String::new();
+	let mut rest = text;
+	while let Some(start) = rest.find('&') {
+		decoded.push_str(&rest[..start]);
+		rest = &rest[start..];
+		let end = rest.find(';').filter(|&end| end <= 10);
+		let character = end.and_then(|end| match &rest[1..end] {
+			"lt" => Some('<'),
+			"gt" => Some('>'),
+			"amp" => Some('&'),
+			"quot" => Some('"'),
+			"apos" => Some('\''),
+			"nbsp" => Some(' '),
+			"le" => Some('≤'),
+			"ge" => Some('≥'),
+			"hellip" => Some('…'),
+			"mdash" => Some('—'),
+			"ndash" => Some('–'),
+			"minus" => Some('−'),
+			"middot" => Some('·'),
+			"times" => Some('×'),
+			reference => {
+				let number = reference.strip_prefix('#')?;
+				let code = match number.strip_prefix(['x', 'X']) {
+					Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+					None => number.parse().ok()?,
+				};
+				char::from_u32(code)
+			}
+		});
+		match (character, end) {
+			(Some(character), Some(end)) => {
+				decoded.push(character);
+				rest = &rest[end + 1..];
+			}
+			_ => {
+				decoded.push('&');
+				rest = &rest[1..];
+			}
+		}
+	}
+	decoded.push_str(rest);
+	decoded
+}
+
+/// The last number in the text, such as the 2 of "time limit per test2 seconds".
+fn last_number(text: &str) -> Option<f64> {
+	text.split(|c: char| !c.is_ascii_digit() && c != '.')
+		.filter_map(|number| number.parse().ok())
+		.next_back()
+}
+
+/// Downloads the page, or reads it if it is the path of a page saved from the browser.
+fn download(url: &str) -> Result<String, Error> {
+	if !url.starts_with("http://") && !url.starts_with("https://") {
+		return Ok(std::fs::read_to_string(url)?);
+	}
+	let output = Command::new("curl")
+		.args([
+			"--silent",
+			"--show-error",
+			"--fail",
+			"--location",
+			"--compressed",
+		])
+		.args(["--user-agent", "Mozilla/5.0 (compatible; cpfuzz)"])
+		.arg(url)
+		.stderr(Stdio::inherit())
+		.output()
+		.map_err(|e| match e.kind() {
+			io::ErrorKind::NotFound => {
+				io::Error::new(e.kind(), "downloading the statement needs curl")
+			}
+			_ => e,
+		})?;
+	if !output.status.success() {
+		return Err(io::Error::other(format!(
+			"downloading {url} failed, save the page from the browser and give its path instead"
+		))
+		.into());
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Bootstraps the specification of a Codeforces problem from its statement: the layout of the
+/// input is taken from the first sample, and the names and ranges of its numbers from the
+/// constraints the input format states.
+pub fn spec_from_url(args: &args::SpecFromUrlArgs) -> Result<ExitCode, Error> {
+	let html = download(&args.url)?;
+	let invalid = |message: &str| {
+		io::Error::new(
+			io::ErrorKind::InvalidData,
+			format!("{}: {message}", args.url),
+		)
+	};
+	let Some(specification) = element(&html, "input-specification") else {
+		return Err(
+			invalid("no input format found, is it the statement of a Codeforces problem?").into(),
+		);
+	};
+	let title = element(&html, "title")
+		.map(|title| text(title).trim().to_string())
+		.unwrap_or_default();
+	let specification = text(specification);
+	let mut constraints = Constraints::default();
+	match specification.contains("$$$") {
+		true => specification
+			.split("$$$")
+			.skip(1)
+			.step_by(2)
+			.for_each(|formula| constraints.read(formula, true)),
+		false => constraints.read(&specification, false),
+	}
+	let Some(sample) = element(&html, "sample-test").and_then(|tests| element(tests, "input"))
+	else {
+		return Err(invalid("no sample found to lay out the input from").into());
+	};
+	let sample = sample
+		.split_once("<pre")
+		.and_then(|(_, pre)| pre.split_once('>'))
+		.map_or(sample, |(_, pre)| pre);
+	let sample = text(sample);
+	let mut sample: Vec<&str> = sample
+		.lines()
+		.filter(|line| !line.trim().is_empty())
+		.collect();
+
+	// A specification can not repeat the test cases of a multi-test input: it generates one.
+	let mut notes = Vec::new();
+	let tests = constraints
+		.scalars
+		.first()
+		.filter(|scalar| scalar.name == "t");
+	if let Some(count) = tests
+		.filter(|_| specification.contains("test case"))
+		.and(sample.first())
+		.and_then(|line| line.trim().parse::<usize>().ok())
+		.filter(|&count| count > 0 && (sample.len() - 1).is_multiple_of(count))
+	{
+		sample.truncate(1 + (sample.len() - 1) / count);
+		sample[0] = "1";
+		constraints.scalars[0].lower = vec![Bound::Number(1)];
+		constraints.scalars[0].higher = vec![Bound::Number(1)];
+		notes.push("the input has test cases, of which the specification generates one");
+	}
+	let mut guessed = Vec::new();
+	let scaffolded = scaffold(&sample.join("\n"), &constraints, &mut guessed);
+	let Some(scaffolded) = scaffolded else {
+		return Err(invalid(
+			"the sample contains words, which a specification can not generate: write a generator",
+		)
+		.into());
+	};
+
+	let slug = listen::slug(&title);
+	let path = args
+		.output
+		.clone()
+		.unwrap_or_else(|| PathBuf::from(format!("{slug}.spec")));
+	if path == Path::new("-") {
+		print!("{scaffolded}");
+	} else {
+		if args.output.is_none() && path.exists() {
+			return Err(io::Error::new(
+				io::ErrorKind::AlreadyExists,
+				format!(
+					"{} exists already, give --output to write elsewhere",
+					path.display()
+				),
+			)
+			.into());
+		}
+		std::fs::write(&path, &scaffolded)?;
+	}
+	eprintln!("{title}");
+	if path != Path::new("-") {
+		eprintln!("Wrote {}, check it against the statement", path.display());
+	}
+	if !guessed.is_empty() {
+		eprintln!("Ranges guessed from the sample: {}", guessed.join(", "));
+	}
+	for note in notes {
+		eprintln!("Note: {note}");
+	}
+	let mut command = format!(
+		"cpfuzz {} {slug} {}",
+		clap::ValueEnum::to_possible_value(&args.language)
+			.expect("no variant is skipped")
+			.get_name(),
+		path.display()
+	);
+	if let Some(time_limit) =
+		element(&html, "time-limit").and_then(|limit| last_number(&text(limit)))
+	{
+		write!(command, " --time-limit {time_limit}s").expect("write to memory");
+	}
+	if let Some(memory_limit) =
+		element(&html, "memory-limit").and_then(|limit| last_number(&text(limit)))
+	{
+		write!(command, " --memory-limit {memory_limit}M").expect("write to memory");
+	}
+	eprintln!("Fuzz with: {command}");
+	Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,62 @@
+use std::{
+	io::IsTerminal as _,
+	sync::atomic::{AtomicBool, Ordering},
+};
+
+use ansi_term::{Color, Style};
+
+use crate::args::ColorMode;
+
+static ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Decides whether the report is coloured. By default it is when stderr is a terminal and
+/// `NO_COLOR` is not set.
+pub fn init(mode: ColorMode) {
+	let enabled = match mode {
+		ColorMode::Always => true,
+		ColorMode::Never => false,
+		ColorMode::Auto => {
+			std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none()
+		}
+	};
+	ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+/// The text in the style, or unstyled if colours are disabled.
+pub fn paint(style: Style, text: &str) -> String {
+	if ENABLED.load(Ordering::Relaxed) {
+		style.paint(text).to_string()
+	} else {
+		text.to_string()
+	}
+}
+
+pub fn failed(text: &str) -> String {
+	paint(Color::Red.bold(), text)
+}
+
+pub fn passed(text: &str) -> String {
+	paint(Color::Green.normal(), text)
+}
+
+pub fn dimmed(text: &str) -> String {
+	paint(Style::new().dimmed(), text)
+}
+
+/// Prints the heading of a section of the report, such as the input or the stderr.
+pub fn section(title: &str) {
+	eprintln!("\n{}", paint(Color::Cyan.bold(), &format!("== {title} ==")));
+}
+
+/// The number with its digits grouped in threes, such as `12 345`.
+pub fn separate_thousands(n: usize) -> String {
+	let digits = n.to_string();
+	let mut separated = String::new();
+	for (i, digit) in digits.chars().enumerate() {
+		if i > 0 && (digits.len() - i).is_multiple_of(3) {
+			separated.push(' ');
+		}
+		separated.push(digit);
+	}
+	separated
+}
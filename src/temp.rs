@@ -0,0 +1,210 @@
+use std::{
+	collections::HashSet,
+	fs::File,
+	io,
+	os::fd::AsRawFd as _,
+	path::{Path, PathBuf},
+	sync::{
+		Mutex,
+		atomic::{AtomicU64, Ordering},
+	},
+};
+
+static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// The temporary files and directories which exist, so they are removed however cpfuzz exits.
+static CREATED: Mutex<Option<HashSet<PathBuf>>> = Mutex::new(None);
+
+fn track(path: &Path) {
+	CREATED
+		.lock()
+		.expect("not poisoned")
+		.get_or_insert_default()
+		.insert(path.to_path_buf());
+}
+
+fn delete(path: &Path) {
+	let _ = match path.is_dir() {
+		true => std::fs::remove_dir_all(path),
+		false => std::fs::remove_file(path),
+	};
+}
+
+/// Removes a temporary file or directory, which is no longer tracked.
+fn remove(path: &Path) {
+	if let Some(created) = CREATED.lock().expect("not poisoned").as_mut() {
+		created.remove(path);
+	}
+	delete(path);
+}
+
+/// Removes every temporary file and directory which still exists.
+fn remove_all() {
+	let created = CREATED.lock().expect("not poisoned").take();
+	created.iter().flatten().for_each(|path| delete(path));
+}
+
+/// Removes the temporary files left when dropped, which is when cpfuzz returns normally, with an
+/// error or by panicking. Ctrl-C is handled from the start of [`crate::cli`], stopping builds and
+/// sessions alike, so it returns as well.
+pub struct Cleanup;
+
+impl Drop for Cleanup {
+	fn drop(&mut self) {
+		remove_all();
+	}
+}
+
+/// Exits cpfuzz with the exit code after removing the temporary files, which
+/// [`std::process::exit`] leaves as it does not run destructors.
+pub fn exit(code: i32) -> ! {
+	remove_all();
+	std::process::exit(code)
+}
+
+/// A file cpfuzz writes outside the temporary directory, such as the generator library in the
+/// working directory, which is removed when dropped or cpfuzz exits.
+pub struct Artifact {
+	path: PathBuf,
+}
+
+impl Artifact {
+	/// Tracks the file at the path, which need not exist yet.
+	pub fn new(path: impl Into<PathBuf>) -> Artifact {
+		let path = path.into();
+		track(&path);
+		Artifact { path }
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for Artifact {
+	fn drop(&mut self) {
+		remove(&self.path);
+	}
+}
+
+/// Inputs at least this large are staged in a file which the programs read as their stdin,
+/// instead of being written through a pipe for every run.
+pub const STAGE_THRESHOLD: usize = 1 << 20;
+
+/// A file in the temporary directory which is removed when dropped or cpfuzz exits. The name is
+/// unique within the session, so concurrent workers do not clobber each others files.
+pub struct TempFile {
+	path: PathBuf,
+}
+
+/// A path in the temporary directory which is unique within the session.
+fn unique(name: &str) -> PathBuf {
+	std::env::temp_dir().join(format!(
+		"cpfuzz-{}-{}-{name}",
+		std::process::id(),
+		COUNTER.fetch_add(1, Ordering::Relaxed),
+	))
+}
+
+impl TempFile {
+	pub fn new(name: &str, contents: &[u8]) -> io::Result<TempFile> {
+		let path = unique(name);
+		track(&path);
+		let file = TempFile { path };
+		std::fs::write(&file.path, contents)?;
+		Ok(file)
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for TempFile {
+	fn drop(&mut self) {
+		remove(&self.path);
+	}
+}
+
+/// An empty directory in the temporary directory which is removed with its contents when
+/// dropped or cpfuzz exits, for programs which write several files.
+pub struct TempDir {
+	path: PathBuf,
+}
+
+impl TempDir {
+	pub fn new(name: &str) -> io::Result<TempDir> {
+		let path = unique(name);
+		track(&path);
+		let dir = TempDir { path };
+		std::fs::create_dir(&dir.path)?;
+		Ok(dir)
+	}
+
+	pub fn path(&self) -> &Path {
+		&self.path
+	}
+}
+
+impl Drop for TempDir {
+	fn drop(&mut self) {
+		remove(&self.path);
+	}
+}
+
+#[cfg(target_os = "linux")]
+mod memory {
+	use std::{
+		ffi::{c_char, c_int, c_uint},
+		fs::File,
+		io::{self, Write as _},
+		os::fd::FromRawFd as _,
+	};
+
+	const MFD_CLOEXEC: c_uint = 1;
+
+	unsafe extern "C" {
+		fn memfd_create(name: *const c_char, flags: c_uint) -> c_int;
+	}
+
+	/// An anonymous file in memory with the contents.
+	pub fn create(contents: &[u8]) -> io::Result<File> {
+		let fd = unsafe { memfd_create(c"cpfuzz-input".as_ptr(), MFD_CLOEXEC) };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let mut file = unsafe { File::from_raw_fd(fd) };
+		file.write_all(contents)?;
+		Ok(file)
+	}
+}
+
+/// A large input written once to an anonymous file in memory, or to a temporary file where there
+/// are none, which every run of the test opens as its stdin.
+pub enum Staged {
+	#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+	Memory(File),
+	Disk(TempFile),
+}
+
+impl Staged {
+	/// Stages the input if it is at least [`STAGE_THRESHOLD`] bytes.
+	pub fn new(input: &[u8]) -> io::Result<Option<Staged>> {
+		if input.len() < STAGE_THRESHOLD {
+			return Ok(None);
+		}
+		#[cfg(target_os = "linux")]
+		if let Ok(file) = memory::create(input) {
+			return Ok(Some(Staged::Memory(file)));
+		}
+		Ok(Some(Staged::Disk(TempFile::new("input", input)?)))
+	}
+
+	/// Opens the input anew, so every run reads it from the start even when they run concurrently.
+	pub fn open(&self) -> io::Result<File> {
+		match self {
+			Staged::Memory(file) => File::open(format!("/proc/self/fd/{}", file.as_raw_fd())),
+			Staged::Disk(file) => File::open(file.path()),
+		}
+	}
+}
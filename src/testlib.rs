@@ -0,0 +1,111 @@
+use crate::{Exit, Run, Status};
+
+/// What testlib.h checkers exit with when they reject the output, by the verdict they report.
+const REJECTIONS: [(i32, &str); 4] = [
+	(1, "wrong answer"),
+	(2, "presentation error"),
+	(4, "unexpected output after the answer"),
+	(8, "unexpected end of the output"),
+];
+
+/// What testlib.h checkers exit with when they fail themselves, such as on a wrong answer file.
+const FAIL: i32 = 3;
+
+/// What testlib.h checkers exit with when they score the output, which is judged as accepted.
+const POINTS: i32 = 7;
+
+/// The first line of what the checker wrote to stderr, which is where testlib reports why, or the
+/// verdict if it wrote nothing.
+pub fn message(run: &Run, verdict: &str) -> String {
+	let stderr = String::from_utf8_lossy(&run.stderr);
+	match stderr.lines().next().map(str::trim) {
+		Some(line) if !line.is_empty() => line.to_string(),
+		_ => verdict.to_string(),
+	}
+}
+
+/// The status of a judge which ran out of memory, which says nothing about the solution it judged.
+pub fn out_of_memory(program: &str) -> Status {
+	Status::JudgeFailed {
+		program: program.to_string(),
+		message: "exceeded the memory limit".to_string(),
+	}
+}
+
+/// Judges the output by how a checker exited, understanding the exit codes of testlib.h. Other
+/// checkers reject the output by exiting with any other non-zero exit code, which is reported as
+/// `rejected`.
+pub fn status(program: &str, run: &Run, rejected: Status) -> Status {
+	match run.exit {
+		Exit::Success | Exit::Failure(Some(POINTS)) => Status::Ok,
+		Exit::Failure(Some(FAIL)) => Status::JudgeFailed {
+			program: program.to_string(),
+			message: message(run, "failed"),
+		},
+		Exit::Failure(Some(code)) => match REJECTIONS.iter().find(|&&(c, _)| c == code) {
+			Some((_, verdict)) => Status::Rejected {
+				program: program.to_string(),
+				message: message(run, verdict),
+			},
+			None => rejected,
+		},
+		Exit::Signal(signal) => Status::JudgeError {
+			program: program.to_string(),
+			signal,
+		},
+		Exit::MemoryLimit => out_of_memory(program),
+		_ => rejected,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn run(exit: Exit, stderr: &str) -> Run {
+		Run {
+			exit,
+			stdout: Vec::new(),
+			spilled: None,
+			stderr: stderr.as_bytes().to_vec(),
+			unconsumed: false,
+		}
+	}
+
+	fn status(exit: Exit, stderr: &str) -> Status {
+		super::status("checker", &run(exit, stderr), Status::CheckerFailed)
+	}
+
+	#[test]
+	fn exit_codes() {
+		assert!(matches!(status(Exit::Success, ""), Status::Ok));
+		assert!(matches!(
+			status(Exit::Failure(Some(POINTS)), ""),
+			Status::Ok
+		));
+		assert!(matches!(
+			status(Exit::Failure(Some(1)), "wrong answer 1st numbers differ\nmore\n"),
+			Status::Rejected { message, .. } if message == "wrong answer 1st numbers differ"
+		));
+		assert!(matches!(
+			status(Exit::Failure(Some(2)), ""),
+			Status::Rejected { message, .. } if message == "presentation error"
+		));
+		assert!(matches!(
+			status(Exit::Failure(Some(FAIL)), "FAIL answer is wrong"),
+			Status::JudgeFailed { message, .. } if message == "FAIL answer is wrong"
+		));
+		assert!(matches!(
+			status(Exit::Failure(Some(5)), ""),
+			Status::CheckerFailed
+		));
+		assert!(matches!(
+			status(Exit::Signal(11), ""),
+			Status::JudgeError { signal: 11, .. }
+		));
+		assert!(matches!(
+			status(Exit::MemoryLimit, ""),
+			Status::JudgeFailed { .. }
+		));
+	}
+}
@@ -0,0 +1,164 @@
+use std::{
+	collections::VecDeque,
+	fmt::Write as _,
+	io::{self, IsTerminal as _, Write as _},
+	sync::{
+		Mutex,
+		mpsc::{Receiver, RecvTimeoutError},
+	},
+	time::{Duration, Instant},
+};
+
+use ansi_term::Style;
+
+use crate::{style, usage::Statistics};
+
+/// How often the dashboard is redrawn.
+const REDRAW_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Number of the most recent events shown.
+const EVENTS: usize = 10;
+
+/// What a worker is doing.
+#[derive(Clone, Copy)]
+enum State {
+	Idle,
+	Running { seed: Option<u64>, since: Instant },
+	Done,
+}
+
+struct Worker {
+	state: State,
+	tests: u64,
+}
+
+/// A full screen view of a session with several workers, showing what every worker is doing, the
+/// aggregate throughput and a log of interesting events. It is drawn on the alternate screen of
+/// the terminal, so the normal report is printed as usual once the session ends.
+pub struct Dashboard {
+	started: Instant,
+	workers: Mutex<Vec<Worker>>,
+	events: Mutex<VecDeque<String>>,
+}
+
+impl Dashboard {
+	/// The dashboard of the workers, or None if stderr is not a terminal to draw it on.
+	pub fn new(workers: usize) -> Option<Dashboard> {
+		if !io::stderr().is_terminal() {
+			return None;
+		}
+		let workers = (0..workers)
+			.map(|_| Worker {
+				state: State::Idle,
+				tests: 0,
+			})
+			.collect();
+		Some(Dashboard {
+			started: Instant::now(),
+			workers: Mutex::new(workers),
+			events: Mutex::new(VecDeque::new()),
+		})
+	}
+
+	/// Notes that the worker started the test generated from the seed, or by mutation without one.
+	pub fn started(&self, worker: usize, seed: Option<u64>) {
+		let mut workers = self.workers.lock().expect("not poisoned");
+		workers[worker].state = State::Running {
+			seed,
+			since: Instant::now(),
+		};
+	}
+
+	/// Notes that the worker finished its test.
+	pub fn finished(&self, worker: usize) {
+		let mut workers = self.workers.lock().expect("not poisoned");
+		workers[worker].state = State::Idle;
+		workers[worker].tests += 1;
+	}
+
+	/// Notes that the worker stopped.
+	pub fn stopped(&self, worker: usize) {
+		self.workers.lock().expect("not poisoned")[worker].state = State::Done;
+	}
+
+	/// Adds an event to the log, such as a failure being found.
+	pub fn event(&self, event: String) {
+		let elapsed = self.started.elapsed().as_secs();
+		let mut events = self.events.lock().expect("not poisoned");
+		if events.len() == EVENTS {
+			events.pop_front();
+		}
+		events.push_back(format!("[{elapsed:>4} s] {event}"));
+	}
+
+	/// Draws the dashboard until the session finishes, restoring the screen and printing the
+	/// events afterwards.
+	pub fn run(&self, finished: Receiver<()>, statistics: &Statistics) -> io::Result<()> {
+		let mut stderr = io::stderr();
+		// Switch to the alternate screen and hide the cursor.
+		write!(stderr, "\x1b[?1049h\x1b[?25l")?;
+		let result = loop {
+			if let Err(e) = self.draw(statistics) {
+				break Err(e);
+			}
+			match finished.recv_timeout(REDRAW_INTERVAL) {
+				Err(RecvTimeoutError::Timeout) => {}
+				_ => break Ok(()),
+			}
+		};
+		write!(stderr, "\x1b[?25h\x1b[?1049l")?;
+		for event in self.events.lock().expect("not poisoned").iter() {
+			writeln!(stderr, "{event}")?;
+		}
+		stderr.flush()?;
+		result
+	}
+
+	fn draw(&self, statistics: &Statistics) -> io::Result<()> {
+		let (tests, maximum) = statistics.maximum();
+		let elapsed = self.started.elapsed();
+		let rate = statistics.rate(elapsed);
+		let mut screen = String::from("\x1b[H\x1b[2J");
+		let heading = format!(
+			"cpfuzz | tests: {} | rate: {rate:.0}/s | elapsed: {} s | max runtime: {:.1?}",
+			style::separate_thousands(tests),
+			elapsed.as_secs(),
+			maximum.unwrap_or_default()
+		);
+		writeln!(screen, "{}\n", style::paint(Style::new().bold(), &heading))
+			.expect("write to string");
+		for (i, worker) in self
+			.workers
+			.lock()
+			.expect("not poisoned")
+			.iter()
+			.enumerate()
+		{
+			let state = match worker.state {
+				State::Idle => style::dimmed("idle"),
+				State::Running { seed, since } => {
+					format!(
+						"running {} for {:.1?}",
+						crate::origin(seed),
+						since.elapsed()
+					)
+				}
+				State::Done => style::dimmed("done"),
+			};
+			writeln!(
+				screen,
+				"worker {:>2} | {:>9} tests | {state}",
+				i + 1,
+				style::separate_thousands(worker.tests as usize)
+			)
+			.expect("write to string");
+		}
+		writeln!(screen, "\nEvents:").expect("write to string");
+		for event in self.events.lock().expect("not poisoned").iter() {
+			writeln!(screen, "  {event}").expect("write to string");
+		}
+		let mut stderr = io::stderr();
+		stderr.write_all(screen.as_bytes())?;
+		stderr.flush()
+	}
+}
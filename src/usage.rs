@@ -0,0 +1,299 @@
+use std::{
+	cell::RefCell,
+	ffi::{c_int, c_long},
+	io,
+	os::unix::process::ExitStatusExt as _,
+	process::{Child, ExitStatus},
+	sync::Mutex,
+	time::Duration,
+};
+
+use crate::random::Random;
+
+#[repr(C)]
+struct TimeVal {
+	seconds: c_long,
+	#[cfg(target_os = "linux")]
+	microseconds: c_long,
+	#[cfg(not(target_os = "linux"))]
+	microseconds: i32,
+}
+
+impl TimeVal {
+	fn duration(&self) -> Duration {
+		Duration::from_secs(self.seconds as u64) + Duration::from_micros(self.microseconds as u64)
+	}
+}
+
+#[repr(C)]
+struct RUsage {
+	user: TimeVal,
+	system: TimeVal,
+	max_rss: c_long,
+	rest: [c_long; 13],
+}
+
+/// The unit of the maximum resident set size.
+#[cfg(target_os = "linux")]
+const MAX_RSS_UNIT: u64 = 1024;
+#[cfg(not(target_os = "linux"))]
+const MAX_RSS_UNIT: u64 = 1;
+
+unsafe extern "C" {
+	fn wait4(pid: c_int, status: *mut c_int, options: c_int, rusage: *mut RUsage) -> c_int;
+}
+
+/// How long a program ran and how much memory it used.
+#[derive(Clone, Copy)]
+pub struct Usage {
+	pub wall: Duration,
+	pub cpu: Duration,
+	/// Maximum resident set size in bytes.
+	pub memory: u64,
+}
+
+impl Usage {
+	pub fn describe(&self) -> String {
+		format!(
+			"{:.1?} wall, {:.1?} cpu, {} memory",
+			self.wall,
+			self.cpu,
+			describe_memory(self.memory)
+		)
+	}
+}
+
+pub fn describe_memory(bytes: u64) -> String {
+	format!("{:.1} MiB", bytes as f64 / (1 << 20) as f64)
+}
+
+/// Waits for the child to exit, returning its exit status and its usage except for the wall time.
+pub fn wait(child: &mut Child) -> io::Result<(ExitStatus, Duration, u64)> {
+	// Like Child::wait, close stdin first so the child does not wait for more input.
+	drop(child.stdin.take());
+	let pid = child.id() as c_int;
+	let mut status = 0;
+	let mut usage = RUsage {
+		user: TimeVal {
+			seconds: 0,
+			microseconds: 0,
+		},
+		system: TimeVal {
+			seconds: 0,
+			microseconds: 0,
+		},
+		max_rss: 0,
+		rest: [0; 13],
+	};
+	while unsafe { wait4(pid, &mut status, 0, &mut usage) } < 0 {
+		let error = io::Error::last_os_error();
+		if error.kind() != io::ErrorKind::Interrupted {
+			return Err(error);
+		}
+	}
+	let cpu = usage.user.duration() + usage.system.duration();
+	let memory = usage.max_rss as u64 * MAX_RSS_UNIT;
+	Ok((ExitStatus::from_raw(status), cpu, memory))
+}
+
+thread_local! {
+	/// The usage of every program run by this thread since it was last taken.
+	static RECORDED: RefCell<Vec<(String, Usage)>> = const { RefCell::new(Vec::new()) };
+}
+
+pub fn record(program: &str, usage: Usage) {
+	RECORDED.with_borrow_mut(|recorded| recorded.push((program.to_string(), usage)));
+}
+
+/// Takes the usage recorded by this thread, in the order the programs exited.
+pub fn take() -> Vec<(String, Usage)> {
+	RECORDED.take()
+}
+
+/// How long building every program took, summed over its builds.
+static BUILDS: Mutex<Vec<(String, Duration)>> = Mutex::new(Vec::new());
+
+pub fn record_build(program: &str, elapsed: Duration) {
+	let mut builds = BUILDS.lock().expect("not poisoned");
+	match builds.iter_mut().find(|(built, _)| built == program) {
+		Some((_, total)) => *total += elapsed,
+		None => builds.push((program.to_string(), elapsed)),
+	}
+}
+
+/// The programs built so far in the order they were first built.
+pub fn builds() -> Vec<(String, Duration)> {
+	BUILDS.lock().expect("not poisoned").clone()
+}
+
+/// Forgets the builds so far, as a new session with --watch starts.
+pub fn clear_builds() {
+	BUILDS.lock().expect("not poisoned").clear();
+}
+
+/// The running time of a test with its seed, unless it was mutated.
+type Sample = (Duration, Option<u64>);
+
+/// How many running times are kept for the percentiles. Once more are recorded the kept ones are
+/// a uniform sample of all of them, so an overnight session is summarized in bounded memory.
+const RESERVOIR: usize = 4096;
+
+/// The running times recorded by this run, summarized as they are recorded.
+struct Samples {
+	count: u64,
+	total: Duration,
+	fastest: Option<Duration>,
+	slowest: Option<Sample>,
+	/// The running times the percentiles are taken from.
+	reservoir: Vec<Duration>,
+	/// Chooses the running times replaced in the reservoir, the same for every session.
+	random: Random,
+}
+
+/// Running times of the primary over the session, with the seeds of the tests unless they were
+/// mutated.
+pub struct Statistics {
+	samples: Mutex<Samples>,
+	/// The number of tests of the runs of a resumed session before this one, and the slowest
+	/// of them. Only their running times of this run are summarized.
+	resumed: Mutex<(usize, Option<Sample>)>,
+}
+
+impl Statistics {
+	pub fn new() -> Statistics {
+		Statistics {
+			samples: Mutex::new(Samples {
+				count: 0,
+				total: Duration::ZERO,
+				fastest: None,
+				slowest: None,
+				reservoir: Vec::new(),
+				random: Random::with_seed(0),
+			}),
+			resumed: Mutex::new((0, None)),
+		}
+	}
+
+	/// Counts the tests of the session before it was resumed.
+	pub fn resume(&self, tests: usize, slowest: Option<(Duration, Option<u64>)>) {
+		*self.resumed.lock().expect("not poisoned") = (tests, slowest);
+	}
+
+	pub fn record(&self, wall: Duration, seed: Option<u64>) {
+		let mut samples = self.samples.lock().expect("not poisoned");
+		samples.count += 1;
+		samples.total = samples.total.saturating_add(wall);
+		samples.fastest = Some(samples.fastest.map_or(wall, |fastest| fastest.min(wall)));
+		if samples.slowest.is_none_or(|(slowest, _)| wall >= slowest) {
+			samples.slowest = Some((wall, seed));
+		}
+		// Reservoir sampling: the running time is kept with the probability the reservoir
+		// has of all those recorded.
+		if samples.reservoir.len() < RESERVOIR {
+			samples.reservoir.push(wall);
+		} else {
+			let count = samples.count;
+			let i = samples.random.up_to(count - 1);
+			if let Some(kept) = samples.reservoir.get_mut(i as usize) {
+				*kept = wall;
+			}
+		}
+	}
+
+	/// The number of running times recorded and the longest of them.
+	pub fn maximum(&self) -> (usize, Option<Duration>) {
+		let (tests, slowest) = self.slowest();
+		(tests, slowest.map(|(wall, _)| wall))
+	}
+
+	/// The number of running times recorded and the longest of them with the seed of its test.
+	pub fn slowest(&self) -> (usize, Option<(Duration, Option<u64>)>) {
+		let (resumed, slowest) = *self.resumed.lock().expect("not poisoned");
+		let samples = self.samples.lock().expect("not poisoned");
+		let slowest = samples
+			.slowest
+			.into_iter()
+			.chain(slowest)
+			.max_by_key(|(wall, _)| *wall);
+		(resumed + samples.count as usize, slowest)
+	}
+
+	/// The number of tests per second run since this run started, the elapsed duration ago.
+	pub fn rate(&self, elapsed: Duration) -> f64 {
+		let tests = self.samples.lock().expect("not poisoned").count;
+		tests as f64 / elapsed.as_secs_f64().max(f64::EPSILON)
+	}
+
+	/// The number of running times recorded, their mean, the fastest and the slowest, with the
+	/// kept running times in increasing order. None if none were recorded.
+	fn sorted(&self) -> Option<(u64, Duration, Duration, Duration, Vec<Duration>)> {
+		let samples = self.samples.lock().expect("not poisoned");
+		let (Some(fastest), Some((slowest, _))) = (samples.fastest, samples.slowest) else {
+			return None;
+		};
+		let mean = Duration::from_secs_f64(samples.total.as_secs_f64() / samples.count as f64);
+		let mut sorted = samples.reservoir.clone();
+		sorted.sort_unstable();
+		Some((samples.count, mean, fastest, slowest, sorted))
+	}
+
+	/// A line summarizing the running times, if any were recorded.
+	pub fn summary(&self) -> Option<String> {
+		let (count, mean, _, max, sorted) = self.sorted()?;
+		let p95 = sorted[(sorted.len() - 1) * 95 / 100];
+		Some(format!(
+			"Running time over {count} tests: mean {mean:.1?}, p95 {p95:.1?}, max {max:.1?}"
+		))
+	}
+
+	/// A line describing the distribution of the running times, if any were recorded.
+	pub fn distribution(&self) -> Option<String> {
+		let (count, _, min, max, sorted) = self.sorted()?;
+		let percentile = |p: usize| sorted[(sorted.len() - 1) * p / 100];
+		Some(format!(
+			"Running time over {count} tests: min {min:.1?}, median {:.1?}, p95 {:.1?}, max \
+			 {max:.1?}",
+			percentile(50),
+			percentile(95),
+		))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn statistics_are_bounded() {
+		let statistics = Statistics::new();
+		assert_eq!(statistics.summary(), None);
+		for ms in 1..=10_000 {
+			statistics.record(Duration::from_millis(ms), Some(ms));
+		}
+		assert_eq!(
+			statistics.samples.lock().unwrap().reservoir.len(),
+			RESERVOIR
+		);
+		assert_eq!(
+			statistics.slowest(),
+			(10_000, Some((Duration::from_secs(10), Some(10_000))))
+		);
+		let (count, mean, min, max, sorted) = statistics.sorted().unwrap();
+		assert_eq!(
+			(count, min, max),
+			(10_000, Duration::from_millis(1), Duration::from_secs(10))
+		);
+		assert_eq!(mean, Duration::from_micros(5_000_500));
+		// The kept running times are spread over all of them.
+		let p95 = sorted[(sorted.len() - 1) * 95 / 100];
+		assert!((9_000..=9_900).contains(&p95.as_millis()), "{p95:?}");
+	}
+
+	#[test]
+	fn resumed_slowest() {
+		let statistics = Statistics::new();
+		statistics.resume(5, Some((Duration::from_secs(3), Some(7))));
+		statistics.record(Duration::from_secs(1), None);
+		assert_eq!(statistics.maximum(), (6, Some(Duration::from_secs(3))));
+	}
+}
@@ -0,0 +1,102 @@
+use std::{
+	io,
+	sync::atomic::{AtomicU64, Ordering},
+};
+
+use crate::{
+	Exit,
+	args::{self, Language},
+	error::{Component, Context as _, Error},
+	kattis::{self, ACCEPTED, Package, REJECTED},
+	limits::Limits,
+	testlib,
+};
+
+/// A program which checks that an input follows the constraints of the problem.
+struct Validator {
+	program: String,
+	language: Language,
+	/// Whether it is an input validator of a problem package, which accepts the input by exiting
+	/// with 42 and rejects it with 43. Others accept it by exiting with code zero, as testlib.h
+	/// validators do.
+	package: bool,
+}
+
+/// The validators every generated input is run through before it is used, with the number of
+/// inputs they rejected.
+pub struct Validators {
+	validators: Vec<Validator>,
+	/// Whether a rejected input fails the session, rather than being skipped.
+	fail: bool,
+	rejected: AtomicU64,
+}
+
+impl Validators {
+	/// The validator given with --input-validator and those of the package, which are built.
+	pub fn new(args: &args::Args, package: Option<&Package>) -> Result<Validators, Error> {
+		let mut validators = Vec::new();
+		if let Some(program) = &args.input_validator {
+			args.language
+				.build(program)
+				.building(Component::InputValidator, program)?;
+			validators.push(Validator {
+				program: program.clone(),
+				language: args.language,
+				package: false,
+			});
+		}
+		for program in package.iter().flat_map(|package| &package.input_validators) {
+			validators.push(Validator {
+				program: program.clone(),
+				language: Language::Cpp,
+				package: true,
+			});
+		}
+		Ok(Validators {
+			validators,
+			fail: args.fail_on_invalid,
+			rejected: AtomicU64::new(0),
+		})
+	}
+
+	/// Runs the input through the validators, returning why the first one rejecting it did, or
+	/// None if all of them accept it.
+	pub fn validate(&self, limits: &Limits, input: &[u8]) -> Result<Option<String>, Error> {
+		for validator in &self.validators {
+			let program = &validator.program;
+			let run = validator
+				.language
+				.run(program, limits, input)
+				.running(Component::InputValidator, program)?;
+			let failed = |message| {
+				Err(io::Error::other(message)).running(Component::InputValidator, program)
+			};
+			let accepted = match (&run.exit, validator.package) {
+				(Exit::Success, false) | (Exit::Failure(Some(ACCEPTED)), true) => true,
+				(Exit::Failure(_), false) | (Exit::Failure(Some(REJECTED)), true) => false,
+				(_, false) => return failed(testlib::message(&run, "crashed")),
+				(_, true) => return failed(kattis::failure(&run)),
+			};
+			if !accepted {
+				let message = testlib::message(&run, "rejected the input");
+				return Ok(Some(format!("{program}: {message}")));
+			}
+		}
+		Ok(None)
+	}
+
+	/// Counts an input the validators rejected, returning whether it is the first one.
+	pub fn reject(&self) -> bool {
+		self.rejected.fetch_add(1, Ordering::Relaxed) == 0
+	}
+
+	/// How many generated inputs the validators rejected.
+	pub fn rejected(&self) -> u64 {
+		self.rejected.load(Ordering::Relaxed)
+	}
+
+	/// Whether a rejected input fails the session.
+	pub fn fail(&self) -> bool {
+		self.fail
+	}
+}
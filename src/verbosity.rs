@@ -0,0 +1,69 @@
+use std::{
+	ffi::OsStr,
+	process::Command,
+	sync::atomic::{AtomicU8, Ordering},
+};
+
+use crate::args;
+
+const QUIET: u8 = 0;
+const NORMAL: u8 = 1;
+const VERBOSE: u8 = 2;
+const DEBUG: u8 = 3;
+
+static LEVEL: AtomicU8 = AtomicU8::new(NORMAL);
+
+/// Sets how much is reported from --quiet and the number of --verbose flags.
+pub fn init(args: &args::Args) {
+	let level = if args.quiet {
+		QUIET
+	} else {
+		NORMAL + args.verbose.min(DEBUG - NORMAL)
+	};
+	LEVEL.store(level, Ordering::Relaxed);
+}
+
+/// Only the final verdict and the paths of the saved files are reported.
+pub fn quiet() -> bool {
+	LEVEL.load(Ordering::Relaxed) == QUIET
+}
+
+/// The verdict and timing of every test is reported.
+pub fn verbose() -> bool {
+	LEVEL.load(Ordering::Relaxed) >= VERBOSE
+}
+
+/// Every spawned command line is reported as well.
+pub fn debug() -> bool {
+	LEVEL.load(Ordering::Relaxed) >= DEBUG
+}
+
+/// Reports the command line about to be spawned when every command line is reported, quoted so
+/// it can be pasted into a shell.
+pub fn command(command: &Command) {
+	if !debug() {
+		return;
+	}
+	let mut line = Vec::new();
+	for (name, value) in command.get_envs() {
+		if let Some(value) = value {
+			line.push(format!("{}={}", name.to_string_lossy(), quote(value)));
+		}
+	}
+	line.push(quote(command.get_program()));
+	line.extend(command.get_args().map(quote));
+	eprintln!("$ {}", line.join(" "));
+}
+
+fn quote(word: &OsStr) -> String {
+	let word = word.to_string_lossy();
+	if !word.is_empty()
+		&& word
+			.chars()
+			.all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c))
+	{
+		word.into_owned()
+	} else {
+		format!("'{}'", word.replace('\'', r"'\''"))
+	}
+}
@@ -0,0 +1,194 @@
+use std::{
+	io,
+	path::PathBuf,
+	process::{ExitCode, ExitStatus},
+	sync::{
+		Mutex,
+		atomic::{AtomicBool, Ordering},
+	},
+	thread,
+	time::{Duration, SystemTime},
+};
+
+use crate::{
+	Session, args::Args, conclude, error::Error, interrupt, style, temp, usage, verbosity,
+};
+
+/// How often the sources are checked for changes.
+const POLL_INTERVAL: Duration = Duration::from_millis(300);
+
+static WATCHING: AtomicBool = AtomicBool::new(false);
+
+/// The programs whose sources did not change since they were last built, which are not built
+/// again.
+static UNCHANGED: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Whether the program is built already and its source did not change since.
+pub fn unchanged(program: &str) -> bool {
+	UNCHANGED
+		.lock()
+		.expect("not poisoned")
+		.iter()
+		.any(|unchanged| unchanged == program)
+}
+
+/// Makes a failed build an error of the session instead of exiting, for running several sessions.
+pub fn report_build_failures() {
+	WATCHING.store(true, Ordering::Relaxed);
+}
+
+/// The error of a failed build. Without --watch cpfuzz exits with the exit code of the build
+/// instead, while watching the error is reported and the build is retried after the next change.
+/// A build stopped by Ctrl-C is an error either way, so cpfuzz stops as interrupted.
+/// The error is attributed to the program by its caller.
+pub fn build_failed(exit_code: ExitStatus) -> io::Error {
+	if let Err(e) = interrupt::check() {
+		return e;
+	}
+	if !WATCHING.load(Ordering::Relaxed) {
+		temp::exit(exit_code.code().unwrap_or(1));
+	}
+	match exit_code.code() {
+		Some(code) => io::Error::other(format!("the build exited with code {code}")),
+		None => io::Error::other("the build was killed"),
+	}
+}
+
+/// The files the session is built from with when they were last modified, and the program built
+/// from each.
+#[derive(Clone)]
+struct Snapshot {
+	sources: Vec<(Option<String>, PathBuf, Option<SystemTime>)>,
+}
+
+impl Snapshot {
+	fn take(args: &Args) -> Snapshot {
+		let programs = [
+			Some(&args.name),
+			args.verify.as_ref(),
+			args.checker.as_ref(),
+			args.input_validator.as_ref(),
+		]
+		.into_iter()
+		.flatten()
+		.chain(&args.compare)
+		.chain(&args.interactive);
+		let mut sources: Vec<_> = programs
+			.map(|program| (Some(program.clone()), args.language.source(program).into()))
+			.collect();
+		if let Some(specification) = &args.specification {
+			sources.push(match (args.generate, args.testlib_generator) {
+				(true, _) => (None, format!("{specification}.cpp").into()),
+				(_, true) => (
+					Some(specification.clone()),
+					args.language.source(specification).into(),
+				),
+				_ => (None, specification.into()),
+			});
+		}
+		sources.extend(
+			args.specifications
+				.iter()
+				.map(|source| (None, source.path.clone().into())),
+		);
+		sources.extend(
+			args.generators
+				.iter()
+				.map(|source| (None, format!("{}.cpp", source.path).into())),
+		);
+		let sources = sources
+			.into_iter()
+			.map(|(program, path)| {
+				let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok();
+				(program, path, modified)
+			})
+			.collect();
+		Snapshot { sources }
+	}
+
+	/// The first source which changed since the other snapshot.
+	fn changed(&self, since: &Snapshot) -> Option<&PathBuf> {
+		self.sources
+			.iter()
+			.zip(&since.sources)
+			.find(|((_, _, modified), (_, _, before))| modified != before)
+			.map(|((_, path, _), _)| path)
+	}
+
+	/// The programs whose sources are the same in the other snapshot.
+	fn unchanged(&self, since: &Snapshot) -> Vec<String> {
+		self.sources
+			.iter()
+			.zip(&since.sources)
+			.filter(|((_, _, modified), (_, _, before))| modified.is_some() && modified == before)
+			.filter_map(|((program, _, _), _)| program.clone())
+			.collect()
+	}
+}
+
+/// Runs sessions until interrupted, starting a new one whenever a source changes: the programs
+/// which changed are rebuilt, the counterexamples of the previous session are run again before the
+/// corpus, and fuzzing resumes.
+pub fn watch(args: Args) -> Result<ExitCode, Error> {
+	report_build_failures();
+	let mut built: Option<Snapshot> = None;
+	let mut replay: Vec<PathBuf> = Vec::new();
+	let mut exit_code = 0;
+	loop {
+		let snapshot = Snapshot::take(&args);
+		*UNCHANGED.lock().expect("not poisoned") = match &built {
+			Some(built) => snapshot.unchanged(built),
+			None => Vec::new(),
+		};
+		let changed = AtomicBool::new(false);
+		usage::clear_builds();
+		match Session::new(args.clone()) {
+			Ok(mut session) => {
+				built = Some(snapshot.clone());
+				session
+					.replay
+					.extend(replay.iter().filter(|path| path.exists()).cloned());
+				let done = AtomicBool::new(false);
+				let failures = thread::scope(|scope| {
+					let watcher = scope.spawn(|| {
+						while !done.load(Ordering::Relaxed) {
+							thread::sleep(POLL_INTERVAL);
+							if Snapshot::take(&args).changed(&snapshot).is_some() {
+								changed.store(true, Ordering::Relaxed);
+								session.stop.store(true, Ordering::Relaxed);
+								break;
+							}
+						}
+					});
+					let failures = session.find_failures();
+					done.store(true, Ordering::Relaxed);
+					watcher.join().expect("does not panic");
+					failures
+				});
+				match failures {
+					_ if changed.load(Ordering::Relaxed) => {}
+					Ok(failures) => (exit_code, replay) = conclude(&session, &failures)?,
+					Err(e) => eprintln!("\n{} {e}", style::failed("Error:")),
+				}
+			}
+			Err(e) => eprintln!("\n{} {e}", style::failed("Error:")),
+		}
+		if interrupt::interrupted() {
+			return Ok(ExitCode::from(exit_code));
+		}
+		if !changed.load(Ordering::Relaxed) {
+			if !verbosity::quiet() {
+				eprintln!("\nWatching the sources for changes, press Ctrl-C to stop");
+			}
+			while Snapshot::take(&args).changed(&snapshot).is_none() {
+				if interrupt::interrupted() {
+					return Ok(ExitCode::from(exit_code));
+				}
+				thread::sleep(POLL_INTERVAL);
+			}
+		}
+		if let Some(path) = Snapshot::take(&args).changed(&snapshot) {
+			eprintln!("\n{} changed, starting over", path.display());
+		}
+	}
+}
@@ -0,0 +1,74 @@
+use std::{
+	fs::File,
+	io::{self, BufWriter, Write as _},
+	path::Path,
+};
+
+/// The CRC-32 of the data, as zip archives checksum their files with.
+fn crc32(data: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for &byte in data {
+		crc ^= byte as u32;
+		for _ in 0..8 {
+			crc = (crc >> 1) ^ (0xedb8_8320 & (crc & 1).wrapping_neg());
+		}
+	}
+	!crc
+}
+
+/// 1980-01-01, the earliest date of a zip archive, as the files are not dated.
+const DATE: u16 = 1 << 5 | 1;
+
+/// Writes a zip archive of the files, given by their paths in the archive, stored uncompressed.
+pub fn write(path: &Path, files: &[(String, Vec<u8>)]) -> io::Result<()> {
+	let too_large = || io::Error::new(io::ErrorKind::InvalidInput, "the archive exceeds 4 GiB");
+	let mut writer = BufWriter::new(File::create(path)?);
+	let mut central = Vec::new();
+	let mut offset = 0u32;
+	for (name, data) in files {
+		let crc = crc32(data);
+		let size = u32::try_from(data.len()).map_err(|_| too_large())?;
+		let name_length = u16::try_from(name.len()).map_err(|_| too_large())?;
+		// Version 2.0, no flags, stored, undated.
+		let common = [
+			&20u16.to_le_bytes()[..],
+			&0u16.to_le_bytes(),
+			&0u16.to_le_bytes(),
+			&0u16.to_le_bytes(),
+			&DATE.to_le_bytes(),
+			&crc.to_le_bytes(),
+			&size.to_le_bytes(),
+			&size.to_le_bytes(),
+			&name_length.to_le_bytes(),
+			&0u16.to_le_bytes(),
+		]
+		.concat();
+		let mut local = 0x0403_4b50u32.to_le_bytes().to_vec();
+		local.extend_from_slice(&common);
+		local.extend_from_slice(name.as_bytes());
+		writer.write_all(&local)?;
+		writer.write_all(data)?;
+		central.extend_from_slice(&0x0201_4b50u32.to_le_bytes());
+		central.extend_from_slice(&20u16.to_le_bytes());
+		central.extend_from_slice(&common);
+		// No comment, on the first disk and without attributes.
+		central.extend_from_slice(&[0; 10]);
+		central.extend_from_slice(&offset.to_le_bytes());
+		central.extend_from_slice(name.as_bytes());
+		offset = u32::try_from(local.len() + data.len())
+			.ok()
+			.and_then(|length| offset.checked_add(length))
+			.ok_or_else(too_large)?;
+	}
+	let entries = u16::try_from(files.len()).map_err(|_| too_large())?;
+	let central_size = u32::try_from(central.len()).map_err(|_| too_large())?;
+	writer.write_all(&central)?;
+	writer.write_all(&0x0605_4b50u32.to_le_bytes())?;
+	writer.write_all(&[0; 4])?;
+	writer.write_all(&entries.to_le_bytes())?;
+	writer.write_all(&entries.to_le_bytes())?;
+	writer.write_all(&central_size.to_le_bytes())?;
+	writer.write_all(&offset.to_le_bytes())?;
+	writer.write_all(&0u16.to_le_bytes())?;
+	writer.flush()
+}
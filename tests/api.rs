@@ -0,0 +1,109 @@
+use std::{path::PathBuf, sync::Mutex};
+
+use cpfuzz_core::{FuzzSession, args::Language};
+
+/// The sessions write their files to the working directory, which the tests share.
+static DIRECTORY: Mutex<()> = Mutex::new(());
+
+/// Runs the test in a fresh directory of its own holding the files.
+fn in_directory<T>(name: &str, files: &[(&str, &str)], test: impl FnOnce() -> T) -> T {
+	let _directory = DIRECTORY
+		.lock()
+		.unwrap_or_else(|poisoned| poisoned.into_inner());
+	let dir: PathBuf =
+		std::env::temp_dir().join(format!("cpfuzz-api-{name}-{}", std::process::id()));
+	let _ = std::fs::remove_dir_all(&dir);
+	std::fs::create_dir_all(&dir).unwrap();
+	for (file, contents) in files {
+		std::fs::write(dir.join(file), contents).unwrap();
+	}
+	let previous = std::env::current_dir().unwrap();
+	std::env::set_current_dir(&dir).unwrap();
+	let result = test();
+	std::env::set_current_dir(previous).unwrap();
+	let _ = std::fs::remove_dir_all(&dir);
+	result
+}
+
+const SUM: &str = "#include <iostream>\n\
+	int main() { long long n, x, s = 0; std::cin >> n; while (std::cin >> x) s += x; std::cout << s; }\n";
+
+/// Wrong whenever the first number is 7.
+const WRONG: &str = "#include <iostream>\n\
+	int main() { long long n, x, s = 0; std::cin >> n; bool seven = false;\n\
+	for (int i = 0; std::cin >> x; i++) { s += x; seven |= i == 0 && x == 7; }\n\
+	std::cout << s + seven; }\n";
+
+#[test]
+fn finds_a_counterexample() {
+	let files = [
+		("spec", "int n 1 3\narr a n 1 10"),
+		("wrong.cpp", WRONG),
+		("sum.cpp", SUM),
+	];
+	let counterexamples = in_directory("counterexample", &files, || {
+		FuzzSession::builder(Language::Cpp, "wrong")
+			.specification("spec")
+			.compare("sum")
+			.seed(1)
+			.max_tests(1000)
+			.run()
+	})
+	.expect("the session runs");
+	let [counterexample] = &counterexamples[..] else {
+		panic!("one counterexample is found");
+	};
+	assert_eq!(counterexample.kind, "different_outputs");
+	let minimized = counterexample.minimized.as_ref().expect("is minimized");
+	assert_eq!(
+		String::from_utf8_lossy(minimized).split_whitespace().nth(1),
+		Some("7")
+	);
+}
+
+#[test]
+fn passes_when_the_outputs_agree() {
+	let files = [
+		("spec", "int n 1 3\narr a n 1 10"),
+		("sum.cpp", SUM),
+		("also.cpp", SUM),
+	];
+	let counterexamples = in_directory("passes", &files, || {
+		FuzzSession::builder(Language::Cpp, "sum")
+			.specification("spec")
+			.compare("also")
+			.max_tests(50)
+			.run()
+	})
+	.expect("the session runs");
+	assert!(counterexamples.is_empty());
+}
+
+#[test]
+fn failed_build_is_an_error() {
+	let files = [
+		("spec", "int n 1 3"),
+		("broken.cpp", "int main() {"),
+		("sum.cpp", SUM),
+	];
+	let result = in_directory("build", &files, || {
+		FuzzSession::builder(Language::Cpp, "broken")
+			.specification("spec")
+			.compare("sum")
+			.run()
+	});
+	assert!(result.is_err());
+}
+
+#[test]
+fn missing_specification_is_an_error() {
+	let result = in_directory("specification", &[("sum.cpp", SUM)], || {
+		FuzzSession::builder(Language::Cpp, "sum")
+			.specification("missing.spec")
+			.run()
+	});
+	let Err(error) = result else {
+		panic!("the session does not run");
+	};
+	assert!(error.to_string().contains("does not exist"), "{error}");
+}